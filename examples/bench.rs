@@ -1,15 +1,41 @@
 use std::time::Instant;
-use an_ok_bstree::BSTree;
+use an_ok_bstree::{BinarySearchTree, IterativeBSTree, RecursiveBSTree};
+
+// 对实现了BinarySearchTree的树逐个插入n个键值对，返回耗时的毫秒数
+fn time_insert<T: BinarySearchTree<i32, i32>>(n: i32) -> u128 {
+    let now = Instant::now();
+    let mut tree = T::default();
+    for i in 0..n {
+        tree.insert(i, i);
+    }
+    now.elapsed().as_millis()
+}
 
 fn main() {
+    println!("Recursion: BSTree insert 10000 times took {} ms.", time_insert::<RecursiveBSTree<i32, i32>>(10000));
+    println!("No Recursion: BSTree insert 10000 times took {} ms.", time_insert::<IterativeBSTree<i32, i32>>(10000));
+
     let now  = Instant::now();
-    let mut tree = BSTree::new();
-    for i in 0..10000 {
-        tree.insert(i, i);
+    let mut tree = RecursiveBSTree::new();
+    tree.insert_many((0..10000).map(|i| (i, i)));
+    println!("Recursion: BSTree insert_many 10000 entries took {} ms.", now.elapsed().as_millis());
+
+    let now  = Instant::now();
+    let mut tree = IterativeBSTree::new();
+    tree.insert_many((0..10000).map(|i| (i, i)));
+    println!("No Recursion: BSTree insert_many 10000 entries took {} ms.", now.elapsed().as_millis());
+
+    #[cfg(feature = "rayon")]
+    {
+        use an_ok_bstree::BSTree;
+        let items: Vec<(i32, i32)> = (0..2_000_000).map(|i| (i, i)).collect();
+        let now = Instant::now();
+        let tree = BSTree::bulk_load(items.clone());
+        println!("BSTree bulk_load 2000000 entries took {} ms.", now.elapsed().as_millis());
+        drop(tree);
+        let now = Instant::now();
+        let tree = BSTree::from_sorted_vec(items);
+        println!("BSTree from_sorted_vec 2000000 entries took {} ms.", now.elapsed().as_millis());
+        drop(tree);
     }
-    let elapsed_time = now.elapsed();
-    #[cfg(feature = "no_recur")]
-    println!("No Recursion: BSTree insert 10000 times took {} ms.", elapsed_time.as_millis());
-    #[cfg(not(feature = "no_recur"))]
-    println!("Recursion: BSTree insert 10000 times took {} ms.", elapsed_time.as_millis());
-}
\ No newline at end of file
+}
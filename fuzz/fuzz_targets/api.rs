@@ -0,0 +1,61 @@
+#![no_main]
+//! 把输入的字节解释为一串操作，同时作用在两种实现的树和`BTreeMap`上，每一步都比较结果
+//!
+//! 每3个字节为一个操作：操作码、键、参数。运行方式：`cargo +nightly fuzz run api`
+
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Unbounded};
+use libfuzzer_sys::fuzz_target;
+
+macro_rules! check_against_model {
+    ($name:ident, $tree:ty) => {
+        fn $name(data: &[u8]) {
+            let mut tree = <$tree>::new();
+            let mut model = BTreeMap::new();
+            for op in data.chunks_exact(3) {
+                let (key, arg) = (op[1], op[2]);
+                match op[0] % 5 {
+                    0 => {
+                        tree.insert(key, arg);
+                        model.insert(key, arg);
+                    }
+                    1 => assert_eq!(tree.take(&key), model.remove_entry(&key)),
+                    2 => {
+                        let high = key.saturating_add(arg);
+                        let res = tree.fold_range(key..=high, Vec::new(), |mut res, k, v| {
+                            res.push((*k, *v));
+                            res
+                        });
+                        let expected: Vec<(u8, u8)> = model.range(key..=high).map(|(k, v)| (*k, *v)).collect();
+                        assert_eq!(res, expected);
+                    }
+                    3 => {
+                        // 在key处拆成两棵树，分别与模型比较后再拼接回去
+                        let (low, high) = std::mem::take(&mut tree).partition(|k, _| *k < key);
+                        let mut model_high = model.split_off(&key);
+                        assert!(low.inorder_iter().map(|(k, v)| (*k, *v)).eq(model.iter().map(|(k, v)| (*k, *v))));
+                        assert!(high.inorder_iter().map(|(k, v)| (*k, *v)).eq(model_high.iter().map(|(k, v)| (*k, *v))));
+                        tree = low;
+                        tree.extend_sorted(BTreeMap::from(high));
+                        model.append(&mut model_high);
+                    }
+                    _ => {
+                        assert_eq!(tree.get(&key), model.get(&key));
+                        assert_eq!(tree.successor(&key), model.range((Excluded(key), Unbounded)).next());
+                    }
+                }
+            }
+            assert!(tree.inorder_iter().eq(model.iter()));
+            assert_eq!(tree.min_pair(), model.iter().next());
+            assert_eq!(tree.max_pair(), model.iter().next_back());
+        }
+    };
+}
+
+check_against_model!(check_recursive, an_ok_bstree::RecursiveBSTree<u8, u8>);
+check_against_model!(check_iterative, an_ok_bstree::IterativeBSTree<u8, u8>);
+
+fuzz_target!(|data: &[u8]| {
+    check_recursive(data);
+    check_iterative(data);
+});
@@ -0,0 +1,225 @@
+//! `an_ok_bstree`的Python绑定，把`BSTree`导出为Python的`bstree.BSTree`类
+//!
+//! 用法与`dict`相似，但按键的升序遍历。键可以是任何能互相比较大小的Python对象，
+//! 与`sorted`对元素的要求相同。构建方式：`maturin develop`
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use an_ok_bstree::BSTree as Tree;
+use pyo3::exceptions::{PyKeyError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyIterator, PyList, PyTuple};
+
+thread_local! {
+    // 树操作过程中第一次失败的Python比较抛出的异常，由checked在操作结束后取出
+    static COMPARE_ERROR: RefCell<Option<PyErr>> = const { RefCell::new(None) };
+}
+
+// 记录比较时抛出的异常，只保留第一个
+fn record(err: PyErr) {
+    COMPARE_ERROR.with(|slot| {
+        slot.borrow_mut().get_or_insert(err);
+    });
+}
+
+// 执行一次树操作，期间有Python比较抛出异常时丢弃结果并返回该异常；
+// 比较失败时树会把两个键当作相等，因此修改树的操作要先用checked确认查找不会出错
+fn checked<T>(f: impl FnOnce() -> T) -> PyResult<T> {
+    COMPARE_ERROR.with(|slot| slot.borrow_mut().take());
+    let res = f();
+    match COMPARE_ERROR.with(|slot| slot.borrow_mut().take()) {
+        Some(err) => Err(err),
+        None => Ok(res),
+    }
+}
+
+// 作为树的键的Python对象，用Python的比较运算确定顺序
+struct Key(PyObject);
+
+impl Clone for Key {
+    fn clone(&self) -> Self {
+        Python::with_gil(|py| Key(self.0.clone_ref(py)))
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        Python::with_gil(|py| self.0.bind(py).eq(other.0.bind(py)).unwrap_or_else(|err| {
+            record(err);
+            false
+        }))
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Python::with_gil(|py| self.0.bind(py).compare(other.0.bind(py)).map_err(record).ok())
+    }
+}
+
+/// 按键有序的映射，由二叉查找树实现
+#[pyclass(name = "BSTree", module = "bstree")]
+struct PyBSTree {
+    tree: Tree<Key, PyObject>,
+    len: usize,
+}
+
+impl PyBSTree {
+    // 树中的键都是同一类可比较的对象，新键先与最小键比较一次，不能比较时抛出TypeError，避免破坏树的顺序；
+    // 不等于自身的键(例如NaN)与任何键都无法排序，同样抛出TypeError
+    fn key(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<Key> {
+        if key.ne(key)? {
+            return Err(PyTypeError::new_err(format!("key {} is not equal to itself", key.repr()?)));
+        }
+        if let Some((min, _)) = self.tree.min_pair() {
+            key.compare(min.0.bind(py))?;
+        }
+        Ok(Key(key.clone().unbind()))
+    }
+
+    fn insert(&mut self, py: Python<'_>, key: &Bound<'_, PyAny>, value: PyObject) -> PyResult<()> {
+        let key = self.key(py, key)?;
+        match checked(|| self.tree.get_mut(&key))? {
+            Some(old) => *old = value,
+            None => {
+                self.tree.insert(key, value);
+                self.len += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let key = self.key(py, key)?;
+        // 先确认查找过程中的比较都不出错，再删除，避免误删被当作相等的键
+        checked(|| self.tree.contains(&key))?;
+        match self.tree.take(&key) {
+            Some((_, value)) => {
+                self.len -= 1;
+                Ok(value)
+            }
+            None => Err(PyKeyError::new_err(key.0)),
+        }
+    }
+
+    fn pair(py: Python<'_>, pair: Option<(&Key, &PyObject)>) -> Option<(PyObject, PyObject)> {
+        pair.map(|(key, value)| (key.0.clone_ref(py), value.clone_ref(py)))
+    }
+}
+
+#[pymethods]
+impl PyBSTree {
+    /// 构建一棵树，items可以是dict或由(键, 值)组成的可迭代对象
+    #[new]
+    #[pyo3(signature = (items=None))]
+    fn new(py: Python<'_>, items: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut tree = PyBSTree { tree: Tree::new(), len: 0 };
+        if let Some(items) = items {
+            let items = match items.downcast::<PyDict>() {
+                Ok(dict) => dict.items().into_any(),
+                Err(_) => items.clone(),
+            };
+            for item in items.try_iter()? {
+                let (key, value): (Bound<'_, PyAny>, PyObject) = item?.extract()?;
+                tree.insert(py, &key, value)?;
+            }
+        }
+        Ok(tree)
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __contains__(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let key = self.key(py, key)?;
+        checked(|| self.tree.contains(&key))
+    }
+
+    fn __getitem__(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let probe = self.key(py, key)?;
+        match checked(|| self.tree.get(&probe).map(|value| value.clone_ref(py)))? {
+            Some(value) => Ok(value),
+            None => Err(PyKeyError::new_err(key.clone().unbind())),
+        }
+    }
+
+    fn __setitem__(&mut self, py: Python<'_>, key: &Bound<'_, PyAny>, value: PyObject) -> PyResult<()> {
+        self.insert(py, key, value)
+    }
+
+    fn __delitem__(&mut self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.take(py, key).map(drop)
+    }
+
+    /// 按键的升序遍历键
+    fn __iter__(&self, py: Python<'_>) -> PyResult<Py<PyIterator>> {
+        let keys = PyList::new(py, self.keys(py))?;
+        Ok(keys.as_any().try_iter()?.unbind())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let mut items = Vec::with_capacity(self.len);
+        for (key, value) in self.tree.inorder_iter() {
+            items.push(format!("{}: {}", key.0.bind(py).repr()?, value.bind(py).repr()?));
+        }
+        Ok(format!("BSTree({{{}}})", items.join(", ")))
+    }
+
+    /// 返回键对应的值，键不存在时返回default
+    #[pyo3(signature = (key, default=None))]
+    fn get(&self, py: Python<'_>, key: &Bound<'_, PyAny>, default: Option<PyObject>) -> PyResult<Option<PyObject>> {
+        let key = self.key(py, key)?;
+        Ok(checked(|| self.tree.get(&key).map(|value| value.clone_ref(py)))?.or(default))
+    }
+
+    /// 删除键并返回对应的值，键不存在时抛出KeyError
+    fn pop(&mut self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        self.take(py, key)
+    }
+
+    /// 按键的升序返回全部键
+    fn keys(&self, py: Python<'_>) -> Vec<PyObject> {
+        self.tree.inorder_iter().map(|(key, _)| key.0.clone_ref(py)).collect()
+    }
+
+    /// 按键的升序返回全部值
+    fn values(&self, py: Python<'_>) -> Vec<PyObject> {
+        self.tree.inorder_iter().map(|(_, value)| value.clone_ref(py)).collect()
+    }
+
+    /// 按键的升序返回全部(键, 值)
+    fn items(&self, py: Python<'_>) -> PyResult<Vec<Py<PyTuple>>> {
+        self.tree
+            .inorder_iter()
+            .map(|(key, value)| Ok(PyTuple::new(py, [key.0.clone_ref(py), value.clone_ref(py)])?.unbind()))
+            .collect()
+    }
+
+    /// 返回最小的(键, 值)，树为空时返回None
+    fn min_item(&self, py: Python<'_>) -> Option<(PyObject, PyObject)> {
+        Self::pair(py, self.tree.min_pair())
+    }
+
+    /// 返回最大的(键, 值)，树为空时返回None
+    fn max_item(&self, py: Python<'_>) -> Option<(PyObject, PyObject)> {
+        Self::pair(py, self.tree.max_pair())
+    }
+
+    /// 返回键大于key的最小的(键, 值)，不存在时返回None
+    fn successor(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<Option<(PyObject, PyObject)>> {
+        let key = self.key(py, key)?;
+        checked(|| Self::pair(py, self.tree.successor(&key)))
+    }
+
+    /// 返回键小于key的最大的(键, 值)，不存在时返回None
+    fn predecessor(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<Option<(PyObject, PyObject)>> {
+        let key = self.key(py, key)?;
+        checked(|| Self::pair(py, self.tree.predecessor(&key)))
+    }
+}
+
+#[pymodule]
+fn bstree(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBSTree>()
+}
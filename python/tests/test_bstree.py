@@ -0,0 +1,76 @@
+# 运行方式：在python目录中执行maturin develop后运行pytest
+import pytest
+
+from bstree import BSTree
+
+
+def test_dict_like():
+    tree = BSTree({3: 'c', 1: 'a'})
+    tree[2] = 'b'
+    tree[2] = 'B'
+    assert len(tree) == 3
+    assert list(tree) == [1, 2, 3]
+    assert tree.items() == [(1, 'a'), (2, 'B'), (3, 'c')]
+    assert repr(tree) == "BSTree({1: 'a', 2: 'B', 3: 'c'})"
+    assert 2 in tree and 5 not in tree
+    assert tree.get(9, 'x') == 'x'
+    del tree[1]
+    assert tree.pop(3) == 'c'
+    assert tree.keys() == [2] and tree.values() == ['B']
+    with pytest.raises(KeyError):
+        tree.pop(7)
+
+
+def test_ordered_queries():
+    tree = BSTree([("pear", 1), ("apple", 2), ("fig", 3)])
+    assert tree.min_item() == ("apple", 2)
+    assert tree.max_item() == ("pear", 1)
+    assert tree.successor("fig") == ("pear", 1)
+    assert tree.predecessor("apple") is None
+
+
+def test_incomparable_key():
+    tree = BSTree({1: 'a'})
+    with pytest.raises(TypeError):
+        tree['z'] = 1
+    assert len(tree) == 1
+
+
+def test_nan_key_rejected():
+    tree = BSTree({1.0: 'a', 2.0: 'b'})
+    with pytest.raises(TypeError):
+        tree[float('nan')] = 'x'
+    assert tree.items() == [(1.0, 'a'), (2.0, 'b')]
+
+
+class Fragile:
+    # 能与1比较，大于1的不同对象之间比较时抛出异常
+    def __init__(self, n):
+        self.n = n
+
+    def _cmp(self, other):
+        if min(self.n, other.n) > 1 and self.n != other.n:
+            raise TypeError("cannot compare")
+        return self.n - other.n
+
+    def __eq__(self, other):
+        return self._cmp(other) == 0
+
+    def __lt__(self, other):
+        return self._cmp(other) < 0
+
+    def __gt__(self, other):
+        return self._cmp(other) > 0
+
+
+def test_failed_comparison_propagates():
+    tree = BSTree()
+    tree[Fragile(1)] = 'a'
+    tree[Fragile(5)] = 'e'
+    with pytest.raises(TypeError):
+        tree[Fragile(3)] = 'c'
+    with pytest.raises(TypeError):
+        del tree[Fragile(3)]
+    with pytest.raises(TypeError):
+        tree.get(Fragile(3))
+    assert tree.values() == ['a', 'e']
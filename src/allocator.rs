@@ -0,0 +1,26 @@
+// 二叉查找树的节点从分配器A中分配。开启allocator_api特性(需要nightly编译器)时A可以是任意实现了
+// 标准库Allocator的分配器，节点保存在Box<_, A>中；稳定版编译器上只有全局分配器，这里给出同名的替身，
+// 此时A只能是Global，节点就是普通的Box
+#[cfg(feature = "allocator_api")]
+pub use std::alloc::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+pub use self::stable::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+mod stable {
+    mod sealed {
+        pub trait Sealed {}
+    }
+
+    /// 稳定版编译器上分配器的占位，只有`Global`实现了它；开启`allocator_api`特性后即为`std::alloc::Allocator`
+    pub trait Allocator: sealed::Sealed {}
+
+    /// 全局分配器，开启`allocator_api`特性后即为`std::alloc::Global`
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    impl sealed::Sealed for Global {}
+
+    impl Allocator for Global {}
+}
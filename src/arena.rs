@@ -0,0 +1,349 @@
+use std::collections::VecDeque;
+use crate::iterator::TraverseIter;
+
+/// `ArenaBSTree`中节点的句柄，由`insert`和`find`返回
+///
+/// 句柄记录节点所在的槽位和槽位的代数，节点被删除后槽位的代数加一，
+/// 旧句柄随之失效，即使槽位被新节点复用也不会误访问到新节点。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: usize,
+    generation: u32,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Option<usize>,
+    right: Option<usize>,
+    parent: Option<usize>,
+}
+
+// 节点槽位，node为None时槽位空闲
+struct Slot<K, V> {
+    generation: u32,
+    node: Option<Node<K, V>>,
+}
+
+/// 节点存放在数组槽位中的二叉查找树，孩子和父节点用槽位下标相连
+///
+/// 插入和查找返回稳定的[`NodeId`]，之后通过`get_by_id`、`remove_by_id`等直接定位节点，
+/// 不需要再从根节点开始比较键。删除节点只会重新连接指针，其他节点的句柄保持有效。
+pub struct ArenaBSTree<K, V> {
+    slots: Vec<Slot<K, V>>,
+    // 空闲槽位的下标，插入时优先复用
+    free: Vec<usize>,
+    root: Option<usize>,
+    len: usize,
+}
+
+impl<K: PartialOrd + Clone, V> Default for ArenaBSTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V> ArenaBSTree<K, V> {
+    /// 构建一棵空树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let tree: ArenaBSTree<i32, char> = ArenaBSTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        ArenaBSTree { slots: Vec::new(), free: Vec::new(), root: None, len: 0 }
+    }
+
+    /// 返回树中键值对的个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(1, 'a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 向树中插入键值对，返回节点的句柄；如果键已经存在，则替换旧值为新值，句柄与之前相同
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// let id = tree.insert(1, 'a');
+    /// assert_eq!(tree.insert(1, 'b'), id);
+    /// assert_eq!(tree.get_by_id(id), Some((&1, &'b')));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> NodeId {
+        let mut parent = None;
+        let mut go_left = false;
+        let mut current = self.root;
+        while let Some(index) = current {
+            let node = self.node_mut(index);
+            if key < node.key {
+                current = node.left;
+                go_left = true;
+            } else if key > node.key {
+                current = node.right;
+                go_left = false;
+            } else {
+                node.value = value;
+                return self.id(index);
+            }
+            parent = Some(index);
+        }
+        let index = self.alloc(Node { key, value, left: None, right: None, parent });
+        match parent {
+            None => self.root = Some(index),
+            Some(parent) if go_left => self.node_mut(parent).left = Some(index),
+            Some(parent) => self.node_mut(parent).right = Some(index),
+        }
+        self.len += 1;
+        self.id(index)
+    }
+
+    /// 查找键key所在节点的句柄，找不到返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// let id = tree.insert(1, 'a');
+    /// assert_eq!(tree.find(&1), Some(id));
+    /// assert_eq!(tree.find(&2), None);
+    /// ```
+    pub fn find(&self, key: &K) -> Option<NodeId> {
+        let mut current = self.root;
+        while let Some(index) = current {
+            let node = self.node(index);
+            if *key < node.key {
+                current = node.left;
+            } else if *key > node.key {
+                current = node.right;
+            } else {
+                return Some(self.id(index));
+            }
+        }
+        None
+    }
+
+    /// 根据键查找对应的值，找不到返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).and_then(|id| self.get_by_id(id)).map(|(_, v)| v)
+    }
+
+    /// 判断句柄id指向的节点是否还在树中
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// let id = tree.insert(1, 'a');
+    /// assert!(tree.contains_id(id));
+    /// tree.remove_by_id(id);
+    /// assert!(!tree.contains_id(id));
+    /// ```
+    pub fn contains_id(&self, id: NodeId) -> bool {
+        self.slot(id).is_some()
+    }
+
+    /// 通过句柄直接取得键值对，不需要比较键，节点已被删除时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// let id = tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.get_by_id(id), Some((&1, &'a')));
+    /// ```
+    pub fn get_by_id(&self, id: NodeId) -> Option<(&K, &V)> {
+        self.slot(id).map(|node| (&node.key, &node.value))
+    }
+
+    /// 通过句柄直接取得值的可变借用，节点已被删除时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// let id = tree.insert(1, 10);
+    /// if let Some(v) = tree.get_by_id_mut(id) {
+    ///     *v += 1;
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// ```
+    pub fn get_by_id_mut(&mut self, id: NodeId) -> Option<&mut V> {
+        self.slots
+            .get_mut(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+            .map(|node| &mut node.value)
+    }
+
+    /// 通过句柄删除节点，返回被删除的键和值，节点已被删除时返回None；
+    /// 节点沿父指针直接摘下，不需要从根节点查找，其他节点的句柄保持有效
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// let a = tree.insert(2, 'b');
+    /// let b = tree.insert(1, 'a');
+    /// assert_eq!(tree.remove_by_id(a), Some((2, 'b')));
+    /// assert_eq!(tree.remove_by_id(a), None);
+    /// assert_eq!(tree.get_by_id(b), Some((&1, &'a')));
+    /// ```
+    pub fn remove_by_id(&mut self, id: NodeId) -> Option<(K, V)> {
+        self.slot(id)?;
+        let index = id.index;
+        let (left, right) = {
+            let node = self.node(index);
+            (node.left, node.right)
+        };
+        match (left, right) {
+            (None, _) => self.transplant(index, right),
+            (_, None) => self.transplant(index, left),
+            (Some(left), Some(right)) => {
+                // 右子树中的最小节点接替被删除节点的位置
+                let mut successor = right;
+                while let Some(next) = self.node(successor).left {
+                    successor = next;
+                }
+                if successor != right {
+                    let successor_right = self.node(successor).right;
+                    self.transplant(successor, successor_right);
+                    self.node_mut(successor).right = Some(right);
+                    self.node_mut(right).parent = Some(successor);
+                }
+                self.transplant(index, Some(successor));
+                self.node_mut(successor).left = Some(left);
+                self.node_mut(left).parent = Some(successor);
+            }
+        }
+        let slot = &mut self.slots[index];
+        let node = slot.node.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.take(&1), Some((1, 'a')));
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        self.find(key).and_then(|id| self.remove_by_id(id))
+    }
+
+    /// 中序遍历迭代器，按键的升序产生键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ArenaBSTree;
+    /// let mut tree = ArenaBSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(3, 'c');
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
+    /// ```
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        let mut stack = Vec::new();
+        let mut current = self.root;
+        loop {
+            while let Some(index) = current {
+                stack.push(index);
+                current = self.node(index).left;
+            }
+            match stack.pop() {
+                Some(index) => {
+                    let node = self.node(index);
+                    queue.push_back((&node.key, &node.value));
+                    current = node.right;
+                }
+                None => break,
+            }
+        }
+        TraverseIter::new(queue)
+    }
+
+    // 句柄有效时返回它指向的节点
+    fn slot(&self, id: NodeId) -> Option<&Node<K, V>> {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    // 返回下标为index的节点，调用者需保证槽位中有节点
+    fn node(&self, index: usize) -> &Node<K, V> {
+        self.slots[index].node.as_ref().expect("arena slot is empty")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<K, V> {
+        self.slots[index].node.as_mut().expect("arena slot is empty")
+    }
+
+    fn id(&self, index: usize) -> NodeId {
+        NodeId { index, generation: self.slots[index].generation }
+    }
+
+    // 把节点放入空闲槽位，没有空闲槽位时追加新槽位，返回槽位的下标
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.slots[index].node = Some(node);
+                index
+            }
+            None => {
+                self.slots.push(Slot { generation: 0, node: Some(node) });
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    // 用以to为根的子树替换以at为根的子树在父节点中的位置
+    fn transplant(&mut self, at: usize, to: Option<usize>) {
+        let parent = self.node(at).parent;
+        match parent {
+            None => self.root = to,
+            Some(parent) => {
+                let parent = self.node_mut(parent);
+                if parent.left == Some(at) {
+                    parent.left = to;
+                } else {
+                    parent.right = to;
+                }
+            }
+        }
+        if let Some(to) = to {
+            self.node_mut(to).parent = parent;
+        }
+    }
+}
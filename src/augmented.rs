@@ -0,0 +1,276 @@
+use std::collections::VecDeque;
+use std::mem;
+use crate::iterator::TraverseIter;
+use crate::random;
+
+pub(crate) type Link<K, V, A> = Option<Box<Node<K, V, A>>>;
+
+pub(crate) struct Node<K, V, A> {
+    pub key: K,
+    pub value: V,
+    // 随机秩，决定节点在堆序中的位置
+    rank: u32,
+    // 附加信息，由Augment::update根据节点自身和左右孩子维护
+    pub aug: A,
+    pub left: Link<K, V, A>,
+    pub right: Link<K, V, A>,
+}
+
+// 节点上的附加信息：子树大小、子树和、默克尔哈希、懒标记等
+// 共享核心在每次改变节点的孩子或值之后调用update，在访问节点的孩子之前调用push
+pub(crate) trait Augment<K, V>: Default + Sized {
+    // 由节点自身的键值和左右孩子的附加信息重新计算节点的附加信息
+    fn update(node: &mut Node<K, V, Self>);
+
+    // 把作用于整棵子树的懒标记转交给孩子，没有懒标记的附加信息不需要实现
+    fn push(_node: &mut Node<K, V, Self>) {}
+
+    // 新节点的秩，默认服从几何分布；需要树的形状只取决于键集合时可以由键计算
+    fn rank(_key: &K) -> u32 {
+        random::next_u64().trailing_zeros()
+    }
+}
+
+// 不带附加信息的树
+impl<K, V> Augment<K, V> for () {
+    fn update(_node: &mut Node<K, V, ()>) {}
+}
+
+// 各种带附加信息的树共用的拉链树：左孩子的秩严格小于父节点，右孩子的秩不大于父节点，
+// 插入和删除都通过unzip（拆分）和zip（拼接）完成而不需要旋转，期望高度为O(log n)，
+// 因此沿路径递归的深度也是O(log n)。修改路径上的节点在返回时依次调用Augment::update
+pub(crate) struct AugTree<K, V, A> {
+    pub root: Link<K, V, A>,
+}
+
+impl<K: PartialOrd, V, A: Augment<K, V>> AugTree<K, V, A> {
+    pub fn new() -> Self {
+        AugTree { root: None }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // 向树中插入键值对，如果键已经存在，则替换旧值为新值并返回旧值
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let value = match Self::replace(&mut self.root, &key, value) {
+            Ok(old) => return Some(old),
+            Err(value) => value,
+        };
+        let node = Box::new(Node {
+            rank: A::rank(&key),
+            key,
+            value,
+            aug: A::default(),
+            left: None,
+            right: None,
+        });
+        Self::insert_node(&mut self.root, node);
+        None
+    }
+
+    // 键已经存在时替换它的值，沿路径返回时更新附加信息，返回旧值；键不存在时把value原样交回
+    fn replace(link: &mut Link<K, V, A>, key: &K, value: V) -> Result<V, V> {
+        let node = match link {
+            Some(node) => node,
+            None => return Err(value),
+        };
+        A::push(node);
+        let res = if *key < node.key {
+            Self::replace(&mut node.left, key, value)
+        } else if *key > node.key {
+            Self::replace(&mut node.right, key, value)
+        } else {
+            Ok(mem::replace(&mut node.value, value))
+        };
+        if res.is_ok() {
+            A::update(node);
+        }
+        res
+    }
+
+    // 沿查找路径找到新节点在堆序中的位置，把原来的子树拆开挂到新节点两侧，调用者需保证键不在树中
+    fn insert_node(link: &mut Link<K, V, A>, mut node: Box<Node<K, V, A>>) {
+        match link {
+            Some(cur) if cur.rank > node.rank || (cur.rank == node.rank && cur.key < node.key) => {
+                A::push(cur);
+                if node.key < cur.key {
+                    Self::insert_node(&mut cur.left, node);
+                } else {
+                    Self::insert_node(&mut cur.right, node);
+                }
+                A::update(cur);
+            }
+            _ => {
+                let (left, right) = Self::unzip_link(link.take(), &node.key);
+                node.left = left;
+                node.right = right;
+                A::update(&mut node);
+                *link = Some(node);
+            }
+        }
+    }
+
+    // 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        Self::take_link(&mut self.root, key).map(|node| (node.key, node.value))
+    }
+
+    // 摘下键为key的节点并把它的左右子树拼接起来，沿路径返回时更新附加信息
+    fn take_link(link: &mut Link<K, V, A>, key: &K) -> Option<Box<Node<K, V, A>>> {
+        let node = link.as_mut()?;
+        A::push(node);
+        let removed = if *key < node.key {
+            Self::take_link(&mut node.left, key)
+        } else if *key > node.key {
+            Self::take_link(&mut node.right, key)
+        } else {
+            let mut node = link.take()?;
+            *link = Self::zip_link(node.left.take(), node.right.take());
+            return Some(node);
+        };
+        if removed.is_some() {
+            A::update(node);
+        }
+        removed
+    }
+
+    // 根据键获取对应的值，不下推懒标记
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if node.key < *key {
+                current = node.right.as_deref();
+            } else if node.key > *key {
+                current = node.left.as_deref();
+            } else {
+                return Some(&node.value);
+            }
+        }
+        None
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // 根据键获取对应值的可变引用，沿途下推懒标记；
+    // 之后不会重新计算附加信息，只能用于附加信息与值无关的树
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            A::push(node);
+            if node.key < *key {
+                current = node.right.as_deref_mut();
+            } else if node.key > *key {
+                current = node.left.as_deref_mut();
+            } else {
+                return Some(&mut node.value);
+            }
+        }
+        None
+    }
+
+    // 按key把树拆成两棵，第一棵包含小于key的键，第二棵包含不小于key的键
+    pub fn unzip(self, key: &K) -> (Self, Self) {
+        let (left, right) = Self::unzip_link(self.root, key);
+        (AugTree { root: left }, AugTree { root: right })
+    }
+
+    // 把other拼接到当前树的右侧，调用者需保证当前树中的键都小于other中的键
+    pub fn zip(self, other: Self) -> Self {
+        AugTree {
+            root: Self::zip_link(self.root, other.root),
+        }
+    }
+
+    // 返回最小的键
+    pub fn min_key(&self) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    // 返回最大的键
+    pub fn max_key(&self) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    // 将树分成键都小于key和键都不小于key的两棵树，拆开的节点保持原有的相对次序，因此堆序不变
+    fn unzip_link(link: Link<K, V, A>, key: &K) -> (Link<K, V, A>, Link<K, V, A>) {
+        match link {
+            None => (None, None),
+            Some(mut node) => {
+                A::push(&mut node);
+                if node.key < *key {
+                    let (left, right) = Self::unzip_link(node.right.take(), key);
+                    node.right = left;
+                    A::update(&mut node);
+                    (Some(node), right)
+                } else {
+                    let (left, right) = Self::unzip_link(node.left.take(), key);
+                    node.left = right;
+                    A::update(&mut node);
+                    (left, Some(node))
+                }
+            }
+        }
+    }
+
+    // 拼接两棵树，left中的键都小于right中的键，沿left的右链和right的左链按秩合并
+    fn zip_link(left: Link<K, V, A>, right: Link<K, V, A>) -> Link<K, V, A> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(mut x), Some(mut y)) => {
+                // 秩相同时键较小的x作父节点，y成为右孩子
+                if x.rank < y.rank {
+                    A::push(&mut y);
+                    y.left = Self::zip_link(Some(x), y.left.take());
+                    A::update(&mut y);
+                    Some(y)
+                } else {
+                    A::push(&mut x);
+                    x.right = Self::zip_link(x.right.take(), Some(y));
+                    A::update(&mut x);
+                    Some(x)
+                }
+            }
+        }
+    }
+
+    // 把树中的懒标记全部下推，使每个节点的值都是最新的
+    pub fn push_all(&mut self) {
+        let mut stack: Vec<&mut Node<K, V, A>> = self.root.as_deref_mut().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            A::push(node);
+            stack.extend(node.left.as_deref_mut());
+            stack.extend(node.right.as_deref_mut());
+        }
+    }
+
+    // 按中序收集树中的键值对，不下推懒标记
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        let mut stack = Vec::new();
+        let mut current = self.root.as_deref();
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            if let Some(node) = stack.pop() {
+                queue.push_back((&node.key, &node.value));
+                current = node.right.as_deref();
+            }
+        }
+        TraverseIter::new(queue)
+    }
+}
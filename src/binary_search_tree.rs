@@ -0,0 +1,60 @@
+use crate::iterator::TraverseIter;
+
+/// 递归实现和迭代实现共有的二叉查找树接口，下游代码和测试可以只针对该接口编写一次
+/// # Example
+/// ```
+/// use an_ok_bstree::{BSTree, BinarySearchTree};
+/// fn keys<T: BinarySearchTree<i32, char>>(tree: &T) -> Vec<i32> {
+///     tree.inorder_iter().map(|(k, _)| *k).collect()
+/// }
+/// let mut tree = BSTree::new();
+/// BinarySearchTree::insert(&mut tree, 2, 'b');
+/// BinarySearchTree::insert(&mut tree, 1, 'a');
+/// assert_eq!(keys(&tree), vec![1, 2]);
+/// ```
+pub trait BinarySearchTree<K, V>: Default {
+    /// 判断当前树是否为空
+    fn is_empty(&self) -> bool;
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值
+    fn insert(&mut self, key: K, value: V);
+
+    /// 从树中删除键值对，如果找不到键值对，则忽略
+    fn delete(&mut self, key: K);
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    fn take(&mut self, key: &K) -> Option<(K, V)>;
+
+    /// 根据键获取对应的值
+    fn get(&self, key: &K) -> Option<&V>;
+
+    /// 根据键获取对应值的可变引用
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+
+    /// 查找是否存在键值对
+    fn contains(&self, key: &K) -> bool;
+
+    /// 返回树中的最小键值对
+    fn min_pair(&self) -> Option<(&K, &V)>;
+
+    /// 返回树中的最大键值对
+    fn max_pair(&self) -> Option<(&K, &V)>;
+
+    /// 返回第一个大于key的键值对
+    fn successor(&self, key: &K) -> Option<(&K, &V)>;
+
+    /// 返回第一个小于key的键值对
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)>;
+
+    /// 前序遍历迭代器
+    fn preorder_iter(&self) -> TraverseIter<'_, K, V>;
+
+    /// 中序遍历迭代器
+    fn inorder_iter(&self) -> TraverseIter<'_, K, V>;
+
+    /// 后序遍历迭代器
+    fn postorder_iter(&self) -> TraverseIter<'_, K, V>;
+
+    /// 层序遍历迭代器
+    fn levelorder_iter(&self) -> TraverseIter<'_, K, V>;
+}
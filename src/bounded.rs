@@ -0,0 +1,178 @@
+use crate::policy::Eviction;
+use crate::BSTree;
+
+/// 容量有限的二叉查找树，键的个数达到容量后按`Eviction`策略淘汰或拒绝
+///
+/// 例如以`Eviction::EvictMin`维护容量为k的树，不断插入新的分数，树中始终是最大的k个分数。
+pub struct BoundedBSTree<K, V> {
+    tree: BSTree<K, V>,
+    len: usize,
+    capacity: usize,
+    policy: Eviction,
+}
+
+impl<K: PartialOrd + Clone, V> BoundedBSTree<K, V> {
+    /// 构建一棵容量为capacity的空树，已满时按policy处理新键
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let tree: BoundedBSTree<i32, char> = BoundedBSTree::with_capacity(3, Eviction::Reject);
+    /// assert_eq!(tree.capacity(), 3);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn with_capacity(capacity: usize, policy: Eviction) -> Self {
+        BoundedBSTree {
+            tree: BSTree::new(),
+            len: 0,
+            capacity,
+            policy,
+        }
+    }
+
+    /// 返回容量
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let tree: BoundedBSTree<i32, char> = BoundedBSTree::with_capacity(5, Eviction::EvictMin);
+    /// assert_eq!(tree.capacity(), 5);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 返回已满时的处理策略
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let tree: BoundedBSTree<i32, char> = BoundedBSTree::with_capacity(5, Eviction::EvictMax);
+    /// assert_eq!(tree.policy(), Eviction::EvictMax);
+    /// ```
+    pub fn policy(&self) -> Eviction {
+        self.policy
+    }
+
+    /// 返回树中键值对的个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut tree = BoundedBSTree::with_capacity(2, Eviction::EvictMin);
+    /// for i in 0..5 {
+    ///     tree.insert(i, i);
+    /// }
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut tree = BoundedBSTree::with_capacity(2, Eviction::Reject);
+    /// assert!(tree.is_empty());
+    /// tree.insert(1, 'a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 判断键的个数是否已经达到容量
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut tree = BoundedBSTree::with_capacity(1, Eviction::Reject);
+    /// assert!(!tree.is_full());
+    /// tree.insert(1, 'a');
+    /// assert!(tree.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.len >= self.capacity
+    }
+
+    /// 返回底层的树，用于只读查询
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut tree = BoundedBSTree::with_capacity(2, Eviction::Reject);
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.tree().get(&1), Some(&'a'));
+    /// ```
+    pub fn tree(&self) -> &BSTree<K, V> {
+        &self.tree
+    }
+
+    /// 消耗当前对象，返回底层的树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut tree = BoundedBSTree::with_capacity(2, Eviction::Reject);
+    /// tree.insert(1, 'a');
+    /// let tree = tree.into_inner();
+    /// assert!(tree.contains(&1));
+    /// ```
+    pub fn into_inner(self) -> BSTree<K, V> {
+        self.tree
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值，不占用新的容量；
+    /// 树已满时按策略处理，返回被淘汰或被拒绝的键值对，淘汰的可能正是刚插入的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut top = BoundedBSTree::with_capacity(2, Eviction::EvictMin);
+    /// assert_eq!(top.insert(80, "bob"), None);
+    /// assert_eq!(top.insert(95, "amy"), None);
+    /// assert_eq!(top.insert(90, "cat"), Some((80, "bob")));
+    /// assert_eq!(top.insert(70, "dan"), Some((70, "dan")));
+    /// let res: Vec<(&i32, &&str)> = top.tree().inorder_iter().collect();
+    /// assert_eq!(res, vec![(&90, &"cat"), (&95, &"amy")]);
+    ///
+    /// let mut tree = BoundedBSTree::with_capacity(1, Eviction::Reject);
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.insert(2, 'b'), Some((2, 'b')));
+    /// assert_eq!(tree.insert(1, 'c'), None);
+    /// assert_eq!(tree.tree().get(&1), Some(&'c'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(old) = self.tree.get_mut(&key) {
+            *old = value;
+            return None;
+        }
+        if !self.is_full() {
+            self.tree.insert(key, value);
+            self.len += 1;
+            return None;
+        }
+        match self.policy {
+            Eviction::Reject => Some((key, value)),
+            Eviction::EvictMin => {
+                self.tree.insert(key, value);
+                self.tree.first_entry().map(|entry| entry.remove_entry())
+            }
+            Eviction::EvictMax => {
+                self.tree.insert(key, value);
+                self.tree.last_entry().map(|entry| entry.remove_entry())
+            }
+        }
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BoundedBSTree, Eviction};
+    /// let mut tree = BoundedBSTree::with_capacity(1, Eviction::Reject);
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.take(&1), Some((1, 'a')));
+    /// assert!(!tree.is_full());
+    /// assert_eq!(tree.insert(2, 'b'), None);
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        let res = self.tree.take(key);
+        if res.is_some() {
+            self.len -= 1;
+        }
+        res
+    }
+}
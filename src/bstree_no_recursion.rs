@@ -1,3 +1,13 @@
+//! 迭代法实现的二叉查找树，节点操作不使用递归，适合很深的树
+
 mod node;
 mod bstree;
-pub use bstree::BSTree;
\ No newline at end of file
+mod entry;
+#[cfg(feature = "rayon")]
+mod par_iter;
+mod walker;
+pub use bstree::BSTree;
+pub use entry::OccupiedEntry;
+pub use walker::Walker;
+#[cfg(feature = "rayon")]
+pub use par_iter::ParIter;
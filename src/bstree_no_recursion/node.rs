@@ -1,37 +1,1059 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::Peekable;
+#[cfg(not(feature = "allocator_api"))]
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{ControlFlow, RangeBounds};
+use std::ptr;
+use std::str::FromStr;
+use crate::allocator::{Allocator, Global};
+use crate::error::DecodeError;
+use crate::metrics;
+use crate::pool::NodePool;
+use crate::random;
+use crate::range;
+use crate::trace;
+use crate::walk::{Order, Visit};
 
-pub type Link<K, V> = Option<Box<Node<K, V>>>;
+//并行构建时，子树的元素个数不超过该值就不再拆分
+#[cfg(feature = "rayon")]
+const PAR_BUILD_THRESHOLD: usize = 4096;
 
-pub struct Node<K, V> {
+pub type Link<K, V, A = Global> = Option<NodeBox<K, V, A>>;
+#[cfg(feature = "allocator_api")]
+pub type NodeBox<K, V, A = Global> = Box<Node<K, V, A>, A>;
+#[cfg(not(feature = "allocator_api"))]
+pub type NodeBox<K, V, A = Global> = Box<Node<K, V, A>>;
+
+pub struct Node<K, V, A: Allocator = Global> {
     pub key: K,
     pub value: V,
-    pub left: Link<K, V>,
-    pub right: Link<K, V>,
+    pub left: Link<K, V, A>,
+    pub right: Link<K, V, A>,
+    //稳定版的Box没有分配器参数，由这里标记节点所属的分配器
+    #[cfg(not(feature = "allocator_api"))]
+    alloc: PhantomData<A>,
 }
 
-impl<K: PartialEq, V> PartialEq for Node<K, V> {
+impl<K: PartialEq, V, A: Allocator + Clone> PartialEq for Node<K, V, A> {
     fn eq(&self, other: &Self) -> bool {
         self.key == other.key
     }
 }
 
 
-impl<K: PartialOrd + Clone, V> Node<K, V> {
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> Node<K, V, A> {
     pub fn new(key: K, value: V) -> Self {
         Node {
             key,
             value,
             left: None,
             right: None,
+            #[cfg(not(feature = "allocator_api"))]
+            alloc: PhantomData,
+        }
+    }
+
+    //由按键严格升序排列的键值对自底向上构建一棵平衡的树，节点从pool中分配
+    pub fn build_sorted(items: Vec<(K, V)>, pool: &mut NodePool<Node<K, V, A>, A>) -> Link<K, V, A> {
+        // 先按二分的方式计算出每个元素在平衡树中的深度
+        let n = items.len();
+        let mut depth = vec![0; n];
+        let mut ranges = vec![(0, n, 0)];
+        while let Some((low, high, d)) = ranges.pop() {
+            if low < high {
+                let mid = low + (high - low) / 2;
+                depth[mid] = d;
+                ranges.push((low, mid, d + 1));
+                ranges.push((mid + 1, high, d + 1));
+            }
+        }
+        // 再按中序依次把节点挂到树上，栈中保存还没有确定右子树的节点，栈底为根节点
+        let mut stack: Vec<(usize, NodeBox<K, V, A>)> = Vec::new();
+        for ((key, value), d) in items.into_iter().zip(depth) {
+            let mut node = pool.alloc(Node::new(key, value));
+            let mut last = None;
+            while let Some((top_depth, mut top)) = stack.pop() {
+                if top_depth < d {
+                    stack.push((top_depth, top));
+                    break;
+                }
+                // 比新节点深的节点都属于新节点的左子树
+                top.right = last;
+                last = Some(top);
+            }
+            node.left = last;
+            stack.push((d, node));
+        }
+        let mut root = None;
+        while let Some((_, mut top)) = stack.pop() {
+            top.right = root;
+            root = Some(top);
+        }
+        root
+    }
+
+    //将按键严格升序排列的键值对一次性插入以link为根的树，键已经存在时替换旧值
+    //每个节点处把待插入的键值对分为左右两部分分别插入，到达空链接时直接构建平衡的子树
+    pub fn insert_sorted(link: &mut Link<K, V, A>, items: Vec<(K, V)>, pool: &mut NodePool<Node<K, V, A>, A>) {
+        let mut tasks = vec![(link, items)];
+        while let Some((link, mut items)) = tasks.pop() {
+            if items.is_empty() {
+                continue;
+            }
+            match link {
+                None => *link = Self::build_sorted(items, pool),
+                Some(node) => {
+                    let lower = items.partition_point(|(k, _)| *k < node.key);
+                    let upper = items.partition_point(|(k, _)| *k <= node.key);
+                    let right = items.split_off(upper);
+                    if upper > lower {
+                        if let Some((_, value)) = items.pop() {
+                            node.value = value;
+                        }
+                    }
+                    tasks.push((&mut node.left, items));
+                    tasks.push((&mut node.right, right));
+                }
+            }
+        }
+    }
+
+    //从以link为根的树中删除keys中的键，keys必须严格升序，返回实际删除的个数
+    pub fn remove_sorted(link: &mut Link<K, V, A>, keys: Vec<K>, pool: &mut NodePool<Node<K, V, A>, A>) -> usize {
+        let mut removed = 0;
+        let mut tasks = vec![(link, keys)];
+        while let Some((link, mut keys)) = tasks.pop() {
+            let (lower, upper) = match link.as_ref() {
+                Some(node) if !keys.is_empty() => (
+                    keys.partition_point(|k| *k < node.key),
+                    keys.partition_point(|k| *k <= node.key),
+                ),
+                _ => continue,
+            };
+            if upper > lower {
+                // 删除当前节点后，会有新的节点换到同一位置，剩余的键在这个位置重新处理
+                keys.remove(lower);
+                if let Some(node) = link.take() {
+                    let (new_root, node) = Self::unlink(node);
+                    *link = new_root;
+                    pool.release(node);
+                }
+                removed += 1;
+                tasks.push((link, keys));
+            } else if let Some(node) = link {
+                let right = keys.split_off(upper);
+                tasks.push((&mut node.left, keys));
+                tasks.push((&mut node.right, right));
+            }
+        }
+        removed
+    }
+
+    //返回键key所在的链接，key不存在时返回它应当插入的空链接
+    pub fn find_slot<'a>(link: &'a mut Link<K, V, A>, key: &K) -> &'a mut Link<K, V, A> {
+        Self::seek_slot(link, key).0
+    }
+
+    //插入使用的find_slot，同时发出insert事件，所有插入键值对的方法都经过这里
+    pub fn insert_slot<'a>(link: &'a mut Link<K, V, A>, key: &K) -> &'a mut Link<K, V, A> {
+        let (slot, path_len) = Self::seek_slot(link, key);
+        trace::insert(|| path_len);
+        slot
+    }
+
+    //find_slot的实现，同时返回查找途中经过的节点个数
+    fn seek_slot<'a>(link: &'a mut Link<K, V, A>, key: &K) -> (&'a mut Link<K, V, A>, usize) {
+        let mut current = link;
+        let mut path_len = 0;
+        while current.as_ref().is_some_and(|node| node.key != *key) {
+            metrics::visit(2);
+            path_len += 1;
+            let node = current.as_mut().unwrap();
+            current = if *key < node.key {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        if current.is_some() {
+            metrics::visit(1);
+            path_len += 1;
+        }
+        (current, path_len)
+    }
+
+    //返回键为key的节点的父节点，key是根节点或不在树中时返回None
+    pub fn parent_node<'a>(root: &'a Link<K, V, A>, key: &K) -> Option<&'a Node<K, V, A>> {
+        let mut parent = None;
+        let mut current = root.as_ref();
+        while let Some(node) = current {
+            if *key < node.key {
+                current = node.left.as_ref();
+            } else if *key > node.key {
+                current = node.right.as_ref();
+            } else {
+                return parent;
+            }
+            parent = Some(&**node);
+        }
+        None
+    }
+
+    //判断键在[min, max]范围内的树能否整体挂到link下的同一个空链接上
+    pub fn can_graft(link: &Link<K, V, A>, min: &K, max: &K) -> bool {
+        let mut current = link.as_ref();
+        while let Some(node) = current {
+            current = if *max < node.key {
+                node.left.as_ref()
+            } else if *min > node.key {
+                node.right.as_ref()
+            } else {
+                return false;
+            };
+        }
+        true
+    }
+
+    //返回最小节点所在的链接
+    pub fn min_slot(link: &mut Link<K, V, A>) -> &mut Link<K, V, A> {
+        let mut current = link;
+        while current.as_ref().is_some_and(|node| node.left.is_some()) {
+            current = &mut current.as_mut().unwrap().left;
+        }
+        current
+    }
+
+    //返回最大节点所在的链接
+    pub fn max_slot(link: &mut Link<K, V, A>) -> &mut Link<K, V, A> {
+        let mut current = link;
+        while current.as_ref().is_some_and(|node| node.right.is_some()) {
+            current = &mut current.as_mut().unwrap().right;
+        }
+        current
+    }
+
+    //将树分裂为两棵树，键满足goes_left的节点组成第一棵树，其余的组成第二棵树
+    //goes_left需要满足：若对某个键成立，则对所有更小的键也成立
+    pub fn split<F: Fn(&K) -> bool>(link: Link<K, V, A>, goes_left: F) -> (Link<K, V, A>, Link<K, V, A>) {
+        let mut left_root = None;
+        let mut right_root = None;
+        // 左树中下一个节点挂在left_tail处，右树中下一个节点挂在right_tail处
+        let mut left_tail = &mut left_root;
+        let mut right_tail = &mut right_root;
+        let mut current = link;
+        while let Some(mut node) = current {
+            if goes_left(&node.key) {
+                current = node.right.take();
+                left_tail = &mut left_tail.get_or_insert(node).right;
+            } else {
+                current = node.left.take();
+                right_tail = &mut right_tail.get_or_insert(node).left;
+            }
+        }
+        (left_root, right_root)
+    }
+
+    //只保留以root为根的树中键在range范围内的节点，只需沿范围的两条边界各向下走一遍，丢弃的节点放回pool
+    pub fn retain_range<R: RangeBounds<K>>(root: &mut Link<K, V, A>, range: &R, pool: &mut NodePool<Node<K, V, A>, A>) {
+        //先让根节点落在范围内：低于下界时连同左子树一起丢弃，高于上界时连同右子树一起丢弃
+        loop {
+            if root.as_ref().is_some_and(|node| range::below(&node.key, range)) {
+                let right = root.as_mut().unwrap().right.take();
+                Self::release_in_order(mem::replace(root, right), pool, &mut |_, _| ());
+            } else if root.as_ref().is_some_and(|node| range::above(&node.key, range)) {
+                let left = root.as_mut().unwrap().left.take();
+                Self::release_in_order(mem::replace(root, left), pool, &mut |_, _| ());
+            } else {
+                break;
+            }
+        }
+        let node = match root {
+            Some(node) => node,
+            None => return,
+        };
+        //左子树的键都小于根，只可能低于下界，沿左侧向下逐个修剪
+        let mut current = &mut node.left;
+        loop {
+            if current.as_ref().is_some_and(|node| range::below(&node.key, range)) {
+                let right = current.as_mut().unwrap().right.take();
+                Self::release_in_order(mem::replace(current, right), pool, &mut |_, _| ());
+            } else if let Some(node) = current {
+                current = &mut node.left;
+            } else {
+                break;
+            }
+        }
+        //右子树的键都大于根，只可能高于上界，沿右侧向下逐个修剪
+        let mut current = &mut node.right;
+        loop {
+            if current.as_ref().is_some_and(|node| range::above(&node.key, range)) {
+                let left = current.as_mut().unwrap().left.take();
+                Self::release_in_order(mem::replace(current, left), pool, &mut |_, _| ());
+            } else if let Some(node) = current {
+                current = &mut node.right;
+            } else {
+                break;
+            }
+        }
+    }
+
+    //将两棵树拼接为一棵，调用者需保证第二棵树中的键都大于第一棵树中的键
+    pub fn join(left: Link<K, V, A>, right: Link<K, V, A>) -> Link<K, V, A> {
+        let mut left = left;
+        let mut current = &mut left;
+        while let Some(node) = current {
+            current = &mut node.right;
+        }
+        *current = right;
+        left
+    }
+
+    //按键的升序取出树中全部的键值对，放入buf
+    pub fn drain_in_order(link: Link<K, V, A>, buf: &mut Vec<(K, V)>) {
+        let mut stack = Vec::new();
+        let mut current = link;
+        loop {
+            while let Some(mut node) = current {
+                current = node.left.take();
+                stack.push(node);
+            }
+            match stack.pop() {
+                None => break,
+                Some(mut node) => {
+                    current = node.right.take();
+                    buf.push((node.key, node.value));
+                }
+            }
+        }
+    }
+
+    //按键的升序把以link为根的树中的节点逐个放回pool，取出的键值对依次交给f
+    pub fn release_in_order<F: FnMut(K, V)>(link: Link<K, V, A>, pool: &mut NodePool<Node<K, V, A>, A>, f: &mut F) {
+        let mut stack = Vec::new();
+        let mut current = link;
+        loop {
+            while let Some(mut node) = current {
+                current = node.left.take();
+                stack.push(node);
+            }
+            match stack.pop() {
+                None => break,
+                Some(mut node) => {
+                    current = node.right.take();
+                    let node = pool.release(node);
+                    f(node.key, node.value);
+                }
+            }
+        }
+    }
+
+    //按键的升序用f转换树中的每个值，保持树的结构不变，新节点从pool中分配
+    pub fn map_values<U, F: FnMut(&K, V) -> U>(link: Link<K, V, A>, f: &mut F, pool: &mut NodePool<Node<K, U, A>, A>) -> Link<K, U, A> {
+        // 用任务栈模拟递归：先转换左子树，再转换当前节点的值，然后转换右子树，最后组装节点
+        enum Task<K, V, A: Allocator> {
+            Enter(Link<K, V, A>),
+            Map(K, V),
+            Assemble,
+        }
+        let mut tasks = vec![Task::Enter(link)];
+        let mut links: Vec<Link<K, U, A>> = Vec::new();
+        let mut pairs = Vec::new();
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Enter(None) => links.push(None),
+                Task::Enter(Some(node)) => {
+                    let node = *node;
+                    tasks.push(Task::Assemble);
+                    tasks.push(Task::Enter(node.right));
+                    tasks.push(Task::Map(node.key, node.value));
+                    tasks.push(Task::Enter(node.left));
+                }
+                Task::Map(key, value) => {
+                    let value = f(&key, value);
+                    pairs.push((key, value));
+                }
+                Task::Assemble => {
+                    let right = links.pop().unwrap();
+                    let left = links.pop().unwrap();
+                    let (key, value) = pairs.pop().unwrap();
+                    let mut node = pool.alloc(Node::new(key, value));
+                    node.left = left;
+                    node.right = right;
+                    links.push(Some(node));
+                }
+            }
+        }
+        links.pop().unwrap()
+    }
+
+    //按键的升序用f转换树中的每个键，f必须严格单调递增，因此可以保持树的结构不变，新节点从pool中分配
+    pub fn map_keys<J: PartialOrd + Clone, F: FnMut(K) -> J>(link: Link<K, V, A>, f: &mut F, pool: &mut NodePool<Node<J, V, A>, A>) -> Link<J, V, A> {
+        // 与map_values相同，用任务栈模拟递归，prev记录上一个转换后的键，仅在debug构建中用于检查单调性
+        enum Task<K, V, A: Allocator> {
+            Enter(Link<K, V, A>),
+            Map(K, V),
+            Assemble,
+        }
+        let mut tasks = vec![Task::Enter(link)];
+        let mut links: Vec<Link<J, V, A>> = Vec::new();
+        let mut pairs = Vec::new();
+        let mut prev: Option<J> = None;
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Enter(None) => links.push(None),
+                Task::Enter(Some(node)) => {
+                    let node = *node;
+                    tasks.push(Task::Assemble);
+                    tasks.push(Task::Enter(node.right));
+                    tasks.push(Task::Map(node.key, node.value));
+                    tasks.push(Task::Enter(node.left));
+                }
+                Task::Map(key, value) => {
+                    let key = f(key);
+                    if cfg!(debug_assertions) {
+                        debug_assert!(
+                            prev.as_ref().is_none_or(|p| *p < key),
+                            "map_keys requires a strictly increasing key mapping"
+                        );
+                        prev = Some(key.clone());
+                    }
+                    pairs.push((key, value));
+                }
+                Task::Assemble => {
+                    let right = links.pop().unwrap();
+                    let left = links.pop().unwrap();
+                    let (key, value) = pairs.pop().unwrap();
+                    let mut node = pool.alloc(Node::new(key, value));
+                    node.left = left;
+                    node.right = right;
+                    links.push(Some(node));
+                }
+            }
+        }
+        links.pop().unwrap()
+    }
+
+    //按中序访问全部键值对，不使用栈、队列，也不分配内存
+    //下降时把走过的链接反转指向父节点，沿反转的链接回到父节点时再恢复，比较键的大小即可知道是从哪一侧回来的
+    pub fn for_each_in_place<F: FnMut(&K, &V)>(root: &mut Link<K, V, A>, f: &mut F) {
+        let cur = root.take();
+        let mut walk = InPlaceWalk { root, cur, up: None };
+        walk.run(f);
+    }
+
+    //返回每个节点所在的列和键值对，按列从左到右排列，同一列中按从上到下、从左到右排列
+    //根节点在第0列，左孩子在父节点左边一列，右孩子在右边一列
+    pub fn columns(root: &Link<K, V, A>) -> Vec<(isize, &K, &V)> {
+        let mut buf = Vec::new();
+        let mut queue: VecDeque<(isize, &NodeBox<K, V, A>)> = root.iter().map(|node| (0, node)).collect();
+        while let Some((col, node)) = queue.pop_front() {
+            buf.push((col, &node.key, &node.value));
+            queue.extend(node.left.iter().map(|left| (col - 1, left)));
+            queue.extend(node.right.iter().map(|right| (col + 1, right)));
+        }
+        // 层序遍历已经是从上到下、从左到右的，稳定排序不会打乱
+        buf.sort_by_key(|&(col, _, _)| col);
+        buf
+    }
+
+    //后序计算以root为根的树的高度（节点数），同时用经过每个节点的最长路径的边数更新best
+    pub fn diameter(root: &Link<K, V, A>, best: &mut usize) -> usize {
+        // 任务栈模拟递归，heights保存已经算完的子树高度，左子树的结果先入栈
+        let mut tasks = vec![(root, false)];
+        let mut heights = Vec::new();
+        while let Some((link, expanded)) = tasks.pop() {
+            match link {
+                None => heights.push(0),
+                Some(node) if expanded => {
+                    let right = heights.pop().unwrap_or(0);
+                    let left = heights.pop().unwrap_or(0);
+                    *best = (*best).max(left + right);
+                    heights.push(1 + left.max(right));
+                }
+                Some(node) => {
+                    tasks.push((link, true));
+                    tasks.push((&node.right, false));
+                    tasks.push((&node.left, false));
+                }
+            }
+        }
+        heights.pop().unwrap_or(0)
+    }
+
+    //判断两棵树的形状和每个位置上的键值对是否都相同
+    pub fn same_structure(a: &Link<K, V, A>, b: &Link<K, V, A>) -> bool
+    where
+        V: PartialEq,
+    {
+        let mut stack = vec![(a, b)];
+        while let Some(pair) = stack.pop() {
+            match pair {
+                (None, None) => {}
+                (Some(x), Some(y)) => {
+                    if x.key != y.key || x.value != y.value {
+                        return false;
+                    }
+                    stack.push((&x.right, &y.right));
+                    stack.push((&x.left, &y.left));
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    //判断树的形状是否关于根节点左右对称，只比较形状，不比较键值对
+    pub fn is_symmetric(root: &Link<K, V, A>) -> bool {
+        let node = match root {
+            Some(node) => node,
+            None => return true,
+        };
+        // 成对比较互为镜像位置上的子树
+        let mut stack = vec![(&node.left, &node.right)];
+        while let Some(pair) = stack.pop() {
+            match pair {
+                (None, None) => {}
+                (Some(x), Some(y)) => {
+                    stack.push((&x.left, &y.right));
+                    stack.push((&x.right, &y.left));
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    //消耗以link为根的树，交换每个节点的左右子树，键用Reverse包装，得到的仍是一棵合法的查找树，新节点从pool中分配
+    pub fn mirror(link: Link<K, V, A>, pool: &mut NodePool<Node<Reverse<K>, V, A>, A>) -> Link<Reverse<K>, V, A> {
+        enum Task<K, V, A: Allocator> {
+            Visit(Link<K, V, A>),
+            Build(K, V),
+        }
+        // 任务栈模拟递归，results保存已经镜像好的子树，右子树先处理，结果先入栈
+        let mut tasks = vec![Task::Visit(link)];
+        let mut results: Vec<Link<Reverse<K>, V, A>> = Vec::new();
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Visit(None) => results.push(None),
+                Task::Visit(Some(node)) => {
+                    let Node { key, value, left, right, .. } = *node;
+                    tasks.push(Task::Build(key, value));
+                    tasks.push(Task::Visit(left));
+                    tasks.push(Task::Visit(right));
+                }
+                Task::Build(key, value) => {
+                    let mirrored_left = results.pop().flatten();
+                    let mirrored_right = results.pop().flatten();
+                    let mut node = pool.alloc(Node::new(Reverse(key), value));
+                    // 原来的左子树成为镜像后的右子树
+                    node.left = mirrored_right;
+                    node.right = mirrored_left;
+                    results.push(Some(node));
+                }
+            }
+        }
+        results.pop().flatten()
+    }
+
+    //按从根到下的顺序收集查找key时经过的键值对，key不存在时到应该插入的位置为止
+    pub fn path_to<'a>(root: &'a Link<K, V, A>, key: &K, buf: &mut VecDeque<(&'a K, &'a V)>) {
+        let mut current = root.as_ref();
+        while let Some(node) = current {
+            buf.push_back((&node.key, &node.value));
+            current = if *key < node.key {
+                node.left.as_ref()
+            } else if *key > node.key {
+                node.right.as_ref()
+            } else {
+                None
+            };
+        }
+    }
+
+    //返回a和b的最近公共祖先，调用者需保证a和b都在以当前节点为根的树中
+    pub fn lca(&self, a: &K, b: &K) -> &Node<K, V, A> {
+        let mut node = self;
+        loop {
+            let child = if *a < node.key && *b < node.key {
+                node.left.as_ref()
+            } else if *a > node.key && *b > node.key {
+                node.right.as_ref()
+            } else {
+                None
+            };
+            // a、b分处两侧或其中一个就是当前节点时，当前节点就是最近公共祖先
+            match child {
+                Some(child) => node = child,
+                None => return node,
+            }
+        }
+    }
+
+    //返回从当前节点到键key所在节点的边数，调用者需保证key在以当前节点为根的树中
+    pub fn depth_of(&self, key: &K) -> usize {
+        let mut depth = 0;
+        let mut node = self;
+        loop {
+            let child = if *key < node.key {
+                node.left.as_ref()
+            } else if *key > node.key {
+                node.right.as_ref()
+            } else {
+                None
+            };
+            match child {
+                Some(child) => {
+                    node = child;
+                    depth += 1;
+                }
+                None => return depth,
+            }
+        }
+    }
+
+    //按前序把键写入buf，空链接写为#，记号之间以空格分隔
+    pub fn encode(root: &Link<K, V, A>, buf: &mut String)
+    where
+        K: fmt::Display,
+    {
+        let mut stack = vec![root];
+        while let Some(link) = stack.pop() {
+            if !buf.is_empty() {
+                buf.push(' ');
+            }
+            match link {
+                None => buf.push('#'),
+                Some(node) => {
+                    buf.push_str(&node.key.to_string());
+                    stack.push(&node.right);
+                    stack.push(&node.left);
+                }
+            }
+        }
+    }
+
+    //对link处的节点右旋，左孩子成为该位置的新根，调用者需保证左孩子存在
+    fn rotate_right(link: &mut Link<K, V, A>) {
+        if let Some(mut node) = link.take() {
+            match node.left.take() {
+                Some(mut left) => {
+                    metrics::rotate();
+                    node.left = left.right.take();
+                    left.right = Some(node);
+                    *link = Some(left);
+                }
+                None => *link = Some(node),
+            }
+        }
+    }
+
+    //自顶向下的伸展操作：沿查找路径向下，把路径拆成键都小于key的左树和键都大于key的右树，
+    //一字形的两步先旋转再拆，最后以键为key的节点为根把左右两树接回；调用者需保证key在树中。
+    //每次拆下节点相当于自底向上伸展中的一次旋转，与一字形的旋转一样计入旋转次数，
+    //因此旋转次数等于key的深度，与递归实现的计数一致
+    pub fn splay(root: &mut Link<K, V, A>, key: &K) {
+        let mut current = match root.take() {
+            Some(node) => node,
+            None => return,
+        };
+        let mut left_tree: Link<K, V, A> = None;
+        let mut right_tree: Link<K, V, A> = None;
+        //左树最右侧和右树最左侧的空链接，拆下的节点依次挂到这里
+        let mut left_slot = &mut left_tree;
+        let mut right_slot = &mut right_tree;
+        loop {
+            if *key < current.key {
+                let mut child = match current.left.take() {
+                    Some(child) => child,
+                    None => break,
+                };
+                if *key < child.key {
+                    metrics::rotate();
+                    current.left = child.right.take();
+                    child.right = Some(current);
+                    current = child;
+                    child = match current.left.take() {
+                        Some(child) => child,
+                        None => break,
+                    };
+                }
+                metrics::rotate();
+                let parent = right_slot.insert(current);
+                right_slot = &mut parent.left;
+                current = child;
+            } else if *key > current.key {
+                let mut child = match current.right.take() {
+                    Some(child) => child,
+                    None => break,
+                };
+                if *key > child.key {
+                    metrics::rotate();
+                    current.right = child.left.take();
+                    child.left = Some(current);
+                    current = child;
+                    child = match current.right.take() {
+                        Some(child) => child,
+                        None => break,
+                    };
+                }
+                metrics::rotate();
+                let parent = left_slot.insert(current);
+                left_slot = &mut parent.right;
+                current = child;
+            } else {
+                break;
+            }
+        }
+        *left_slot = current.left.take();
+        *right_slot = current.right.take();
+        current.left = left_tree;
+        current.right = right_tree;
+        *root = Some(current);
+    }
+
+    //不断右旋把以root为根的树原地展开成只有右孩子的有序链，不分配内存
+    pub fn flatten(root: &mut Link<K, V, A>) {
+        let mut tail = root;
+        loop {
+            if tail.as_ref().is_some_and(|node| node.left.is_some()) {
+                Self::rotate_right(tail);
+            } else if let Some(node) = tail {
+                tail = &mut node.right;
+            } else {
+                break;
+            }
+        }
+    }
+
+    //按键的升序对每个键值对调用f，f可以修改值
+    pub fn for_each_mut<F: FnMut(&K, &mut V)>(root: &mut Link<K, V, A>, f: &mut F) {
+        // 把节点拆成互不重叠的几部分，栈中保存还没有访问的键、值和右子树
+        let mut stack = Vec::new();
+        let mut current = root;
+        loop {
+            while let Some(node) = current {
+                let Node { key, value, left, right, .. } = &mut **node;
+                stack.push((&*key, value, right));
+                current = left;
+            }
+            match stack.pop() {
+                Some((key, value, right)) => {
+                    f(key, value);
+                    current = right;
+                }
+                None => break,
+            }
+        }
+    }
+
+    //按中序把名次在[start, end)内的键值对放入queue，到达end后不再继续访问
+    pub fn slice(root: &Link<K, V, A>, start: usize, end: usize) -> VecDeque<(&K, &V)> {
+        let mut queue = VecDeque::new();
+        let mut stack = Vec::new();
+        let mut current = root.as_ref();
+        let mut rank = 0;
+        while rank < end {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_ref();
+            }
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            if rank >= start {
+                queue.push_back((&node.key, &node.value));
+            }
+            rank += 1;
+            current = node.right.as_ref();
+        }
+        queue
+    }
+
+    //按中序返回以root为根的树中每个节点的键和平衡因子(左子树高度 - 右子树高度)，空树高度为0
+    pub fn balance_factors(root: &Link<K, V, A>) -> Vec<(&K, isize)> {
+        let mut out = Vec::new();
+        //已经算出的子树高度，右子树的高度在左子树之上
+        let mut heights: Vec<usize> = Vec::new();
+        //(链接, 阶段, 节点在out中的位置)，阶段0时还没有访问左子树，1时左子树已经算完，2时右子树也已经算完
+        let mut stack: Vec<(&Link<K, V, A>, u8, usize)> = vec![(root, 0, 0)];
+        while let Some((link, stage, index)) = stack.pop() {
+            let node = match link {
+                Some(node) => node,
+                None => {
+                    heights.push(0);
+                    continue;
+                }
+            };
+            match stage {
+                0 => {
+                    stack.push((link, 1, 0));
+                    stack.push((&node.left, 0, 0));
+                }
+                1 => {
+                    out.push((&node.key, 0));
+                    stack.push((link, 2, out.len() - 1));
+                    stack.push((&node.right, 0, 0));
+                }
+                _ => {
+                    let right = heights.pop().unwrap();
+                    let left = heights.pop().unwrap();
+                    out[index].1 = left as isize - right as isize;
+                    heights.push(1 + left.max(right));
+                }
+            }
+        }
+        out
+    }
+
+    //按中序从最小的节点开始，把满足f的节点依次摘下放入buf，遇到第一个不满足f的节点就停止；
+    //摘下左侧链上的节点放入栈中，栈顶总是剩余的最小节点，结束时由PopWalk把栈中的节点依次接回
+    pub fn pop_while<F: FnMut(&K, &V) -> bool>(root: &mut Link<K, V, A>, mut f: F, buf: &mut Vec<NodeBox<K, V, A>>) {
+        let mut rest = root.take();
+        let mut walk = PopWalk { root, stack: Vec::new() };
+        loop {
+            while let Some(mut node) = rest {
+                rest = node.left.take();
+                walk.stack.push(node);
+            }
+            //栈顶节点留在栈中调用f，f发生panic时它也会被接回树中
+            match walk.stack.last() {
+                Some(node) if f(&node.key, &node.value) => {
+                    let mut node = walk.stack.pop().unwrap();
+                    rest = node.right.take();
+                    buf.push(node);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    //返回以root为根的树中节点的个数
+    pub fn size(root: &Link<K, V, A>) -> usize {
+        let mut count = 0;
+        let mut stack: Vec<&NodeBox<K, V, A>> = root.iter().collect();
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.left.iter());
+            stack.extend(node.right.iter());
+        }
+        count
+    }
+
+    //统计以root为根的树中节点的个数，数到limit就停止，返回min(个数, limit)
+    pub fn size_capped(root: &Link<K, V, A>, limit: usize) -> usize {
+        let mut count = 0;
+        let mut stack: Vec<&NodeBox<K, V, A>> = root.iter().collect();
+        while count < limit {
+            match stack.pop() {
+                Some(node) => {
+                    count += 1;
+                    stack.extend(node.left.iter());
+                    stack.extend(node.right.iter());
+                }
+                None => break,
+            }
+        }
+        count
+    }
+
+    //随机化插入：在大小为s的子树处以1/(s+1)的概率把新节点插为该子树的根，否则继续向下，
+    //调用者需保证新节点没有子树，且它的键不在树中
+    pub fn insert_randomized(root: &mut Link<K, V, A>, mut new: NodeBox<K, V, A>) {
+        let mut link = root;
+        loop {
+            let limit = random::root_insert_limit();
+            if Self::size_capped(link, limit) < limit {
+                let (left, right) = Self::split(link.take(), |k| *k < new.key);
+                new.left = left;
+                new.right = right;
+                *link = Some(new);
+                return;
+            }
+            // 子树大小不小于limit，一定不为空
+            let node = link.as_mut().unwrap();
+            link = if new.key < node.key { &mut node.left } else { &mut node.right };
+        }
+    }
+
+    //前序检查每个节点，删除键值对满足f的节点为根的树枝，节点放回pool，返回删除的节点个数
+    pub fn prune<F: FnMut(&K, &V) -> bool>(root: &mut Link<K, V, A>, f: &mut F, pool: &mut NodePool<Node<K, V, A>, A>) -> usize {
+        let mut removed = 0;
+        let mut stack = vec![root];
+        while let Some(link) = stack.pop() {
+            if link.as_ref().is_some_and(|node| f(&node.key, &node.value)) {
+                Self::release_in_order(link.take(), pool, &mut |_, _| removed += 1);
+            } else if let Some(node) = link {
+                stack.push(&mut node.right);
+                stack.push(&mut node.left);
+            }
+        }
+        removed
+    }
+
+    //按order遍历以root为根的树，f返回Break时立即停止，不生成中间结果
+    pub fn walk<'a, B, C, F>(root: &'a Link<K, V, A>, order: Order, f: &mut F) -> ControlFlow<B>
+    where
+        C: Into<Visit>,
+        F: FnMut(&'a K, &'a V) -> ControlFlow<B, C>,
+    {
+        match order {
+            Order::PreOrder => {
+                let mut stack: Vec<&NodeBox<K, V, A>> = root.iter().collect();
+                while let Some(node) = stack.pop() {
+                    if f(&node.key, &node.value)?.into() == Visit::Children {
+                        stack.extend(node.right.iter());
+                        stack.extend(node.left.iter());
+                    }
+                }
+            }
+            Order::InOrder => {
+                let mut stack = Vec::new();
+                let mut current = root.as_ref();
+                loop {
+                    while let Some(node) = current {
+                        stack.push(node);
+                        current = node.left.as_ref();
+                    }
+                    let node = match stack.pop() {
+                        Some(node) => node,
+                        None => break,
+                    };
+                    current = if f(&node.key, &node.value)?.into() == Visit::Children {
+                        node.right.as_ref()
+                    } else {
+                        None
+                    };
+                }
+            }
+            Order::PostOrder => {
+                let mut stack = Vec::new();
+                let mut current = root.as_ref();
+                let mut prev: Option<&NodeBox<K, V, A>> = None;
+                loop {
+                    while let Some(node) = current {
+                        stack.push(node);
+                        current = node.left.as_ref();
+                    }
+                    let top = match stack.last() {
+                        Some(top) => *top,
+                        None => break,
+                    };
+                    // 右子树存在且还没有访问过时，先访问右子树
+                    match top.right.as_ref() {
+                        Some(right) if !prev.is_some_and(|p| ptr::eq(p, right)) => current = Some(right),
+                        _ => {
+                            f(&top.key, &top.value)?;
+                            prev = stack.pop();
+                        }
+                    }
+                }
+            }
+            Order::LevelOrder => {
+                let mut queue: VecDeque<&NodeBox<K, V, A>> = root.iter().collect();
+                while let Some(node) = queue.pop_front() {
+                    if f(&node.key, &node.value)?.into() == Visit::Children {
+                        queue.extend(node.left.iter());
+                        queue.extend(node.right.iter());
+                    }
+                }
+            }
+            Order::ZigZag => {
+                let mut level: Vec<&NodeBox<K, V, A>> = root.iter().collect();
+                let mut left_to_right = true;
+                while !level.is_empty() {
+                    // level始终按从左到右保存，从右到左访问时倒着取，并倒着收集下一层
+                    let mut next = Vec::new();
+                    for i in 0..level.len() {
+                        let node = if left_to_right { level[i] } else { level[level.len() - 1 - i] };
+                        if f(&node.key, &node.value)?.into() == Visit::Children {
+                            if left_to_right {
+                                next.extend(node.left.iter());
+                                next.extend(node.right.iter());
+                            } else {
+                                next.extend(node.right.iter());
+                                next.extend(node.left.iter());
+                            }
+                        }
+                    }
+                    if !left_to_right {
+                        next.reverse();
+                    }
+                    level = next;
+                    left_to_right = !left_to_right;
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    //按键的升序对range范围内的键值对累积计算，跳过不在范围内的子树
+    pub fn fold_range<'a, B, R, F>(root: &'a Link<K, V, A>, range: &R, mut acc: B, f: &mut F) -> B
+    where
+        R: RangeBounds<K>,
+        F: FnMut(B, &'a K, &'a V) -> B,
+    {
+        let mut stack = Vec::new();
+        let mut current = root.as_ref();
+        loop {
+            while let Some(node) = current {
+                if range::below(&node.key, range) {
+                    // 当前节点和它的左子树都在范围之外
+                    current = node.right.as_ref();
+                } else {
+                    stack.push(node);
+                    current = node.left.as_ref();
+                }
+            }
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            if range::above(&node.key, range) {
+                break;
+            }
+            acc = f(acc, &node.key, &node.value);
+            current = node.right.as_ref();
+        }
+        acc
+    }
+
+    //按升序收集keys中各个键对应值的可变借用，keys必须严格升序，不存在的键会被跳过
+    pub fn values_mut<'a>(link: &'a mut Link<K, V, A>, keys: &[&K], buf: &mut Vec<&'a mut V>) {
+        enum Task<'a, 'k, K, V, A: Allocator> {
+            Visit(&'a mut Link<K, V, A>, &'k [&'k K]),
+            Emit(&'a mut V),
+        }
+        let mut tasks = vec![Task::Visit(link, keys)];
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Emit(value) => buf.push(value),
+                Task::Visit(Some(node), keys) if !keys.is_empty() => {
+                    let Node { key, value, left, right, .. } = &mut **node;
+                    // keys有序，小于、等于、大于当前键的部分各自连续
+                    let lower = keys.iter().take_while(|k| **k < key).count();
+                    let upper = keys.iter().take_while(|k| **k <= key).count();
+                    tasks.push(Task::Visit(right, &keys[upper..]));
+                    if upper > lower {
+                        tasks.push(Task::Emit(value));
+                    }
+                    tasks.push(Task::Visit(left, &keys[..lower]));
+                }
+                Task::Visit(..) => {}
+            }
         }
     }
 
     //找出当前树中值最小的节点，返回元组:(除去最小节点后剩下的树，最小节点)
-    fn remove_min(mut self) -> (Link<K, V>, Box<Node<K, V>>) {
-        let mut current = &mut self;
+    fn remove_min(mut node: NodeBox<K, V, A>) -> (Link<K, V, A>, NodeBox<K, V, A>) {
+        let mut current = &mut node;
         while let Some(mut left) = current.left.take() {
                 if left.left.is_none() {
                     current.left = left.right.take();
-                    return (Some(Box::new(self)), left);
+                    return (Some(node), left);
                 }
                 else {
                     current.left = Some(left);
@@ -39,35 +1061,267 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
                 }
 
         }
-        (self.right.take(), Box::new(self))
+        (node.right.take(), node)
     }
 
     //将两棵子树合并为一棵，返回新生成树的根节点
     fn combine_two_subtrees(
-        left: Node<K, V>,
-        right: Node<K, V>,
-    ) -> Box<Node<K, V>> {
+        left: NodeBox<K, V, A>,
+        right: NodeBox<K, V, A>,
+    ) -> NodeBox<K, V, A> {
         // 得到右子树中最小的节点和去除最小节点后剩余的树
-        let (remain_tree, min) = right.remove_min();
+        let (remain_tree, min) = Self::remove_min(right);
         // 最小节点作为两个子树的新根节点
         let mut new_root = min;
         new_root.right = remain_tree;
-        new_root.left = Some(Box::new(left));
+        new_root.left = Some(left);
         new_root
     }
 
-    //删除当前节点，并返回新的根节点
-    pub fn delete_root(mut self) -> Link<K, V> {
+    //删除节点后，将它的左右子树合并为一棵，返回新的根节点
+    fn merge_children(left: Link<K, V, A>, right: Link<K, V, A>) -> Link<K, V, A> {
         // 二叉搜索树树删除节点的三种情况：
         // 1.如果是叶子节点，则直接删除
         // 2.如果待删除节点只有左子树或只有右子树，删除该节点，然后将左子树或右子树移动到该节点
         // 3.如果待删除节点左右子树都有，就选取右子树中最小的节点代替待删除节点的位置(或者取左子树中最大节点代替也可以)。
-        match (self.left.take(), self.right.take()) {
-            (None, None) => None,
-            (Some(left), None) => Some(left),
-            (None, Some(right)) => Some(right),
-            (Some(left), Some(right)) => Some(Self::combine_two_subtrees(*left, *right)),
+        match (left, right) {
+            (None, None) => {
+                trace::delete("leaf");
+                None
+            }
+            (Some(child), None) | (None, Some(child)) => {
+                trace::delete("one child");
+                Some(child)
+            }
+            (Some(left), Some(right)) => {
+                trace::delete("two children");
+                Some(Self::combine_two_subtrees(left, right))
+            }
         }
     }
+
+    //把节点从树中摘下，返回新的根节点以及摘下的节点，摘下的节点没有子树，可以重新挂到树上
+    pub fn unlink(mut node: NodeBox<K, V, A>) -> (Link<K, V, A>, NodeBox<K, V, A>) {
+        let new_root = Self::merge_children(node.left.take(), node.right.take());
+        (new_root, node)
+    }
+
+    //用f(键, 旧值)得到新值写回link处的节点，节点仍留在原位置；
+    //f发生panic时旧值已经交给了f，ValueHole把节点的左右子树合并后接回link，树的其余部分保持不变
+    pub fn resolve_value<F: FnOnce(&K, V) -> V>(link: &mut Link<K, V, A>, pool: &mut NodePool<Node<K, V, A>, A>, f: F) {
+        let node = match link.take() {
+            Some(node) => pool.release(node),
+            None => return,
+        };
+        let Node { key, value, left, right, .. } = node;
+        let mut hole = ValueHole { link, children: Some((left, right)) };
+        let value = f(&key, value);
+        let (left, right) = hole.children.take().unwrap();
+        let mut node = Node::new(key, value);
+        node.left = left;
+        node.right = right;
+        *hole.link = Some(pool.alloc(node));
+    }
 }
 
+impl<K: PartialOrd + Clone, V> Node<K, V> {
+    //并行地由按键严格升序排列的键值对构建平衡的树，取走items中的元素，左右子树交给rayon在不同线程上构建，递归深度只有log(n/阈值)层
+    #[cfg(feature = "rayon")]
+    pub fn par_build_sorted(items: &mut [Option<(K, V)>]) -> Link<K, V>
+    where
+        K: Send,
+        V: Send,
+    {
+        // 数据量较小时并行的开销超过收益，直接顺序构建
+        if items.len() <= PAR_BUILD_THRESHOLD {
+            return Self::build_sorted(items.iter_mut().filter_map(Option::take).collect(), &mut NodePool::new_in(Global));
+        }
+        // 与顺序构建取相同的根节点，保证树的形状一致，切分切片不需要移动数据
+        let (left, rest) = items.split_at_mut(items.len() / 2);
+        let (mid, right) = rest.split_first_mut()?;
+        let (key, value) = mid.take()?;
+        let (left, right) = rayon::join(
+            || Self::par_build_sorted(left),
+            || Self::par_build_sorted(right),
+        );
+        let mut node = Node::new(key, value);
+        node.left = left;
+        node.right = right;
+        Some(Box::new(node))
+    }
+
+    //由键的前序序列还原树，不属于前序序列的部分留在items中
+    pub fn from_preorder<I: Iterator<Item = (K, V)>>(items: &mut Peekable<I>) -> Link<K, V> {
+        let mut root = None;
+        // 栈中保存等待填充的链接，以及该位置上的键必须满足的(low, high)范围，下一个键不在范围内时该位置为空
+        let mut slots = vec![(&mut root, None, None)];
+        while let Some((slot, low, high)) = slots.pop() {
+            let fits = items.peek().is_some_and(|(key, _)| {
+                low.as_ref().is_none_or(|low| *key > *low) && high.as_ref().is_none_or(|high| *key < *high)
+            });
+            if !fits {
+                continue;
+            }
+            let (key, value) = match items.next() {
+                Some(item) => item,
+                None => break,
+            };
+            let node = slot.insert(Box::new(Node::new(key.clone(), value)));
+            let Node { left, right, .. } = &mut **node;
+            slots.push((right, Some(key.clone()), high));
+            slots.push((left, low, Some(key)));
+        }
+        root
+    }
+
+    //由层序序列还原树，不属于层序序列的部分留在items中
+    pub fn from_level_order<I: Iterator<Item = (K, V)>>(items: &mut Peekable<I>) -> Link<K, V> {
+        let mut root = None;
+        // 队列中按层序保存等待填充的链接，以及该位置上的键必须满足的(low, high)范围，下一个键不在范围内时该位置为空
+        let mut slots = VecDeque::new();
+        slots.push_back((&mut root, None, None));
+        while let Some((slot, low, high)) = slots.pop_front() {
+            let fits = items.peek().is_some_and(|(key, _)| {
+                low.as_ref().is_none_or(|low| *key > *low) && high.as_ref().is_none_or(|high| *key < *high)
+            });
+            if !fits {
+                continue;
+            }
+            let (key, value) = match items.next() {
+                Some(item) => item,
+                None => break,
+            };
+            let node = slot.insert(Box::new(Node::new(key.clone(), value)));
+            let Node { left, right, .. } = &mut **node;
+            slots.push_back((left, low, Some(key.clone())));
+            slots.push_back((right, Some(key), high));
+        }
+        root
+    }
+
+    //从前序记号中还原一棵树，值取默认值
+    pub fn decode<'s, I>(tokens: &mut I) -> Result<Link<K, V>, DecodeError>
+    where
+        I: Iterator<Item = &'s str>,
+        K: FromStr,
+        V: Default,
+    {
+        let mut root = None;
+        // 栈中保存等待填充的链接，以及该位置上的键必须满足的(low, high)范围
+        let mut slots = vec![(&mut root, None, None)];
+        while let Some((slot, low, high)) = slots.pop() {
+            let token = tokens.next().ok_or(DecodeError::UnexpectedEnd)?;
+            if token == "#" {
+                continue;
+            }
+            let key: K = token.parse().map_err(|_| DecodeError::InvalidKey(token.to_string()))?;
+            if low.as_ref().is_some_and(|low| key <= *low) || high.as_ref().is_some_and(|high| key >= *high) {
+                return Err(DecodeError::OutOfOrder(token.to_string()));
+            }
+            let node = slot.insert(Box::new(Node::new(key.clone(), V::default())));
+            let Node { left, right, .. } = &mut **node;
+            slots.push((right, Some(key.clone()), high));
+            slots.push((left, low, Some(key)));
+        }
+        Ok(root)
+    }
+}
+
+//for_each_in_place遍历中的状态：cur是当前节点，up是由反转的链接串起来的祖先节点
+//离开作用域时沿up恢复全部反转的链接并把树放回root，f发生panic时栈展开也会经过这里，树不会随之被释放
+struct InPlaceWalk<'a, K: PartialOrd, V, A: Allocator + Clone> {
+    root: &'a mut Link<K, V, A>,
+    cur: Link<K, V, A>,
+    up: Link<K, V, A>,
+}
+
+impl<K: PartialOrd, V, A: Allocator + Clone> InPlaceWalk<'_, K, V, A> {
+    fn run<F: FnMut(&K, &V)>(&mut self, f: &mut F) {
+        'down: loop {
+            while let Some(left) = self.cur.as_mut().and_then(|cur| cur.left.take()) {
+                if let Some(cur) = self.cur.as_mut() {
+                    cur.left = self.up.take();
+                }
+                self.up = self.cur.replace(left);
+            }
+            loop {
+                //cur的左子树已经访问完毕
+                let cur = match self.cur.as_mut() {
+                    Some(cur) => cur,
+                    None => return,
+                };
+                f(&cur.key, &cur.value);
+                if let Some(right) = cur.right.take() {
+                    cur.right = self.up.take();
+                    self.up = self.cur.replace(right);
+                    continue 'down;
+                }
+                //cur的右子树也访问完毕，向上回溯直到从某个节点的左侧回来
+                loop {
+                    match self.up.take() {
+                        Some(parent) => {
+                            if self.ascend(parent) {
+                                break;
+                            }
+                        }
+                        None => return,
+                    }
+                }
+            }
+        }
+    }
+
+    //沿反转的链接回到父节点parent，恢复parent指向cur的链接，返回是否从parent的左侧回来
+    fn ascend(&mut self, mut parent: NodeBox<K, V, A>) -> bool {
+        let from_left = self.cur.as_ref().is_some_and(|cur| cur.key < parent.key);
+        let link = if from_left { &mut parent.left } else { &mut parent.right };
+        self.up = mem::replace(link, self.cur.take());
+        self.cur = Some(parent);
+        from_left
+    }
+}
+
+impl<K: PartialOrd, V, A: Allocator + Clone> Drop for InPlaceWalk<'_, K, V, A> {
+    fn drop(&mut self) {
+        while let Some(parent) = self.up.take() {
+            self.ascend(parent);
+        }
+        *self.root = self.cur.take();
+    }
+}
+
+//pop_while中摘下的左侧链：栈中下面的节点都比上面的大，每个节点的右子树仍然挂在节点上
+//离开作用域时把上面的节点接回为下面节点的左子树并放回root，f发生panic时栈展开也会经过这里
+struct PopWalk<'a, K: PartialOrd, V, A: Allocator + Clone> {
+    root: &'a mut Link<K, V, A>,
+    stack: Vec<NodeBox<K, V, A>>,
+}
+
+impl<K: PartialOrd, V, A: Allocator + Clone> Drop for PopWalk<'_, K, V, A> {
+    fn drop(&mut self) {
+        let mut link = None;
+        while let Some(mut node) = self.stack.pop() {
+            node.left = link;
+            link = Some(node);
+        }
+        *self.root = link;
+    }
+}
+
+//resolve_value调用f期间，节点的键值已经取出，children暂存它的左右子树；
+//f发生panic时栈展开经过这里，把两棵子树合并后接回link，只丢失正在合并的键值对
+struct ValueHole<'a, K: PartialOrd + Clone, V, A: Allocator + Clone> {
+    link: &'a mut Link<K, V, A>,
+    children: Option<Children<K, V, A>>,
+}
+
+//节点的左右子树
+type Children<K, V, A> = (Link<K, V, A>, Link<K, V, A>);
+
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> Drop for ValueHole<'_, K, V, A> {
+    fn drop(&mut self) {
+        if let Some((left, right)) = self.children.take() {
+            *self.link = Node::merge_children(left, right);
+        }
+    }
+}
@@ -1,9 +1,35 @@
-use std::collections::VecDeque;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::fmt;
+use std::mem;
+use std::ops::{AddAssign, Bound, ControlFlow, Index, IndexMut, RangeBounds};
+use std::str::FromStr;
+use crate::allocator::{Allocator, Global};
+use crate::bstree_recursion::entry::OccupiedEntry;
 use crate::bstree_recursion::node::{Link, Node};
-use crate::iterator::TraverseIter;
+#[cfg(feature = "rayon")]
+use crate::bstree_recursion::par_iter::ParIter;
+use crate::bstree_recursion::walker::{InOrder, Walker};
+use crate::binary_search_tree::BinarySearchTree;
+use crate::change::Change;
+use crate::cursor::{Cursor, Neighbor, Neighbors};
+use crate::distance::Distance;
+use crate::error::{DecodeError, GetManyMutError, GraftError, KeyNotFound, OccupiedError, RekeyError};
+use crate::iterator::{DepthIter, IntoKeys, IntoValues, ParentIter, TraverseIter};
+use crate::merge::{Difference, Intersection, MergeIter};
+use crate::policy::{DuplicatePolicy, Tie};
+use crate::pool::NodePool;
+use crate::random;
+use crate::range;
+use crate::sorted;
+use crate::trace;
+use crate::validate;
+use crate::walk::{Order, Visit};
 
-pub struct BSTree<K, V> {
-    root: Link<K, V>,
+pub struct BSTree<K, V, A: Allocator = Global> {
+    root: Link<K, V, A>,
+    pool: NodePool<Node<K, V, A>, A>,
 }
 
 impl<K: PartialOrd + Clone, V> Default for BSTree<K, V> {
@@ -12,254 +38,2272 @@ impl<K: PartialOrd + Clone, V> Default for BSTree<K, V> {
     }
 }
 
+/// 由`BTreeMap`构建一棵平衡的二叉查找树，`BTreeMap`的键已严格升序，只需O(n)时间
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let map = BTreeMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+/// let tree = BSTree::from(map);
+/// let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+/// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
+/// ```
+impl<K: Ord + Clone, V> From<BTreeMap<K, V>> for BSTree<K, V> {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        Self::bulk_load(map)
+    }
+}
+
+/// 将树中的全部键值对转移到`BTreeMap`中
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let tree = BSTree::bulk_load(vec![(2, 'b'), (1, 'a')]);
+/// let map = BTreeMap::from(tree);
+/// assert_eq!(map, BTreeMap::from([(1, 'a'), (2, 'b')]));
+/// ```
+impl<K: Ord + Clone, V, A: Allocator + Clone> From<BSTree<K, V, A>> for BTreeMap<K, V> {
+    fn from(tree: BSTree<K, V, A>) -> Self {
+        let mut entries = Vec::new();
+        Node::drain_in_order(tree.root, &mut entries);
+        entries.into_iter().collect()
+    }
+}
+
+/// 两棵树包含相同的键值对时相等，与树的形状无关
+/// # Example
+/// ```
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let a = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+/// let mut b = BSTree::new();
+/// for (k, v) in [(1, 'a'), (2, 'b'), (3, 'c')] {
+///     b.insert(k, v);
+/// }
+/// assert!(a == b);
+/// b.insert(3, 'x');
+/// assert!(a != b);
+/// ```
+impl<K: PartialOrd + Clone, V: PartialEq, A: Allocator + Clone> PartialEq for BSTree<K, V, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inorder_iter().eq(other.inorder_iter())
+    }
+}
+
+impl<K: Ord + Clone, V: Eq, A: Allocator + Clone> Eq for BSTree<K, V, A> {}
+
+/// 按中序的键值对序列逐项比较两棵树，即字典序，与树的形状无关
+/// # Example
+/// ```
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let a = BSTree::bulk_load(vec![(1, 'a'), (2, 'b')]);
+/// let b = BSTree::bulk_load(vec![(1, 'a'), (3, 'c')]);
+/// let c = BSTree::bulk_load(vec![(1, 'a')]);
+/// assert!(a < b);
+/// assert!(c < a);
+/// let mut trees = vec![b, a, c];
+/// trees.sort();
+/// assert_eq!(trees[0].max_pair(), Some((&1, &'a')));
+/// ```
+impl<K: PartialOrd + Clone, V: PartialOrd, A: Allocator + Clone> PartialOrd for BSTree<K, V, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.inorder_iter().partial_cmp(other.inorder_iter())
+    }
+}
+
+impl<K: Ord + Clone, V: Ord, A: Allocator + Clone> Ord for BSTree<K, V, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.inorder_iter().cmp(other.inorder_iter())
+    }
+}
+
+/// 按键取值，与标准库的映射一样，键不存在时panic
+/// # Example
+/// ```
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b')]);
+/// assert_eq!(tree[&2], 'b');
+/// ```
+/// ```should_panic
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let tree = BSTree::bulk_load(vec![(1, 'a')]);
+/// let _ = tree[&3];
+/// ```
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> Index<&K> for BSTree<K, V, A> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("key not found in BSTree")
+    }
+}
+
+/// 按键修改值，键不存在时panic
+/// # Example
+/// ```
+/// use an_ok_bstree::RecursiveBSTree as BSTree;
+/// let mut tree = BSTree::bulk_load(vec![(1, 10), (2, 20)]);
+/// tree[&2] += 1;
+/// assert_eq!(tree[&2], 21);
+/// ```
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> IndexMut<&K> for BSTree<K, V, A> {
+    fn index_mut(&mut self, key: &K) -> &mut V {
+        self.get_mut(key).expect("key not found in BSTree")
+    }
+}
+
+impl<K: PartialOrd + Clone, V> BinarySearchTree<K, V> for BSTree<K, V> {
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.insert(key, value)
+    }
+
+    fn delete(&mut self, key: K) {
+        self.delete(key)
+    }
+
+    fn take(&mut self, key: &K) -> Option<(K, V)> {
+        self.take(key)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.contains(key)
+    }
+
+    fn min_pair(&self) -> Option<(&K, &V)> {
+        self.min_pair()
+    }
+
+    fn max_pair(&self) -> Option<(&K, &V)> {
+        self.max_pair()
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.successor(key)
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.predecessor(key)
+    }
+
+    fn preorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.preorder_iter()
+    }
+
+    fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.inorder_iter()
+    }
+
+    fn postorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.postorder_iter()
+    }
+
+    fn levelorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.levelorder_iter()
+    }
+}
+
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> Neighbors<K, V> for BSTree<K, V, A> {
+    fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
+        self.get_pair(key)
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.successor(key)
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.predecessor(key)
+    }
+
+    fn min_pair(&self) -> Option<(&K, &V)> {
+        self.min_pair()
+    }
+
+    fn max_pair(&self) -> Option<(&K, &V)> {
+        self.max_pair()
+    }
+}
+
 impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// 构建一棵空的二叉查找树
     /// # Examples
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree: BSTree<i32, i32> = BSTree::new();
     /// ```
     pub fn new() -> Self {
-        BSTree { root: None }
+        BSTree::from_root(None)
+    }
+
+    /// 由键值对序列构建一棵平衡的二叉查找树，输入已按键严格升序时只需O(n)时间，否则先排序，重复的键保留最后出现的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+    /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
+    /// ```
+    pub fn bulk_load<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let items = sorted::sort_dedup(iter.into_iter().collect());
+        trace::rebuild("bulk_load", items.len());
+        BSTree::from_sorted_in(items, Global)
+    }
+
+    /// 并行构建一棵平衡的二叉查找树，左右子树在不同的线程上独立构建后再拼接，适合数据量很大的批量加载，需要开启`rayon`特性
+    /// 输入已按键严格升序时不再排序，否则先排序，重复的键保留最后出现的值，得到的树与`bulk_load`完全相同
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::from_sorted_vec((0..10000).map(|i| (i, i * 2)).collect());
+    /// assert_eq!(tree.get(&4321), Some(&8642));
+    /// assert_eq!(tree.min_pair(), Some((&0, &0)));
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn from_sorted_vec(items: Vec<(K, V)>) -> Self
+    where
+        K: Send,
+        V: Send,
+    {
+        let mut items: Vec<Option<(K, V)>> = sorted::sort_dedup(items).into_iter().map(Some).collect();
+        trace::rebuild("from_sorted_vec", items.len());
+        BSTree::from_root(Node::par_build_sorted(&mut items))
+    }
+
+    /// 由键的前序序列和对应的值还原出唯一的一棵查找树，只需O(n)时间，与`encode_structure`配合可以完整地保存和恢复树；
+    /// 键的个数与值的个数不同，或键的序列不是任何查找树的前序序列时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::from_preorder(vec![8, 5, 3, 6, 15], vec!['h', 'e', 'c', 'f', 'o']).unwrap();
+    /// assert_eq!(tree.encode_structure(), "8 5 3 # # 6 # # 15 # #");
+    /// assert_eq!(tree.get(&6), Some(&'f'));
+    /// assert!(BSTree::from_preorder(vec![2, 3, 1], vec![(); 3]).is_none());
+    /// ```
+    pub fn from_preorder<I, J>(keys: I, values: J) -> Option<Self>
+    where
+        I: IntoIterator<Item = K>,
+        J: IntoIterator<Item = V>,
+    {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let values: Vec<V> = values.into_iter().collect();
+        if keys.len() != values.len() {
+            return None;
+        }
+        let mut items = keys.into_iter().zip(values).peekable();
+        let root = Node::from_preorder(&mut items, None, None);
+        // 还有剩余的键说明序列不合法
+        match items.next() {
+            Some(_) => None,
+            None => Some(BSTree::from_root(root)),
+        }
+    }
+
+    /// 由层序序列还原出唯一的一棵查找树，是`levelorder_iter`的逆操作，只需O(n)时间；
+    /// 序列不是任何查找树的层序序列时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::from_level_order(vec![(8, 'h'), (5, 'e'), (15, 'o'), (3, 'c'), (6, 'f')]).unwrap();
+    /// assert_eq!(tree.encode_structure(), "8 5 3 # # 6 # # 15 # #");
+    /// assert!(BSTree::from_level_order(vec![(8, ()), (5, ()), (9, ()), (7, ()), (6, ())]).is_some());
+    /// assert!(BSTree::from_level_order(vec![(8, ()), (5, ()), (5, ())]).is_none());
+    /// ```
+    pub fn from_level_order<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Option<Self> {
+        let mut items = pairs.into_iter().peekable();
+        let root = Node::from_level_order(&mut items);
+        // 还有剩余的键说明序列不合法
+        match items.next() {
+            Some(_) => None,
+            None => Some(BSTree::from_root(root)),
+        }
+    }
+
+    /// 从`encode_structure`生成的文本还原出形状完全相同的树，值取默认值；
+    /// 记号无法解析、键的顺序不合法、输入不完整或有多余内容时返回错误
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, DecodeError};
+    /// let tree: BSTree<i32, ()> = BSTree::decode_structure("8 5 # 6 # # 15 # #").unwrap();
+    /// let res: Vec<&i32> = tree.preorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&8, &5, &6, &15]);
+    /// assert_eq!(BSTree::<i32, ()>::decode_structure("8 9 # # #").err(), Some(DecodeError::OutOfOrder("9".to_string())));
+    /// assert_eq!(BSTree::<i32, ()>::decode_structure("8 #").err(), Some(DecodeError::UnexpectedEnd));
+    /// ```
+    pub fn decode_structure(s: &str) -> Result<Self, DecodeError>
+    where
+        K: FromStr,
+        V: Default,
+    {
+        let mut tokens = s.split_whitespace();
+        let root = Node::decode(&mut tokens)?;
+        match tokens.next() {
+            Some(token) => Err(DecodeError::TrailingInput(token.to_string())),
+            None => Ok(BSTree::from_root(root)),
+        }
+    }
+
+    // 由根节点构建树，节点从全局分配器中分配
+    fn from_root(root: Link<K, V>) -> Self {
+        BSTree::from_root_in(root, Global)
+    }
+}
+
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> BSTree<K, V, A> {
+    /// 构建一棵空的二叉查找树，之后的节点都从alloc中分配；
+    /// 稳定版编译器上只有全局分配器`Global`，开启`allocator_api`特性(需要nightly编译器)后可以传入任意`Allocator`，例如bump或arena分配器的引用
+    /// # Example
+    /// ```
+    /// # #![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+    /// use an_ok_bstree::{Global, RecursiveBSTree as BSTree};
+    /// let mut tree: BSTree<i32, char> = BSTree::new_in(Global);
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// ```
+    pub fn new_in(alloc: A) -> Self {
+        BSTree::from_root_in(None, alloc)
+    }
+
+    /// 返回分配节点使用的分配器
+    /// # Example
+    /// ```
+    /// # #![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+    /// use an_ok_bstree::{Global, RecursiveBSTree as BSTree};
+    /// let tree: BSTree<i32, char> = BSTree::new();
+    /// let _: &Global = tree.allocator();
+    /// ```
+    pub fn allocator(&self) -> &A {
+        self.pool.allocator()
+    }
+
+    // 由根节点构建树，节点池为空，之后的节点从alloc中分配
+    fn from_root_in(root: Link<K, V, A>, alloc: A) -> Self {
+        BSTree {
+            root,
+            pool: NodePool::new_in(alloc),
+        }
+    }
+
+    // 由按键严格升序排列的键值对构建一棵平衡的树，节点从alloc中分配
+    fn from_sorted_in(items: Vec<(K, V)>, alloc: A) -> Self {
+        let mut pool = NodePool::new_in(alloc);
+        let n = items.len();
+        let root = Node::build_sorted(&mut items.into_iter(), n, &mut pool);
+        BSTree { root, pool }
+    }
+
+    // 开启debug-validate特性的调试构建中检查查找树的性质，不满足时panic，op为刚刚执行的操作
+    fn debug_validate(&self, op: &str) {
+        if validate::ENABLED {
+            validate::check_order(self.in_order().iter(), op);
+        }
+    }
+
+    // 执行交出值的可变借用的修改操作f；开启检查时先检查查找树的性质，再按key重新找到值交出借用
+    fn try_validated_mut<E, F>(&mut self, key: K, op: &str, f: F) -> Result<&mut V, E>
+    where
+        F: FnOnce(&mut Self, K) -> Result<&mut V, E>,
+    {
+        if !validate::ENABLED {
+            return f(self, key);
+        }
+        let probe = key.clone();
+        f(self, key)?;
+        self.debug_validate(op);
+        Ok(self.get_mut(&probe).expect("the key was just inserted"))
+    }
+
+    // 与try_validated_mut相同，用于不会失败的修改操作
+    fn validated_mut<F: FnOnce(&mut Self, K) -> &mut V>(&mut self, key: K, op: &str, f: F) -> &mut V {
+        match self.try_validated_mut(key, op, |tree, key| Ok::<_, Infallible>(f(tree, key))) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree: BSTree<i32, i32> = BSTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.get(&2), Some(&'b'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        let slot = Node::insert_slot(&mut self.root, &key);
+        match slot {
+            Some(node) => node.value = value,
+            None => *slot = Some(self.pool.alloc(Node::new(key, value))),
+        }
+        self.debug_validate("insert");
+    }
+
+    /// 将一段按键严格升序、且都大于当前最大键的键值对构建为平衡的子树，挂到树的最右侧，
+    /// 不满足条件时退化为逐个插入
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.extend_sorted(vec![(2, 'b'), (3, 'c'), (4, 'd')]);
+    /// let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&3, &'c'), (&2, &'b'), (&4, &'d')]);
+    /// ```
+    pub fn extend_sorted<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let items: Vec<(K, V)> = iter.into_iter().collect();
+        let above_max = match (self.max_pair(), items.first()) {
+            (Some((max, _)), Some((first, _))) => *first > *max,
+            _ => true,
+        };
+        if !above_max || !sorted::is_strictly_sorted(&items) {
+            for (key, value) in items {
+                self.insert(key, value);
+            }
+            return;
+        }
+        trace::rebuild("extend_sorted", items.len());
+        let n = items.len();
+        let subtree = Node::build_sorted(&mut items.into_iter(), n, &mut self.pool);
+        match self.root {
+            None => self.root = subtree,
+            Some(ref mut node) => node.attach_max(subtree),
+        }
+        self.debug_validate("extend_sorted");
+    }
+
+    /// 向树中插入键值对并返回新值的可变借用，如果键已经存在，则不修改树并返回错误，错误中带回未插入的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, OccupiedError};
+    /// let mut tree = BSTree::new();
+    /// assert_eq!(tree.try_insert(1, 'a'), Ok(&mut 'a'));
+    /// assert_eq!(tree.try_insert(1, 'b'), Err(OccupiedError { key: 1, value: 'b' }));
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// ```
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, OccupiedError<K, V>> {
+        self.try_validated_mut(key, "try_insert", |tree, key| {
+            let slot = Node::insert_slot(&mut tree.root, &key);
+            if slot.is_some() {
+                return Err(OccupiedError { key, value });
+            }
+            Ok(&mut slot.get_or_insert(tree.pool.alloc(Node::new(key, value))).value)
+        })
+    }
+
+    /// 返回键对应值的可变借用，如果键不存在，则先插入由f生成的值，整个过程只查找一次
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 10);
+    /// *tree.get_or_insert_with(1, || 0) += 1;
+    /// *tree.get_or_insert_with(2, || 0) += 1;
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// assert_eq!(tree.get(&2), Some(&1));
+    /// ```
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        self.validated_mut(key, "get_or_insert_with", |tree, key| {
+            let pool = &mut tree.pool;
+            let slot = Node::insert_slot(&mut tree.root, &key);
+            &mut slot.get_or_insert_with(|| pool.alloc(Node::new(key, f()))).value
+        })
+    }
+
+    /// 返回键对应值的可变借用，如果键不存在，则先插入`V::default()`，适合计数、累加等用途
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut counts = BSTree::new();
+    /// for word in "a b a c a".split(' ') {
+    ///     *counts.get_or_insert_default(word) += 1;
+    /// }
+    /// assert_eq!(counts.get(&"a"), Some(&3));
+    /// assert_eq!(counts.get(&"c"), Some(&1));
+    /// ```
+    pub fn get_or_insert_default(&mut self, key: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.get_or_insert_with(key, V::default)
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则把value累加到旧值上，整个过程只查找一次，适合构建直方图
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut hist = BSTree::new();
+    /// for (bucket, weight) in [(1, 0.5), (2, 1.0), (1, 2.0)] {
+    ///     hist.insert_add(bucket, weight);
+    /// }
+    /// assert_eq!(hist.get(&1), Some(&2.5));
+    /// assert_eq!(hist.get(&2), Some(&1.0));
+    /// ```
+    pub fn insert_add(&mut self, key: K, value: V)
+    where
+        V: AddAssign,
+    {
+        let slot = Node::insert_slot(&mut self.root, &key);
+        match slot {
+            Some(node) => node.value += value,
+            None => *slot = Some(self.pool.alloc(Node::new(key, value))),
+        }
+        self.debug_validate("insert_add");
+    }
+
+    /// 向树中插入键值对，如果已经存在相等的键，则连同键一起替换，并返回旧的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// assert_eq!(tree.replace(1, 'a'), None);
+    /// assert_eq!(tree.replace(1, 'b'), Some((1, 'a')));
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// ```
+    pub fn replace(&mut self, key: K, value: V) -> Option<(K, V)> {
+        let res = match Node::insert_slot(&mut self.root, &key) {
+            Some(node) => {
+                let old_key = mem::replace(&mut node.key, key);
+                let old_value = mem::replace(&mut node.value, value);
+                Some((old_key, old_value))
+            }
+            slot => {
+                *slot = Some(self.pool.alloc(Node::new(key, value)));
+                None
+            }
+        };
+        self.debug_validate("replace");
+        res
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则由policy决定保存的值，返回最终保存的值的可变借用
+    ///
+    /// policy发生panic时只丢失这一个键，树中其余的键值对保持不变
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, KeepOld, Overwrite};
+    /// let mut tree = BSTree::new();
+    /// tree.insert_with_policy(1, 10, KeepOld);
+    /// tree.insert_with_policy(1, 20, KeepOld);
+    /// assert_eq!(tree.get(&1), Some(&10));
+    /// tree.insert_with_policy(1, 30, Overwrite);
+    /// assert_eq!(tree.get(&1), Some(&30));
+    /// assert_eq!(*tree.insert_with_policy(1, 5, |_: &i32, old, new| old + new), 35);
+    /// ```
+    pub fn insert_with_policy<P: DuplicatePolicy<K, V>>(&mut self, key: K, value: V, policy: P) -> &mut V {
+        self.validated_mut(key, "insert_with_policy", |tree, key| {
+            let slot = Node::insert_slot(&mut tree.root, &key);
+            if slot.is_some() {
+                Node::resolve_value(slot, &mut tree.pool, |key, old| policy.resolve(key, old, value));
+            } else {
+                *slot = Some(tree.pool.alloc(Node::new(key, value)));
+            }
+            &mut slot.as_mut().unwrap().value
+        })
+    }
+
+    /// 键不存在时插入由f生成的值并返回true；键已经存在时不调用f，保留旧值并返回false
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// assert!(tree.insert_with(1, || "expensive".to_string()));
+    /// assert!(!tree.insert_with(1, || unreachable!()));
+    /// assert_eq!(tree.get(&1).map(String::as_str), Some("expensive"));
+    /// ```
+    pub fn insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> bool {
+        let slot = Node::insert_slot(&mut self.root, &key);
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(self.pool.alloc(Node::new(key, f())));
+        self.debug_validate("insert_with");
+        true
+    }
+
+    /// 批量插入键值对，先排序去重(重复的键保留最后的值)，再沿树一次性向下分发插入，
+    /// 比逐个insert需要的遍历更少，已经存在的键会被替换为新值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(5, 'e');
+    /// tree.insert_many(vec![(3, 'c'), (8, 'h'), (5, 'E'), (1, 'a')]);
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&3, &'c'), (&5, &'E'), (&8, &'h')]);
+    /// ```
+    pub fn insert_many<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let items = sorted::sort_dedup(iter.into_iter().collect());
+        trace::rebuild("insert_many", items.len());
+        Node::insert_sorted(&mut self.root, items, &mut self.pool);
+        self.debug_validate("insert_many");
+    }
+
+    /// 随机化插入键值对，如果键已经存在，则替换旧值为新值
+    ///
+    /// 在大小为s的子树处以1/(s+1)的概率把新键插为该子树的根，否则继续向下。
+    /// 只要全部键都用这种方式插入，无论插入顺序如何，树的期望高度都是O(log n)，不需要旋转等平衡操作
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// for i in 0..1000 {
+    ///     tree.insert_randomized(i, i);
+    /// }
+    /// assert!(tree.levels().len() < 100);
+    /// assert_eq!(tree.get(&500), Some(&500));
+    /// ```
+    pub fn insert_randomized(&mut self, key: K, value: V) {
+        if let Some(old) = Node::insert_slot(&mut self.root, &key) {
+            old.value = value;
+            return;
+        }
+        let node = self.pool.alloc(Node::new(key, value));
+        Node::insert_randomized(&mut self.root, node);
+        self.debug_validate("insert_randomized");
+    }
+
+    /// 从树中删除键值对，如果找不到键值对，则忽略
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.delete(1);
+    /// assert!(tree.is_empty());
+    /// tree.delete(2);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn delete(&mut self, key: K) {
+        self.take(&key);
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.take(&1), Some((1, 'a')));
+    /// assert_eq!(tree.take(&1), None);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        let slot = Node::find_slot(&mut self.root, key);
+        let (new_root, node) = Node::unlink(slot.take()?);
+        *slot = new_root;
+        let node = self.pool.release(node);
+        self.debug_validate("take");
+        Some((node.key, node.value))
+    }
+
+    /// 预先在节点池中分配n个节点，使之后至少n次插入新键时不需要再分配内存；
+    /// 所有插入新键的方法(包括`try_insert`、`get_or_insert_with`、`insert_many`、`insert_randomized`等)都先从节点池取节点，
+    /// 所有删除键值对的方法(包括`take`、`remove_range`、`retain_range`、`remove_many`、`pop_while`、`prune`、`delete_tree`等)
+    /// 都把节点放回节点池，供之后的插入复用。只有`remove_tree`、`try_remove_tree`把切下的节点交给返回的新树，不放回节点池
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.reserve(3);
+    /// assert_eq!(tree.pooled_nodes(), 3);
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.pooled_nodes(), 1);
+    /// tree.delete(1);
+    /// assert_eq!(tree.pooled_nodes(), 2);
+    /// ```
+    pub fn reserve(&mut self, n: usize) {
+        self.pool.reserve(n);
+    }
+
+    /// 释放节点池中全部空闲的节点，归还给分配器
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree: BSTree<i32, char> = BSTree::new();
+    /// tree.reserve(10);
+    /// tree.shrink_to_fit();
+    /// assert_eq!(tree.pooled_nodes(), 0);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.pool.shrink_to_fit();
+    }
+
+    /// 返回节点池中可供插入复用的空闲节点个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.pooled_nodes(), 0);
+    /// tree.take(&1);
+    /// assert_eq!(tree.pooled_nodes(), 1);
+    /// ```
+    pub fn pooled_nodes(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// 删除键在range范围内的全部键值对，并按键的升序返回被删除的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.remove_range(3..6), vec![(3, 30), (4, 40), (5, 50)]);
+    /// assert_eq!(tree.remove_range(..=1).len(), 2);
+    /// let res: Vec<&i32> = tree.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&2, &6, &7, &8, &9]);
+    /// ```
+    pub fn remove_range<R: RangeBounds<K>>(&mut self, range: R) -> Vec<(K, V)> {
+        let mut removed = Vec::new();
+        self.root = Node::remove_range(self.root.take(), &range, &mut removed, &mut self.pool);
+        self.debug_validate("remove_range");
+        removed
+    }
+
+    /// 从最小的键值对开始，按键的升序依次删除满足f的键值对，遇到第一个不满足f的就停止，返回删除的键值对；
+    /// 整个过程只沿树走一遍，不会对每个键值对单独从根开始删除，适合"取出所有到期的定时器"这类用法
+    ///
+    /// f发生panic时已经删除的键值对随之丢失，其余的键值对仍留在树中
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut timers = BSTree::new();
+    /// for (deadline, name) in [(30, "c"), (10, "a"), (50, "e"), (20, "b"), (40, "d")] {
+    ///     timers.insert(deadline, name);
+    /// }
+    /// let now = 30;
+    /// let due = timers.pop_while(|deadline, _| *deadline <= now);
+    /// assert_eq!(due, vec![(10, "a"), (20, "b"), (30, "c")]);
+    /// let res: Vec<(&i32, &&str)> = timers.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&40, &"d"), (&50, &"e")]);
+    /// ```
+    pub fn pop_while<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) -> Vec<(K, V)> {
+        let mut popped = Vec::new();
+        Node::pop_while(&mut self.root, &mut f, &mut popped);
+        self.debug_validate("pop_while");
+        popped
+            .into_iter()
+            .map(|node| {
+                let node = self.pool.release(node);
+                (node.key, node.value)
+            })
+            .collect()
+    }
+
+    /// 只保留键在range范围内的键值对，范围外的全部丢弃，是remove_range的补集操作，
+    /// 只需沿范围的两条边界各向下走一遍，适合按时间窗口截断时间序列
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// for i in [5, 2, 8, 1, 3, 7, 9] {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// tree.retain_range(3..8);
+    /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&3, &30), (&5, &50), (&7, &70)]);
+    /// ```
+    pub fn retain_range<R: RangeBounds<K>>(&mut self, range: R) {
+        Node::retain_range(&mut self.root, &range, &mut self.pool);
+        self.debug_validate("retain_range");
+    }
+
+    /// 把键old改为new，值保持不变，节点从原位置摘下后直接挂到新位置，不会重新分配；
+    /// old不存在或new已经存在时返回错误，树保持不变。new与old相等时只替换键
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, RekeyError};
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.rekey(&1, 3), Ok(()));
+    /// assert_eq!(tree.get(&3), Some(&'a'));
+    /// assert!(!tree.contains(&1));
+    /// assert_eq!(tree.rekey(&3, 2), Err(RekeyError::Occupied(2)));
+    /// assert_eq!(tree.rekey(&1, 5), Err(RekeyError::NotFound(5)));
+    /// ```
+    pub fn rekey(&mut self, old: &K, new: K) -> Result<(), RekeyError<K>> {
+        if *old == new {
+            return match Node::find_slot(&mut self.root, old) {
+                Some(node) => {
+                    node.key = new;
+                    Ok(())
+                }
+                None => Err(RekeyError::NotFound(new)),
+            };
+        }
+        if Node::find_slot(&mut self.root, &new).is_some() {
+            return Err(RekeyError::Occupied(new));
+        }
+        let slot = Node::find_slot(&mut self.root, old);
+        let node = match slot.take() {
+            Some(node) => node,
+            None => return Err(RekeyError::NotFound(new)),
+        };
+        let (new_root, mut node) = Node::unlink(node);
+        *slot = new_root;
+        node.key = new;
+        let slot = Node::find_slot(&mut self.root, &node.key);
+        *slot = Some(node);
+        self.debug_validate("rekey");
+        Ok(())
+    }
+
+    /// 交换键a和键b对应的值，任意一个键不存在时返回错误，树保持不变
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, KeyNotFound};
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.swap_values(&1, &2), Ok(()));
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// assert_eq!(tree.get(&2), Some(&'a'));
+    /// assert_eq!(tree.swap_values(&1, &3), Err(KeyNotFound(3)));
+    /// ```
+    pub fn swap_values(&mut self, a: &K, b: &K) -> Result<(), KeyNotFound<K>> {
+        for key in [a, b] {
+            if !self.contains(key) {
+                return Err(KeyNotFound(key.clone()));
+            }
+        }
+        let keys = if *a < *b { [a, b] } else { [b, a] };
+        let mut values = Vec::with_capacity(2);
+        Node::values_mut(&mut self.root, &keys, &mut values);
+        if let [first, second] = &mut values[..] {
+            mem::swap(&mut **first, &mut **second);
+        }
+        self.debug_validate("swap_values");
+        Ok(())
+    }
+
+    /// 批量删除键值对，先对键排序去重，再沿树一次性向下分发删除，返回实际删除的键值对个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::bulk_load((1..=5).map(|i| (i, i)));
+    /// assert_eq!(tree.remove_many(vec![4, 2, 9, 2]), 2);
+    /// let res: Vec<&i32> = tree.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&1, &3, &5]);
+    /// ```
+    pub fn remove_many<I: IntoIterator<Item = K>>(&mut self, keys: I) -> usize {
+        let keys = sorted::sort_dedup_keys(keys.into_iter().collect());
+        let res = Node::remove_sorted(&mut self.root, &keys, &mut self.pool);
+        self.debug_validate("remove_many");
+        res
+    }
+
+    /// 根据键获取相应键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get_pair(&1), Some((&1, &'a')));
+    /// ```
+    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
+        self.root.as_ref().and_then(|node| node.search_pair(key))
+    }
+
+    /// 根据键查找对应的值，找不到返回None，返回值的不可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.root.as_ref().and_then(|node| node.search(key))
+    }
+
+    /// 根据键获取对应值的可变引用，找不到返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 10);
+    /// if let Some(v) = tree.get_mut(&1) {
+    ///     *v += 1;
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// assert_eq!(tree.get_mut(&2), None);
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        Node::find_slot(&mut self.root, key)
+            .as_mut()
+            .map(|node| &mut node.value)
+    }
+
+    /// 同时获取多个不同键对应值的可变借用，结果与keys的顺序一致，键重复或不存在时返回错误
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, GetManyMutError};
+    /// let mut tree = BSTree::bulk_load(vec![(1, 10), (2, 20), (3, 30)]);
+    /// if let Ok([a, b]) = tree.get_many_mut([&3, &1]) {
+    ///     *a -= 5;
+    ///     *b += 5;
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&15));
+    /// assert_eq!(tree.get(&3), Some(&25));
+    /// assert_eq!(tree.get_many_mut([&1, &1]).err(), Some(GetManyMutError::Duplicate(1)));
+    /// assert_eq!(tree.get_many_mut([&1, &4]).err(), Some(GetManyMutError::NotFound(4)));
+    /// ```
+    pub fn get_many_mut<const N: usize>(&mut self, keys: [&K; N]) -> Result<[&mut V; N], GetManyMutError<K>> {
+        for key in keys {
+            if !self.contains(key) {
+                return Err(GetManyMutError::NotFound(key.clone()));
+            }
+        }
+        // 按键排序后沿树一次取出全部可变借用，再按原来的顺序放回
+        let mut order: Vec<usize> = (0..N).collect();
+        order.sort_by(|&a, &b| keys[a].partial_cmp(keys[b]).unwrap_or(Ordering::Equal));
+        if let Some(w) = order.windows(2).find(|w| keys[w[0]] == keys[w[1]]) {
+            return Err(GetManyMutError::Duplicate(keys[w[0]].clone()));
+        }
+        let sorted: Vec<&K> = order.iter().map(|&i| keys[i]).collect();
+        let mut values = Vec::with_capacity(N);
+        Node::values_mut(&mut self.root, &sorted, &mut values);
+        let mut slots: Vec<Option<&mut V>> = (0..N).map(|_| None).collect();
+        for (i, value) in order.into_iter().zip(values) {
+            slots[i] = Some(value);
+        }
+        Ok(std::array::from_fn(|i| slots[i].take().unwrap()))
+    }
+
+    /// 据键查找对应的值，找不到返回默认值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get_or(&1, &'z'), &'a');
+    /// assert_eq!(tree.get_or(&2, &'z'), &'z');
+    /// ```
+    pub fn get_or<'a>(&'a self, key: &K, default: &'a V) -> &'a V {
+        self.get(key).map_or(default, |data| data)
+    }
+
+    /// 据键查找对应的值，找不到时才调用f得到默认值的引用
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, String::from("a"));
+    /// let fallback = String::from("z");
+    /// assert_eq!(tree.get_or_else(&1, || unreachable!()), "a");
+    /// assert_eq!(tree.get_or_else(&2, || &fallback), "z");
+    /// ```
+    pub fn get_or_else<'a, F: FnOnce() -> &'a V>(&'a self, key: &K, f: F) -> &'a V {
+        self.get(key).unwrap_or_else(f)
+    }
+
+    /// 据键查找对应的值并返回它的副本，找不到时返回f计算出的默认值，键存在时不会调用f
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert("apple", vec![1, 2]);
+    /// assert_eq!(tree.get_cloned_or_else(&"apple", Vec::new), vec![1, 2]);
+    /// assert_eq!(tree.get_cloned_or_else(&"pear", Vec::new), Vec::<i32>::new());
+    /// ```
+    pub fn get_cloned_or_else<F: FnOnce() -> V>(&self, key: &K, f: F) -> V
+    where
+        V: Clone,
+    {
+        self.get(key).cloned().unwrap_or_else(f)
+    }
+
+    /// 查找是否存在键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.contains(&1), true);
+    /// assert_eq!(tree.contains(&2), false);
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 返回树中的最大键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.max_pair(), Some((&3, &'c')));
+    /// ```
+    pub fn max_pair(&self) -> Option<(&K, &V)> {
+        self.root.as_ref().map(|node| node.max_pair())
+    }
+
+    /// 返回树中的最小键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.min_pair(), Some((&1, &'a')));
+    /// ```
+    pub fn min_pair(&self) -> Option<(&K, &V)> {
+        self.root.as_ref().map(|node| node.min_pair())
+    }
+
+    /// 返回树中的最小键值对，值为可变借用，可以原地修改最小键对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// if let Some((_, v)) = tree.min_pair_mut() {
+    ///     *v = 'A';
+    /// }
+    /// assert_eq!(tree.min_pair(), Some((&1, &'A')));
+    /// ```
+    pub fn min_pair_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.root.as_mut().map(|node| node.min_pair_mut())
+    }
+
+    /// 返回树中的最大键值对，值为可变借用，可以原地修改最大键对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 10);
+    /// tree.insert(1, 20);
+    /// if let Some((_, v)) = tree.max_pair_mut() {
+    ///     *v += 5;
+    /// }
+    /// assert_eq!(tree.max_pair(), Some((&3, &15)));
+    /// assert_eq!(BSTree::<i32, i32>::new().max_pair_mut(), None);
+    /// ```
+    pub fn max_pair_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.root.as_mut().map(|node| node.max_pair_mut())
+    }
+
+
+    /// 返回树中的最小键值对，与`min_pair`相同
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.first(), Some((&1, &'a')));
+    /// ```
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.min_pair()
+    }
+
+    /// 返回树中的最大键值对，与`max_pair`相同
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.last(), Some((&2, &'b')));
+    /// ```
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.max_pair()
+    }
+
+    /// 返回最小键值对的入口，可以原地修改或删除最小键值对，树为空时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// if let Some(mut entry) = tree.first_entry() {
+    ///     *entry.get_mut() = 'z';
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&'z'));
+    /// assert_eq!(tree.first_entry().map(|entry| entry.remove_entry()), Some((1, 'z')));
+    /// assert_eq!(tree.first(), Some((&2, &'b')));
+    /// ```
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V, A>> {
+        OccupiedEntry::new(Node::min_slot(&mut self.root), &mut self.pool)
+    }
+
+    /// 返回最大键值对的入口，可以原地修改或删除最大键值对，树为空时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// if let Some(mut entry) = tree.last_entry() {
+    ///     assert_eq!(entry.insert('y'), 'b');
+    /// }
+    /// assert_eq!(tree.get(&2), Some(&'y'));
+    /// assert_eq!(tree.last_entry().map(|entry| entry.remove()), Some('y'));
+    /// assert_eq!(tree.last(), Some((&1, &'a')));
+    /// ```
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V, A>> {
+        OccupiedEntry::new(Node::max_slot(&mut self.root), &mut self.pool)
+    }
+
+    ///返回第一个大于key的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.successor(&1), Some((&2, &'b')));
+    /// assert_eq!(tree.successor(&0), Some((&1, &'a')));
+    /// assert_eq!(tree.successor(&3), None);
+    /// ```
+    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.root.as_ref().and_then(|node| node.successor(key))
+    }
+
+    ///返回第一个小于key的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.predecessor(&3), Some((&2, &'b')));
+    /// assert_eq!(tree.predecessor(&5), Some((&3, &'c')));
+    /// assert_eq!(tree.predecessor(&1), None);
+    /// ```
+    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.root.as_ref().and_then(|node| node.predecessor(key))
+    }
+
+    ///返回第一个大于key的键值对，值可以原地修改
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(5, 50);
+    /// if let Some((_, v)) = tree.successor_mut(&2) {
+    ///     *v += 1;
+    /// }
+    /// assert_eq!(tree.get(&5), Some(&51));
+    /// assert_eq!(tree.successor_mut(&5), None);
+    /// ```
+    pub fn successor_mut(&mut self, key: &K) -> Option<(&K, &mut V)> {
+        self.root.as_mut().and_then(|node| node.successor_mut(key))
+    }
+
+    ///返回第一个小于key的键值对，值可以原地修改
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(5, 50);
+    /// if let Some((_, v)) = tree.predecessor_mut(&5) {
+    ///     *v += 1;
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// assert_eq!(tree.predecessor_mut(&1), None);
+    /// ```
+    pub fn predecessor_mut(&mut self, key: &K) -> Option<(&K, &mut V)> {
+        self.root.as_mut().and_then(|node| node.predecessor_mut(key))
+    }
+
+    /// 一次查找同时返回不大于key的最大键值对和不小于key的最小键值对，即(floor, ceiling)，
+    /// key在树中时两者都是key本身，比分别调用`predecessor`和`successor`少走一遍
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(10, 'a'), (20, 'b'), (30, 'c')]);
+    /// assert_eq!(tree.neighbors(&15), (Some((&10, &'a')), Some((&20, &'b'))));
+    /// assert_eq!(tree.neighbors(&20), (Some((&20, &'b')), Some((&20, &'b'))));
+    /// assert_eq!(tree.neighbors(&5), (None, Some((&10, &'a'))));
+    /// assert_eq!(tree.neighbors(&35), (Some((&30, &'c')), None));
+    /// ```
+    pub fn neighbors(&self, key: &K) -> (Neighbor<'_, K, V>, Neighbor<'_, K, V>) {
+        match self.root {
+            None => (None, None),
+            Some(ref node) => node.neighbors(key, None, None),
+        }
+    }
+
+    /// 返回位于下界处的游标：`Included(k)`时游标之后是第一个不小于k的键值对，`Excluded(k)`时是第一个大于k的键值对，`Unbounded`时位于最前
+    /// # Example
+    /// ```
+    /// use std::ops::Bound;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+    /// let mut cursor = tree.lower_bound(Bound::Included(&2));
+    /// assert_eq!(cursor.peek_prev(), Some((&1, &'a')));
+    /// let res: Vec<(&i32, &char)> = cursor.collect();
+    /// assert_eq!(res, vec![(&2, &'b'), (&3, &'c'), (&4, &'d')]);
+    /// let cursor = tree.lower_bound(Bound::Excluded(&2));
+    /// assert_eq!(cursor.peek_next(), Some((&3, &'c')));
+    /// ```
+    pub fn lower_bound(&self, bound: Bound<&K>) -> Cursor<'_, K, V> {
+        Cursor::lower_bound(self, bound)
+    }
+
+    /// 返回位于上界处的游标：`Included(k)`时游标之前是最后一个不大于k的键值对，`Excluded(k)`时是最后一个小于k的键值对，`Unbounded`时位于最后
+    /// # Example
+    /// ```
+    /// use std::ops::Bound;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+    /// let mut cursor = tree.upper_bound(Bound::Included(&3));
+    /// assert_eq!(cursor.peek_next(), Some((&4, &'d')));
+    /// assert_eq!(cursor.prev(), Some((&3, &'c')));
+    /// assert_eq!(cursor.prev(), Some((&2, &'b')));
+    /// let cursor = tree.upper_bound(Bound::Unbounded);
+    /// assert_eq!(cursor.peek_prev(), Some((&4, &'d')));
+    /// ```
+    pub fn upper_bound(&self, bound: Bound<&K>) -> Cursor<'_, K, V> {
+        Cursor::upper_bound(self, bound)
+    }
+
+    /// 返回键key所在节点的父节点，key是根节点或不在树中时返回None，查找时顺带记录父节点，只需向下走一遍
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.parent_of(&1), Some((&2, &20)));
+    /// assert_eq!(tree.parent_of(&6), Some((&4, &40)));
+    /// assert_eq!(tree.parent_of(&4), None);
+    /// assert_eq!(tree.parent_of(&8), None);
+    /// ```
+    pub fn parent_of(&self, key: &K) -> Option<(&K, &V)> {
+        let parent = self.root.as_ref()?.parent_node(key)?;
+        Some((&parent.key, &parent.value))
+    }
+
+    /// 返回键key所在节点的兄弟节点，即父节点的另一个孩子，key是根节点、不在树中或没有兄弟时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=6).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.sibling_of(&2), Some((&6, &60)));
+    /// assert_eq!(tree.sibling_of(&1), Some((&3, &30)));
+    /// assert_eq!(tree.sibling_of(&5), None);
+    /// assert_eq!(tree.sibling_of(&4), None);
+    /// ```
+    pub fn sibling_of(&self, key: &K) -> Option<(&K, &V)> {
+        let parent = self.root.as_ref()?.parent_node(key)?;
+        let sibling = if *key < parent.key { parent.right() } else { parent.left() }?;
+        Some((&sibling.key, &sibling.value))
+    }
+
+    /// 返回键key所在节点的叔节点，即父节点的兄弟节点，不存在时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.uncle_of(&1), Some((&6, &60)));
+    /// assert_eq!(tree.uncle_of(&7), Some((&2, &20)));
+    /// assert_eq!(tree.uncle_of(&2), None);
+    /// ```
+    pub fn uncle_of(&self, key: &K) -> Option<(&K, &V)> {
+        let parent = self.root.as_ref()?.parent_node(key)?;
+        self.sibling_of(&parent.key)
+    }
+
+    /// 返回键a和键b的最近公共祖先，任意一个键不存在时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.lca(&1, &3), Some((&2, &20)));
+    /// assert_eq!(tree.lca(&3, &7), Some((&4, &40)));
+    /// assert_eq!(tree.lca(&6, &7), Some((&6, &60)));
+    /// assert_eq!(tree.lca(&6, &8), None);
+    /// ```
+    pub fn lca(&self, a: &K, b: &K) -> Option<(&K, &V)> {
+        if !self.contains(a) || !self.contains(b) {
+            return None;
+        }
+        let node = self.root.as_ref()?.lca(a, b);
+        Some((&node.key, &node.value))
+    }
+
+    /// 返回键a和键b之间路径上的边数，即两者到最近公共祖先的距离之和，任意一个键不存在时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.distance(&1, &7), Some(4));
+    /// assert_eq!(tree.distance(&2, &3), Some(1));
+    /// assert_eq!(tree.distance(&5, &5), Some(0));
+    /// assert_eq!(tree.distance(&1, &8), None);
+    /// ```
+    pub fn distance(&self, a: &K, b: &K) -> Option<usize> {
+        if !self.contains(a) || !self.contains(b) {
+            return None;
+        }
+        let node = self.root.as_ref()?.lca(a, b);
+        Some(node.depth_of(a) + node.depth_of(b))
+    }
+
+    /// 返回与key距离最近的键值对，距离由f计算，距离相等时返回较小的键，树为空时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(10, 'a');
+    /// tree.insert(20, 'b');
+    /// let dist = |a: &i32, b: &i32| (a - b).abs();
+    /// assert_eq!(tree.nearest_by(&14, dist), Some((&10, &'a')));
+    /// assert_eq!(tree.nearest_by(&16, dist), Some((&20, &'b')));
+    /// assert_eq!(tree.nearest_by(&15, dist), Some((&10, &'a')));
+    /// ```
+    pub fn nearest_by<D: PartialOrd, F: Fn(&K, &K) -> D>(&self, key: &K, f: F) -> Option<(&K, &V)> {
+        if let Some(pair) = self.get_pair(key) {
+            return Some(pair);
+        }
+        match (self.predecessor(key), self.successor(key)) {
+            (Some(floor), Some(ceiling)) => {
+                if f(ceiling.0, key) < f(floor.0, key) {
+                    Some(ceiling)
+                } else {
+                    Some(floor)
+                }
+            }
+            (floor, ceiling) => floor.or(ceiling),
+        }
+    }
+
+    /// 返回与key距离最近的键值对，距离由键的`Distance`实现计算，距离相等时返回较小的键
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(100, "start");
+    /// tree.insert(160, "end");
+    /// assert_eq!(tree.nearest(&120), Some((&100, &"start")));
+    /// assert_eq!(tree.nearest(&500), Some((&160, &"end")));
+    /// ```
+    pub fn nearest(&self, key: &K) -> Option<(&K, &V)>
+    where
+        K: Distance,
+    {
+        self.nearest_by(key, |a, b| a.distance(b))
+    }
+
+    /// 按与key的距离从近到远返回最近的n个键值对，距离由f计算，距离相等时较小的键在前
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// for i in [1i32, 4, 6, 9, 15] {
+    ///     tree.insert(i, ());
+    /// }
+    /// let res: Vec<&i32> = tree.nearest_k_by(&5, 3, |a, b| (a - b).abs()).map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &6, &1]);
+    /// ```
+    pub fn nearest_k_by<D: PartialOrd, F: Fn(&K, &K) -> D>(
+        &self,
+        key: &K,
+        n: usize,
+        f: F,
+    ) -> TraverseIter<'_, K, V> {
+        // 从key所在的位置分别向后、向前按中序各取最多n个键值对
+        let mut forward = Vec::new();
+        let mut backward = Vec::new();
+        if let Some(ref node) = self.root {
+            node.collect_ge(key, n, &mut forward);
+            node.collect_lt(key, n, &mut backward);
+        }
+        // 两个方向都按距离由近到远排列，归并即可
+        let mut queue = VecDeque::new();
+        let (mut forward, mut backward) = (forward.into_iter().peekable(), backward.into_iter().peekable());
+        while queue.len() < n {
+            let pick_forward = match (forward.peek(), backward.peek()) {
+                (Some(ceiling), Some(floor)) => f(ceiling.0, key) < f(floor.0, key),
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let next = if pick_forward { forward.next() } else { backward.next() };
+            queue.extend(next);
+        }
+        TraverseIter::new(queue)
+    }
+
+    /// 按与key的距离从近到远返回最近的n个键值对，距离由键的`Distance`实现计算
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// for (i, c) in [(10, 'a'), (20, 'b'), (30, 'c')] {
+    ///     tree.insert(i, c);
+    /// }
+    /// let res: Vec<(&i32, &char)> = tree.nearest_k(&22, 2).collect();
+    /// assert_eq!(res, vec![(&20, &'b'), (&30, &'c')]);
+    /// ```
+    pub fn nearest_k(&self, key: &K, n: usize) -> TraverseIter<'_, K, V>
+    where
+        K: Distance,
+    {
+        self.nearest_k_by(key, n, |a, b| a.distance(b))
+    }
+
+    /// 按键的升序用f转换树中的每个值，得到一棵结构完全相同的新树，不需要重新排序或插入
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, "bb");
+    /// tree.insert(1, "a");
+    /// tree.insert(3, "ccc");
+    /// let lens = tree.map_values(|_, v| v.len());
+    /// let res: Vec<(&i32, &usize)> = lens.preorder_iter().collect();
+    /// assert_eq!(res, vec![(&2, &2), (&1, &1), (&3, &3)]);
+    /// ```
+    pub fn map_values<U, F: FnMut(&K, V) -> U>(self, mut f: F) -> BSTree<K, U, A> {
+        let mut pool = NodePool::new_in(self.pool.allocator().clone());
+        let root = Node::map_values(self.root, &mut f, &mut pool);
+        BSTree { root, pool }
+    }
+
+    /// 按键的升序用f转换树中的每个键，得到一棵结构完全相同的新树，不需要重新排序或插入
+    /// f必须严格单调递增（例如对时间戳进行缩放），debug构建中会检查这一点，违反时panic
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(20, 'b');
+    /// tree.insert(10, 'a');
+    /// tree.insert(30, 'c');
+    /// let scaled = tree.map_keys(|k| k * 1000);
+    /// let res: Vec<(&i32, &char)> = scaled.preorder_iter().collect();
+    /// assert_eq!(res, vec![(&20000, &'b'), (&10000, &'a'), (&30000, &'c')]);
+    /// ```
+    pub fn map_keys<J: PartialOrd + Clone, F: FnMut(K) -> J>(self, mut f: F) -> BSTree<J, V, A> {
+        let mut pool = NodePool::new_in(self.pool.allocator().clone());
+        let root = Node::map_keys(self.root, &mut f, &mut None, &mut pool);
+        BSTree { root, pool }
+    }
+
+    /// 将other中的键值对合并到当前树中，两棵树都存在的键由f(键, 当前树的值, other的值)决定合并后的值
+    ///
+    /// f发生panic时只丢失正在合并的键，已经合并的键值对和当前树中其余的键值对保持不变
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut counts = BSTree::new();
+    /// counts.insert("a", 1);
+    /// counts.insert("b", 2);
+    /// let mut other = BSTree::new();
+    /// other.insert("b", 10);
+    /// other.insert("c", 20);
+    /// counts.merge_with(other, |_, x, y| x + y);
+    /// let res: Vec<(&&str, &i32)> = counts.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&"a", &1), (&"b", &12), (&"c", &20)]);
+    /// ```
+    pub fn merge_with<F: FnMut(&K, V, V) -> V>(&mut self, other: Self, mut f: F) {
+        let mut entries = Vec::new();
+        Node::drain_in_order(other.root, &mut entries);
+        for (key, value) in entries {
+            self.insert_with_policy(key, value, &mut f);
+        }
+        self.debug_validate("merge_with");
+    }
+
+    ///返回以key为根节点的树枝中键值对的个数，key不在树中时返回0，需要遍历整个树枝
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// assert_eq!(tree.subtree_size(&3), 4);
+    /// assert_eq!(tree.subtree_size(&2), 2);
+    /// assert_eq!(tree.subtree_size(&4), 1);
+    /// assert_eq!(tree.subtree_size(&5), 0);
+    /// ```
+    pub fn subtree_size(&self, key: &K) -> usize {
+        self.root.as_ref().and_then(|node| node.search_node(key)).map_or(0, |node| node.subtree_size())
+    }
+
+    ///用一次伸展操作把键key旋转到根节点，之后对它的查找只需一次比较；
+    ///路径上的其他节点也大致上移，键的顺序不变。返回是否找到key，找不到时树保持不变
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::bulk_load((1..=7).map(|k| (k, k * 10)));
+    /// assert!(tree.splay_to_root(&7));
+    /// assert_eq!(tree.preorder_iter().next(), Some((&7, &70)));
+    /// let res: Vec<&i32> = tree.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&1, &2, &3, &4, &5, &6, &7]);
+    /// assert!(!tree.splay_to_root(&8));
+    /// assert_eq!(tree.preorder_iter().next(), Some((&7, &70)));
+    /// ```
+    pub fn splay_to_root(&mut self, key: &K) -> bool {
+        let found = Node::splay(&mut self.root, key);
+        self.debug_validate("splay_to_root");
+        found
+    }
+
+    ///删除以val为根节点的树枝，返回删除的键值对个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// assert_eq!(tree.delete_tree(2), 2);
+    /// assert!(!tree.contains(&2));
+    /// assert!(!tree.contains(&1));
+    /// assert_eq!(tree.delete_tree(5), 0);
+    /// assert_eq!(tree.delete_tree(3), 2);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn delete_tree(&mut self, key: K) -> usize {
+        let mut removed = 0;
+        let subtree = self.cut_subtree(key).root;
+        Node::release_in_order(subtree, &mut self.pool, &mut |_, _| removed += 1);
+        self.debug_validate("delete_tree");
+        removed
+    }
+
+    ///删除以val为根节点的树枝, 并返回切掉的树枝
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// let rm_tree = tree.remove_tree(2);
+    /// assert!(rm_tree.contains(&2));
+    /// assert!(rm_tree.contains(&1));
+    /// let rm_tree = tree.remove_tree(12);
+    /// assert!(rm_tree.is_empty());
+    /// ```
+    pub fn remove_tree(&mut self, key: K) -> Self {
+        let removed = self.cut_subtree(key);
+        self.debug_validate("remove_tree");
+        removed
+    }
+
+    // 切下以key为根的子树，key不在树中时返回空树
+    fn cut_subtree(&mut self, key: K) -> Self {
+        let ret_node = match self.root {
+            None => None,
+            Some(ref mut node) if node.key == key => self.root.take(),
+            Some(ref mut node) => node.remove_tree(key),
+        };
+        Self::from_root_in(ret_node, self.allocator().clone())
+    }
+
+    ///删除以val为根节点的树枝, 并返回切掉的树枝；val不在树中时返回None，
+    ///以便与`remove_tree`返回的空树区分
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// assert!(tree.try_remove_tree(12).is_none());
+    /// let rm_tree = tree.try_remove_tree(2).unwrap();
+    /// assert!(rm_tree.contains(&2));
+    /// assert!(rm_tree.contains(&1));
+    /// assert!(tree.try_remove_tree(2).is_none());
+    /// ```
+    pub fn try_remove_tree(&mut self, key: K) -> Option<Self> {
+        let removed = self.remove_tree(key);
+        if removed.is_empty() {
+            None
+        } else {
+            Some(removed)
+        }
+    }
+
+    ///把subtree整体挂到树中，是`remove_tree`的逆操作，不会重新分配节点；
+    ///subtree的键范围内不能有树中已有的键，也不能跨越树中的任何键，否则返回错误并带回subtree，树保持不变
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// let rm_tree = tree.remove_tree(2);
+    /// assert!(!tree.contains(&1));
+    /// tree.graft(rm_tree).unwrap();
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), Some(&'b'));
+    /// let mut other = BSTree::new();
+    /// other.insert(0, 'z');
+    /// other.insert(5, 'e');
+    /// let err = tree.graft(other).unwrap_err();
+    /// assert!(err.0.contains(&5));
+    /// assert!(!tree.contains(&0));
+    /// ```
+    pub fn graft(&mut self, mut subtree: Self) -> Result<(), GraftError<Self>> {
+        let fits = match (subtree.min_pair(), subtree.max_pair()) {
+            (Some((min, _)), Some((max, _))) => Node::can_graft(&self.root, min, max),
+            _ => return Ok(()),
+        };
+        if !fits {
+            return Err(GraftError(subtree));
+        }
+        if let Some(node) = subtree.root.take() {
+            let slot = Node::find_slot(&mut self.root, &node.key);
+            *slot = Some(node);
+        }
+        self.debug_validate("graft");
+        Ok(())
+    }
+
+    ///从根节点开始向下检查，删除键值对满足f的节点为根的整个树枝，返回删除的键值对个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// assert_eq!(tree.prune(|k, _| *k == 2 || *k == 1), 2);
+    /// assert!(!tree.contains(&1));
+    /// assert_eq!(tree.prune(|_, v| *v == 'c'), 2);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn prune<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) -> usize {
+        let res = Node::prune(&mut self.root, &mut f, &mut self.pool);
+        self.debug_validate("prune");
+        res
+    }
+
+    ///前序遍历
+    fn prev_order(&self) -> Vec<K> {
+        let mut buf = Vec::new();
+        Node::prev_order(&self.root, &mut buf);
+        buf
+    }
+
+    ///中序遍历
+    fn in_order(&self) -> Vec<K> {
+        let mut buf = Vec::new();
+        Node::in_order(&self.root, &mut buf);
+        buf
+    }
+
+    ///后序遍历
+    fn post_order(&self) -> Vec<K> {
+        let mut buf = Vec::new();
+        Node::post_order(&self.root, &mut buf);
+        buf
+    }
+
+    ///层序遍历
+    fn level_order(&self) -> Vec<K> {
+        let mut buf = Vec::new();
+        Node::level_order(&self.root, &mut buf);
+        buf
+    }
+
+    /// 按层返回树中的键值对，第i个元素为第i层从左到右的全部键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.levels(), vec![vec![(&2, &'b')], vec![(&1, &'a'), (&3, &'c')]]);
+    /// ```
+    pub fn levels(&self) -> Vec<Vec<(&K, &V)>> {
+        let mut buf = Vec::new();
+        Node::levels(&self.root, 0, &mut buf);
+        buf
+    }
+
+    /// 按列返回树中的键值对，根节点在第0列，左孩子在父节点左边一列，右孩子在右边一列；
+    /// 各列从左到右排列，同一列中按从上到下、从左到右排列
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// let res: Vec<Vec<&i32>> = tree
+    ///     .vertical_order()
+    ///     .into_iter()
+    ///     .map(|col| col.into_iter().map(|(k, _)| k).collect())
+    ///     .collect();
+    /// assert_eq!(res, vec![vec![&1], vec![&2], vec![&4, &3, &5], vec![&6], vec![&7]]);
+    /// ```
+    pub fn vertical_order(&self) -> Vec<Vec<(&K, &V)>> {
+        let mut columns: Vec<Vec<(&K, &V)>> = Vec::new();
+        let mut last = None;
+        for (col, key, value) in Node::columns(&self.root) {
+            if last != Some(col) {
+                columns.push(Vec::new());
+                last = Some(col);
+            }
+            if let Some(column) = columns.last_mut() {
+                column.push((key, value));
+            }
+        }
+        columns
+    }
+
+    /// 返回树中任意两个节点之间最长路径的边数，只需一次后序遍历，空树和只有一个节点的树返回0
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.diameter(), 4);
+    /// ```
+    pub fn diameter(&self) -> usize {
+        let mut best = 0;
+        Node::diameter(&self.root, &mut best);
+        best
+    }
+
+    /// 判断两棵树的形状和每个位置上的键值对是否完全相同；键值对相同而形状不同的两棵树返回false
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let balanced = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let mut chain = BSTree::new();
+    /// chain.insert(1, 'a');
+    /// chain.insert(2, 'b');
+    /// chain.insert(3, 'c');
+    /// assert!(!balanced.same_structure(&chain));
+    /// let mut same = BSTree::new();
+    /// same.insert(2, 'b');
+    /// same.insert(1, 'a');
+    /// same.insert(3, 'c');
+    /// assert!(balanced.same_structure(&same));
+    /// ```
+    pub fn same_structure(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        Node::same_structure(&self.root, &other.root)
+    }
+
+    /// 判断树的形状是否关于根节点左右对称，只比较形状，不比较键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::bulk_load((1..=7).map(|i| (i, i)));
+    /// assert!(tree.is_symmetric());
+    /// tree.insert(8, 8);
+    /// assert!(!tree.is_symmetric());
+    /// ```
+    pub fn is_symmetric(&self) -> bool {
+        Node::is_symmetric(&self.root)
+    }
+
+    /// 消耗当前树，生成它的镜像：交换每个节点的左右子树，键用`Reverse`包装，
+    /// 得到的树按原来键的降序排列，仍是一棵合法的查找树
+    /// # Example
+    /// ```
+    /// use std::cmp::Reverse;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let mirrored = tree.mirror();
+    /// let res: Vec<(&Reverse<i32>, &char)> = mirrored.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&Reverse(3), &'c'), (&Reverse(2), &'b'), (&Reverse(1), &'a')]);
+    /// assert_eq!(mirrored.get(&Reverse(2)), Some(&'b'));
+    /// ```
+    pub fn mirror(self) -> BSTree<Reverse<K>, V, A> {
+        let mut pool = NodePool::new_in(self.pool.allocator().clone());
+        let root = Node::mirror(self.root, &mut pool);
+        BSTree { root, pool }
+    }
+
+    /// 通过旋转把树原地展开成一条只有右孩子的有序链，不分配内存，相当于DSW算法的前半部分；
+    /// 展开后前序遍历即为键的升序
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// tree.flatten();
+    /// let res: Vec<&i32> = tree.preorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&1, &2, &3, &4, &5, &6, &7]);
+    /// assert_eq!(tree.levels().len(), 7);
+    /// ```
+    pub fn flatten(&mut self) {
+        Node::flatten(&mut self.root);
+        self.debug_validate("flatten");
+    }
+
+    /// 消耗当前树，将键值对分为满足f和不满足f的两棵平衡的树，只需遍历一次
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=6).map(|i| (i, i)));
+    /// let (even, odd) = tree.partition(|k, _| k % 2 == 0);
+    /// let res: Vec<&i32> = even.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&2, &4, &6]);
+    /// let res: Vec<&i32> = odd.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&1, &3, &5]);
+    /// ```
+    pub fn partition<F: FnMut(&K, &V) -> bool>(self, mut f: F) -> (Self, Self) {
+        let mut entries = Vec::new();
+        Node::drain_in_order(self.root, &mut entries);
+        let (yes, no): (Vec<_>, Vec<_>) = entries.into_iter().partition(|(k, v)| f(k, v));
+        let yes = BSTree::from_sorted_in(yes, self.pool.allocator().clone());
+        let no = BSTree::from_sorted_in(no, self.pool.allocator().clone());
+        yes.debug_validate("partition");
+        no.debug_validate("partition");
+        (yes, no)
+    }
+
+    /// 按键的升序交错产生两棵树中的全部键值对，不会构造新树或Vec；
+    /// 相同的键按tie处理：`Tie::Both`先产生当前树的再产生other的，`Tie::Left`和`Tie::Right`只产生一边的
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, Tie};
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(2, 'x'), (3, 'y')]);
+    /// let res: Vec<(&i32, &char)> = a.merge_iter(&b, Tie::Both).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'x'), (&3, &'c'), (&3, &'y')]);
+    /// let res: Vec<(&i32, &char)> = a.merge_iter(&b, Tie::Right).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'x'), (&3, &'y')]);
+    /// ```
+    pub fn merge_iter<'a>(&'a self, other: &'a Self, tie: Tie) -> MergeIter<'a, K, V> {
+        MergeIter::new(self.lower_bound(Bound::Unbounded), other.lower_bound(Bound::Unbounded), tie)
+    }
+
+    /// 按键的升序产生在任意一棵树中出现的键，相同的键只产生当前树中的键值对；
+    /// 与`merge_iter(other, Tie::Left)`相同，不会构造新树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(2, 'x'), (3, 'y')]);
+    /// let res: Vec<(&i32, &char)> = a.union_iter(&b).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'x'), (&3, &'c')]);
+    /// ```
+    pub fn union_iter<'a>(&'a self, other: &'a Self) -> MergeIter<'a, K, V> {
+        self.merge_iter(other, Tie::Left)
+    }
+
+    /// 按键的升序产生当前树中键也在other中出现的键值对，通过同时遍历两棵树得到，不会构造新树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(2, 'x'), (3, 'y'), (4, 'z')]);
+    /// let res: Vec<(&i32, &char)> = a.intersection_iter(&b).collect();
+    /// assert_eq!(res, vec![(&2, &'b'), (&3, &'c')]);
+    /// ```
+    pub fn intersection_iter<'a>(&'a self, other: &'a Self) -> Intersection<'a, K, V> {
+        Intersection::new(self.lower_bound(Bound::Unbounded), other.lower_bound(Bound::Unbounded))
+    }
+
+    /// 按键的升序产生当前树中键不在other中出现的键值对，通过同时遍历两棵树得到，不会构造新树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(2, 'x'), (3, 'y'), (4, 'z')]);
+    /// let res: Vec<(&i32, &char)> = a.difference_iter(&b).collect();
+    /// assert_eq!(res, vec![(&1, &'a')]);
+    /// ```
+    pub fn difference_iter<'a>(&'a self, other: &'a Self) -> Difference<'a, K, V> {
+        Difference::new(self.lower_bound(Bound::Unbounded), other.lower_bound(Bound::Unbounded))
+    }
+
+    /// 返回只在其中一棵树中出现的键组成的新树，通过同时中序遍历两棵树得到
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(2, 'x'), (3, 'y'), (4, 'z')]);
+    /// let diff = a.symmetric_difference(&b);
+    /// let res: Vec<(&i32, &char)> = diff.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&4, &'z')]);
+    /// ```
+    pub fn symmetric_difference(&self, other: &Self) -> Self
+    where
+        V: Clone,
+    {
+        let mut entries = Vec::new();
+        let mut left = InOrder::new(self.root.as_deref()).peekable();
+        let mut right = InOrder::new(other.root.as_deref()).peekable();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) if l.0 < r.0 => left.next(),
+                (Some(l), Some(r)) if l.0 > r.0 => right.next(),
+                (Some(_), Some(_)) => {
+                    left.next();
+                    right.next();
+                    continue;
+                }
+                (Some(_), None) => left.next(),
+                (None, Some(_)) => right.next(),
+                (None, None) => break,
+            };
+            entries.extend(next.map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Self::from_sorted_in(entries, self.allocator().clone())
+    }
+
+    /// 判断当前树的键是否都在other中出现，通过同时中序遍历两棵树判断
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(1, 10), (2, 20), (3, 30)]);
+    /// assert!(a.is_subset(&b));
+    /// assert!(!b.is_subset(&a));
+    /// ```
+    pub fn is_subset<U>(&self, other: &BSTree<K, U, A>) -> bool {
+        let mut others = InOrder::new(other.root.as_deref()).map(|(k, _)| k);
+        for (key, _) in InOrder::new(self.root.as_deref()) {
+            // other中小于key的键都可以跳过
+            match others.find(|k| *k >= key) {
+                Some(k) if k == key => {}
+                _ => return false,
+            }
+        }
+        true
     }
 
-    /// 判断当前树是否为空
+    /// 判断other的键是否都在当前树中出现
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree: BSTree<i32, i32> = BSTree::new();
-    /// assert!(tree.is_empty());
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let a = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let b = BSTree::bulk_load(vec![(2, 'x')]);
+    /// assert!(a.is_superset(&b));
+    /// assert!(!b.is_superset(&a));
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.root.is_none()
+    pub fn is_superset<U>(&self, other: &BSTree<K, U, A>) -> bool {
+        other.is_subset(self)
     }
 
-    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值
+    /// 比较当前树(旧)与other(新)，按键的升序返回从当前树变为other所需的全部修改
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get(&1), Some(&'a'));
-    /// tree.insert(2, 'b');
-    /// assert_eq!(tree.get(&2), Some(&'b'));
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, Change};
+    /// let old = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let new = BSTree::bulk_load(vec![(2, 'b'), (3, 'z'), (4, 'd')]);
+    /// assert_eq!(old.diff(&new), vec![
+    ///     Change::Removed(1, 'a'),
+    ///     Change::Modified(3, 'c', 'z'),
+    ///     Change::Added(4, 'd'),
+    /// ]);
     /// ```
-    pub fn insert(&mut self, key: K, value: V) {
-        match self.root {
-            None => self.root = Some(Box::new(Node::new(key, value))),
-            Some(ref mut node) => node.insert(key, value),
+    pub fn diff(&self, other: &Self) -> Vec<Change<K, V>>
+    where
+        V: Clone + PartialEq,
+    {
+        let mut changes = Vec::new();
+        let mut old = InOrder::new(self.root.as_deref()).peekable();
+        let mut new = InOrder::new(other.root.as_deref()).peekable();
+        loop {
+            let change = match (old.peek(), new.peek()) {
+                (Some(o), Some(n)) if o.0 < n.0 => old.next().map(|(k, v)| Change::Removed(k.clone(), v.clone())),
+                (Some(o), Some(n)) if o.0 > n.0 => new.next().map(|(k, v)| Change::Added(k.clone(), v.clone())),
+                (Some(o), Some(n)) => {
+                    let change = if o.1 != n.1 {
+                        Some(Change::Modified(o.0.clone(), o.1.clone(), n.1.clone()))
+                    } else {
+                        None
+                    };
+                    old.next();
+                    new.next();
+                    change
+                }
+                (Some(_), None) => old.next().map(|(k, v)| Change::Removed(k.clone(), v.clone())),
+                (None, Some(_)) => new.next().map(|(k, v)| Change::Added(k.clone(), v.clone())),
+                (None, None) => break,
+            };
+            changes.extend(change);
         }
+        changes
     }
 
-    /// 从树中删除键值对，如果找不到键值对，则忽略
+    /// 按order遍历树，f返回`ControlFlow::Break`时立即停止并返回，不会生成中间的Vec；
+    /// f返回`ControlFlow::Continue(Visit::SkipChildren)`时跳过当前节点的子树，详见[`Visit`]
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(1, 'a');
-    /// tree.delete(1);
-    /// assert!(tree.is_empty());
-    /// tree.delete(2);
-    /// assert!(tree.is_empty());
+    /// use std::ops::ControlFlow;
+    /// use an_ok_bstree::{RecursiveBSTree as BSTree, Order, Visit};
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// // 查找第一个值大于25的键
+    /// let found = tree.walk(Order::InOrder, |k, v| {
+    ///     if *v > 25 { ControlFlow::Break(*k) } else { ControlFlow::Continue(()) }
+    /// });
+    /// assert_eq!(found, ControlFlow::Break(3));
+    /// // 前序遍历，跳过键2的子树
+    /// let mut keys = Vec::new();
+    /// let _: ControlFlow<()> = tree.walk(Order::PreOrder, |k, _| {
+    ///     keys.push(*k);
+    ///     ControlFlow::Continue(if *k == 2 { Visit::SkipChildren } else { Visit::Children })
+    /// });
+    /// assert_eq!(keys, vec![4, 2, 6, 5, 7]);
     /// ```
-    pub fn delete(&mut self, key: K) {
-        if let Some(node) = self.root.take() {
-            self.root = node.delete(key)
-        }
+    pub fn walk<B, C, F>(&self, order: Order, mut f: F) -> ControlFlow<B>
+    where
+        C: Into<Visit>,
+        F: FnMut(&K, &V) -> ControlFlow<B, C>,
+    {
+        Node::walk(&self.root, order, &mut f)
     }
 
-    /// 根据键获取相应键值对
+    /// 按键的升序对全部键值对累积计算，不需要先生成迭代器
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get_pair(&1), Some((&1, &'a')));
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=4).map(|i| (i, i * 10)));
+    /// assert_eq!(tree.fold(0, |acc, _, v| acc + v), 100);
+    /// assert_eq!(tree.fold(String::new(), |acc, k, _| acc + &k.to_string()), "1234");
     /// ```
-    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
-        self.root.as_ref().and_then(|node| node.search_pair(key))
+    pub fn fold<B, F: FnMut(B, &K, &V) -> B>(&self, init: B, f: F) -> B {
+        self.fold_range(.., init, f)
     }
 
-    /// 根据键查找对应的值，找不到返回None，返回值的不可变借用
+    /// 按键的升序对键在range范围内的键值对累积计算，范围之外的子树不会被访问
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=10).map(|i| (i, i)));
+    /// assert_eq!(tree.fold_range(3..=5, 0, |acc, _, v| acc + v), 12);
+    /// assert_eq!(tree.fold_range(20.., 0, |acc, _, v| acc + v), 0);
     /// ```
-    pub fn get(&self, key: &K) -> Option<&V> {
-        self.root.as_ref().and_then(|node| node.search(key))
+    pub fn fold_range<B, R, F>(&self, range: R, init: B, mut f: F) -> B
+    where
+        R: RangeBounds<K>,
+        F: FnMut(B, &K, &V) -> B,
+    {
+        Node::fold_range(&self.root, &range, init, &mut f)
     }
 
-    /// 据键查找对应的值，找不到返回默认值
+    /// 按键的升序查找第一个值满足pred的键值对
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get_or(&1, &'z'), &'a');
-    /// assert_eq!(tree.get_or(&2, &'z'), &'z');
+    /// tree.insert(3, "carol");
+    /// tree.insert(1, "alice");
+    /// tree.insert(2, "bob");
+    /// assert_eq!(tree.find_by_value(|v| v.starts_with('b')), Some((&2, &"bob")));
+    /// assert_eq!(tree.find_by_value(|v| v.is_empty()), None);
     /// ```
-    pub fn get_or<'a>(&'a self, key: &K, default: &'a V) -> &'a V {
-        self.get(key).map_or(default, |data| data)
+    pub fn find_by_value<P: FnMut(&V) -> bool>(&self, mut pred: P) -> Option<(&K, &V)> {
+        let found = Node::walk(&self.root, Order::InOrder, &mut |k, v| {
+            if pred(v) {
+                ControlFlow::Break((k, v))
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        match found {
+            ControlFlow::Break(pair) => Some(pair),
+            ControlFlow::Continue(()) => None,
+        }
     }
 
-    /// 查找是否存在键值对
+    /// 按键的升序返回值满足pred的全部键
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.contains(&1), true);
-    /// assert_eq!(tree.contains(&2), false);
+    /// tree.insert(3, 'x');
+    /// tree.insert(1, 'x');
+    /// tree.insert(2, 'y');
+    /// assert_eq!(tree.keys_with_value(|v| *v == 'x'), vec![&1, &3]);
     /// ```
-    pub fn contains(&self, key: &K) -> bool {
-        self.get(key).is_some()
+    pub fn keys_with_value<P: FnMut(&V) -> bool>(&self, mut pred: P) -> Vec<&K> {
+        Node::fold_range(&self.root, &.., Vec::new(), &mut |mut keys, k, v| {
+            if pred(v) {
+                keys.push(k);
+            }
+            keys
+        })
     }
 
-    /// 返回树中的最大键值对
+    /// 按比较函数f返回值最小的键值对，只遍历一次，有多个最小值时返回键最小的一个
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.max_pair(), Some((&3, &'c')));
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut scores = BSTree::new();
+    /// scores.insert("bob", 7);
+    /// scores.insert("alice", 9);
+    /// scores.insert("carol", 3);
+    /// assert_eq!(scores.min_by_value(|a, b| a.cmp(b)), Some((&"carol", &3)));
     /// ```
-    pub fn max_pair(&self) -> Option<(&K, &V)> {
-        self.root.as_ref().map(|node| node.max_pair())
+    pub fn min_by_value<F: FnMut(&V, &V) -> Ordering>(&self, mut f: F) -> Option<(&K, &V)> {
+        Node::fold_range(&self.root, &.., None, &mut |best: Option<(&K, &V)>, k, v| match best {
+            Some(best) if f(v, best.1) != Ordering::Less => Some(best),
+            _ => Some((k, v)),
+        })
     }
 
-    /// 返回树中的最小键值对
+    /// 按比较函数f返回值最大的键值对，只遍历一次，有多个最大值时返回键最小的一个
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.min_pair(), Some((&1, &'a')));
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut scores = BSTree::new();
+    /// scores.insert("bob", 7);
+    /// scores.insert("alice", 9);
+    /// scores.insert("carol", 3);
+    /// assert_eq!(scores.max_by_value(|a, b| a.cmp(b)), Some((&"alice", &9)));
     /// ```
-    pub fn min_pair(&self) -> Option<(&K, &V)> {
-        self.root.as_ref().map(|node| node.min_pair())
+    pub fn max_by_value<F: FnMut(&V, &V) -> Ordering>(&self, mut f: F) -> Option<(&K, &V)> {
+        Node::fold_range(&self.root, &.., None, &mut |best: Option<(&K, &V)>, k, v| match best {
+            Some(best) if f(v, best.1) != Ordering::Greater => Some(best),
+            _ => Some((k, v)),
+        })
     }
 
-
-    ///返回第一个大于key的键值对
+    /// 估算树中节点占用的堆内存字节数，包括节点池中留待复用的空闲节点，不包括键和值自身额外持有的堆内存
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.successor(&1), Some((&2, &'b')));
-    /// assert_eq!(tree.successor(&0), Some((&1, &'a')));
-    /// assert_eq!(tree.successor(&3), None);
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree: BSTree<u64, u64> = BSTree::new();
+    /// assert_eq!(tree.memory_usage(), 0);
+    /// tree.insert(1, 1);
+    /// tree.insert(2, 2);
+    /// let node = 8 + 8 + 2 * std::mem::size_of::<usize>();
+    /// assert_eq!(tree.memory_usage(), 2 * node);
+    /// // 删除的节点留在节点池中，内存并没有归还
+    /// tree.delete(1);
+    /// assert_eq!(tree.memory_usage(), 2 * node);
+    /// tree.shrink_to_fit();
+    /// assert_eq!(tree.memory_usage(), node);
     /// ```
-    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
-        self.root.as_ref().and_then(|node| node.successor(key))
+    pub fn memory_usage(&self) -> usize {
+        (Node::size(&self.root) + self.pool.len()) * mem::size_of::<Node<K, V, A>>()
     }
 
-    ///返回第一个小于key的键值对
+    /// 估算树占用的堆内存字节数，f返回每个键值对自身额外持有的堆内存字节数，例如String的容量
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.predecessor(&3), Some((&2, &'b')));
-    /// assert_eq!(tree.predecessor(&5), Some((&3, &'c')));
-    /// assert_eq!(tree.predecessor(&1), None);
+    /// tree.insert(1, String::with_capacity(100));
+    /// assert_eq!(tree.memory_usage_with(|_, v| v.capacity()), tree.memory_usage() + 100);
     /// ```
-    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
-        self.root.as_ref().and_then(|node| node.predecessor(key))
+    pub fn memory_usage_with<F: FnMut(&K, &V) -> usize>(&self, mut f: F) -> usize {
+        self.fold(self.memory_usage(), |acc, k, v| acc + f(k, v))
     }
 
-    ///删除以val为根节点的树枝
+    /// 并行遍历全部键值对，按子树划分任务，不需要先把数据复制到Vec中，需要开启`rayon`特性
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// tree.insert(4, 'd');
-    /// tree.delete_tree(2);
-    /// assert!(!tree.contains(&2));
-    /// assert!(!tree.contains(&1));
-    /// tree.delete_tree(3);
-    /// assert!(tree.is_empty());
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// use rayon::prelude::*;
+    /// let tree = BSTree::bulk_load((1..=100).map(|i| (i, i * 2)));
+    /// let sum: i32 = tree.par_iter().map(|(_, v)| *v).sum();
+    /// assert_eq!(sum, 10100);
     /// ```
-    pub fn delete_tree(&mut self, key: K) {
-        match self.root {
-            None => {},
-            Some(ref mut node) if node.key == key => {
-                self.root = None;
-            }
-            Some(ref mut node) => node.delete_tree(key),
-        }
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(&self) -> ParIter<'_, K, V, A>
+    where
+        K: Sync,
+        V: Sync,
+    {
+        ParIter::new(self.root.as_deref())
     }
 
-    ///删除以val为根节点的树枝, 并返回切掉的树枝
+    /// 按键的升序对每个键值对调用f，只需O(1)的额外空间，不分配内存
+    ///
+    /// 遍历时临时反转走过的链接来记住回去的路，所以需要可变借用，遍历结束后树的结构完全复原。
+    /// f发生panic时，栈展开过程中会先恢复反转的链接，树保持原样
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
-    /// let mut tree = BSTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// tree.insert(4, 'd');
-    /// let rm_tree = tree.remove_tree(2);
-    /// assert!(rm_tree.contains(&2));
-    /// assert!(rm_tree.contains(&1));
-    /// let rm_tree = tree.remove_tree(12);
-    /// assert!(rm_tree.is_empty());
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::bulk_load(vec![(2, 'b'), (1, 'a'), (3, 'c')]);
+    /// let mut res = Vec::new();
+    /// tree.for_each(|k, v| res.push((*k, *v)));
+    /// assert_eq!(res, vec![(1, 'a'), (2, 'b'), (3, 'c')]);
     /// ```
-    pub fn remove_tree(&mut self, key: K) -> Self {
-        let ret_node = match self.root {
-            None => None,
-            Some(ref mut node) if node.key == key => self.root.take(),
-            Some(ref mut node) => node.remove_tree(key),
-        };
-        Self { root: ret_node }
-    }
-
-    ///前序遍历
-    fn prev_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::prev_order(&self.root, &mut buf);
-        buf
-    }
-
-    ///中序遍历
-    fn in_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::in_order(&self.root, &mut buf);
-        buf
+    pub fn for_each<F: FnMut(&K, &V)>(&mut self, mut f: F) {
+        Node::for_each_in_place(&mut self.root, &mut f);
+        self.debug_validate("for_each");
     }
 
-    ///后序遍历
-    fn post_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::post_order(&self.root, &mut buf);
-        buf
+    /// 返回一个可以跳过子树的前序遍历迭代器，产生某个键值对后调用`skip_subtree`即可剪掉它的整棵子树，
+    /// 不需要先遍历再过滤
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// // 只关心不小于4的键：遇到小于4的节点时，它的左子树中的键更小，可以直接跳过
+    /// let mut walker = tree.walker();
+    /// let mut res = Vec::new();
+    /// while let Some((k, _)) = walker.next() {
+    ///     if *k < 4 {
+    ///         walker.skip_subtree();
+    ///     } else {
+    ///         res.push(*k);
+    ///     }
+    /// }
+    /// assert_eq!(res, vec![4, 6, 5, 7]);
+    /// ```
+    pub fn walker(&self) -> Walker<'_, K, V, A> {
+        Walker::new(self.root.as_deref())
     }
 
-    ///层序遍历
-    fn level_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::level_order(&self.root, &mut buf);
-        buf
+    /// 按键的升序对每个键值对调用f，f可以原地修改值，只需遍历一次
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::bulk_load(vec![(1, 10), (2, 20), (3, 30)]);
+    /// let mut total = 0;
+    /// tree.for_each_mut(|_, v| {
+    ///     total += *v;
+    ///     *v = total;
+    /// });
+    /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &10), (&2, &30), (&3, &60)]);
+    /// ```
+    pub fn for_each_mut<F: FnMut(&K, &mut V)>(&mut self, mut f: F) {
+        Node::for_each_mut(&mut self.root, &mut f);
     }
 
     /// 前序遍历迭代器
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
     /// tree.insert(2, 'b');
     /// tree.insert(3, 'c');
@@ -267,7 +2311,7 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
     /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
     /// ```
-    pub fn preorder_iter(&self) -> TraverseIter<K, V> {
+    pub fn preorder_iter(&self) -> TraverseIter<'_, K, V> {
         let pre_order = self.prev_order();
         let mut queue = VecDeque::new();
         for key in pre_order {
@@ -281,7 +2325,7 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// 中序遍历迭代器
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
     /// tree.insert(2, 'b');
     /// tree.insert(3, 'c');
@@ -289,7 +2333,7 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
     /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
     /// ```
-    pub fn inorder_iter(&self) -> TraverseIter<K, V> {
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
         let in_order = self.in_order();
         let mut queue = VecDeque::new();
         for key in in_order {
@@ -300,10 +2344,115 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
         TraverseIter::new(queue)
     }
 
+    /// 消耗当前树，按键的升序产生全部键
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(2, 'b'), (1, 'a'), (3, 'c')]);
+    /// let keys: Vec<i32> = tree.into_keys().collect();
+    /// assert_eq!(keys, vec![1, 2, 3]);
+    /// ```
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        let mut entries = Vec::new();
+        Node::drain_in_order(self.root, &mut entries);
+        IntoKeys::new(entries)
+    }
+
+    /// 消耗当前树，按键的升序产生全部值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load(vec![(2, String::from("b")), (1, String::from("a"))]);
+    /// let values: Vec<String> = tree.into_values().collect();
+    /// assert_eq!(values, vec!["a", "b"]);
+    /// ```
+    pub fn into_values(self) -> IntoValues<K, V> {
+        let mut entries = Vec::new();
+        Node::drain_in_order(self.root, &mut entries);
+        IntoValues::new(entries)
+    }
+
+    /// 按键的升序产生名次（从0开始）落在range内的键值对，例如排序后的第1000到1049行，用于分页；
+    /// 树中没有保存子树大小，需要按中序数过前面的start个节点，遍历到end后即停止，
+    /// 需要直接定位到start时使用保存子树大小的`OrderStatTree`
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((0..10).map(|i| (i * 10, i)));
+    /// let res: Vec<&i32> = tree.iter_slice(3..6).map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&30, &40, &50]);
+    /// assert_eq!(tree.iter_slice(8..).count(), 2);
+    /// assert_eq!(tree.iter_slice(20..30).count(), 0);
+    /// ```
+    pub fn iter_slice<R: RangeBounds<usize>>(&self, range: R) -> TraverseIter<'_, K, V> {
+        let (start, end) = range::rank_window(&range);
+        let mut queue = VecDeque::new();
+        Node::slice(&self.root, start, end, &mut 0, &mut queue);
+        TraverseIter::new(queue)
+    }
+
+    /// 从树中等概率随机选取一个键值对，树为空时返回None；rng每次调用返回一个均匀分布的64位随机数，
+    /// 可以包装任意随机数生成器。树中没有保存子树大小，需要先数出节点个数，再按中序数到选中的名次，
+    /// 需要O(h)时间时使用保存子树大小的`OrderStatTree`
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=5).map(|i| (i, i * 10)));
+    /// let mut state = 7u64;
+    /// let mut rng = || {
+    ///     state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     state
+    /// };
+    /// let (k, v) = tree.sample(&mut rng).unwrap();
+    /// assert_eq!(*v, k * 10);
+    /// assert_eq!(BSTree::<i32, i32>::new().sample(&mut rng), None);
+    /// ```
+    pub fn sample<F: FnMut() -> u64>(&self, rng: &mut F) -> Option<(&K, &V)> {
+        let len = Node::size(&self.root);
+        if len == 0 {
+            return None;
+        }
+        let rank = random::below(rng(), len);
+        self.iter_slice(rank..=rank).next()
+    }
+
+    /// 从树中等概率随机选取n个互不相同的键值对，按键的升序返回，n超过树中节点个数时返回全部键值对；
+    /// 按中序遍历一遍，每个节点以(还需选取的个数 / 剩余节点个数)的概率被选中
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=100).map(|i| (i, ())));
+    /// let mut state = 7u64;
+    /// let mut rng = || {
+    ///     state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     state
+    /// };
+    /// let picked: Vec<&i32> = tree.sample_n(10, &mut rng).into_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(picked.len(), 10);
+    /// assert!(picked.windows(2).all(|w| w[0] < w[1]));
+    /// assert_eq!(tree.sample_n(200, &mut rng).len(), 100);
+    /// ```
+    pub fn sample_n<F: FnMut() -> u64>(&self, n: usize, rng: &mut F) -> Vec<(&K, &V)> {
+        let mut remaining = Node::size(&self.root);
+        let mut needed = n.min(remaining);
+        let mut picked = Vec::with_capacity(needed);
+        for pair in InOrder::new(self.root.as_deref()) {
+            if needed == 0 {
+                break;
+            }
+            if random::below(rng(), remaining) < needed {
+                picked.push(pair);
+                needed -= 1;
+            }
+            remaining -= 1;
+        }
+        picked
+    }
+
     /// 后序遍历迭代器
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
     /// tree.insert(2, 'b');
     /// tree.insert(3, 'c');
@@ -311,7 +2460,7 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// let res: Vec<(&i32, &char)> = tree.postorder_iter().collect();
     /// assert_eq!(res, vec![(&1, &'a'), (&3, &'c'), (&2, &'b')]);
     /// ```
-    pub fn postorder_iter(&self) -> TraverseIter<K, V> {
+    pub fn postorder_iter(&self) -> TraverseIter<'_, K, V> {
         let post_order = self.post_order();
         let mut queue = VecDeque::new();
         for key in post_order {
@@ -325,7 +2474,7 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// 层序遍历迭代器
     /// # Example
     /// ```
-    /// use an_ok_bstree::BSTree;
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
     /// let mut tree = BSTree::new();
     /// tree.insert(2, 'b');
     /// tree.insert(3, 'c');
@@ -333,7 +2482,7 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
     /// let res: Vec<(&i32, &char)> = tree.levelorder_iter().collect();
     /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
     /// ```
-    pub fn levelorder_iter(&self) -> TraverseIter<K, V> {
+    pub fn levelorder_iter(&self) -> TraverseIter<'_, K, V> {
         let level_order = self.level_order();
         let mut queue = VecDeque::new();
         for key in level_order {
@@ -343,4 +2492,170 @@ impl<K: PartialOrd + Clone, V> BSTree<K, V> {
         }
         TraverseIter::new(queue)
     }
+
+    /// 之字形层序遍历迭代器，偶数层从左到右，奇数层从右到左
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// let res: Vec<&i32> = tree.zigzag_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &6, &2, &1, &3, &5, &7]);
+    /// ```
+    pub fn zigzag_iter(&self) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        let _: ControlFlow<()> = Node::walk(&self.root, Order::ZigZag, &mut |k, v| {
+            queue.push_back((k, v));
+            ControlFlow::Continue(())
+        });
+        TraverseIter::new(queue)
+    }
+
+    /// 从根节点开始，依次产生查找key时经过的键值对；key存在时以它结束，不存在时以它应该插入位置的父节点结束
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// let res: Vec<&i32> = tree.path_to(&3).map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &2, &3]);
+    /// let res: Vec<&i32> = tree.path_to(&9).map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &6, &7]);
+    /// ```
+    pub fn path_to(&self, key: &K) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        Node::path_to(&self.root, key, &mut queue);
+        TraverseIter::new(queue)
+    }
+
+    /// 从根节点开始一直向左走，依次产生左侧链上的键值对，最后一个是最小的键值对；
+    /// 这些键把树按从大到小的顺序逐层切分，可用于范围剪枝或可视化
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// let res: Vec<&i32> = tree.left_spine().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &2, &1]);
+    /// ```
+    pub fn left_spine(&self) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            queue.push_back((&node.key, &node.value));
+            current = node.left();
+        }
+        TraverseIter::new(queue)
+    }
+
+    /// 从根节点开始一直向右走，依次产生右侧链上的键值对，最后一个是最大的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// let res: Vec<&i32> = tree.right_spine().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &6, &7]);
+    /// ```
+    pub fn right_spine(&self) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            queue.push_back((&node.key, &node.value));
+            current = node.right();
+        }
+        TraverseIter::new(queue)
+    }
+
+    /// 把树的结构编码为紧凑的文本：按前序写出每个键，空链接写为`#`，记号之间以空格分隔，
+    /// 只记录键和形状，不记录值，可以用`decode_structure`原样还原
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// for k in [8, 5, 3, 6, 15] {
+    ///     tree.insert(k, ());
+    /// }
+    /// assert_eq!(tree.encode_structure(), "8 5 3 # # 6 # # 15 # #");
+    /// assert_eq!(BSTree::<i32, ()>::new().encode_structure(), "#");
+    /// ```
+    pub fn encode_structure(&self) -> String
+    where
+        K: fmt::Display,
+    {
+        let mut buf = String::new();
+        Node::encode(&self.root, &mut buf);
+        buf
+    }
+
+    /// 带深度的前序遍历迭代器，产生(深度, 键, 值)，根节点的深度为0
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(usize, &i32, &char)> = tree.depth_iter().collect();
+    /// assert_eq!(res, vec![(0, &2, &'b'), (1, &1, &'a'), (1, &3, &'c')]);
+    /// ```
+    pub fn depth_iter(&self) -> DepthIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        Node::depth_order(&self.root, 0, &mut queue);
+        DepthIter::new(queue)
+    }
+
+    /// 按键的升序返回每个节点的键和平衡因子，平衡因子为左子树高度减去右子树高度，
+    /// 绝对值大的节点就是树不平衡的位置，可用来调整插入顺序或重建的时机
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(3, 'c');
+    /// tree.insert(4, 'd');
+    /// tree.insert(5, 'e');
+    /// assert_eq!(tree.balance_factors(), vec![(&1, 0), (&2, -2), (&3, -2), (&4, -1), (&5, 0)]);
+    /// ```
+    pub fn balance_factors(&self) -> Vec<(&K, isize)> {
+        let mut out = Vec::new();
+        Node::balance_factors(&self.root, &mut out);
+        out
+    }
+
+    /// 带父节点的前序遍历迭代器，产生(父节点的键, 键, 值)，根节点的父节点为None；
+    /// 父节点总是先于子节点产生，可以直接按顺序写入带父节点列的关系表
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(Option<&i32>, &i32, &char)> = tree.parent_iter().collect();
+    /// assert_eq!(res, vec![(None, &2, &'b'), (Some(&2), &1, &'a'), (Some(&2), &3, &'c')]);
+    /// ```
+    pub fn parent_iter(&self) -> ParentIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        Node::parent_order(&self.root, None, &mut queue);
+        ParentIter::new(queue)
+    }
+
+    /// 按键的升序遍历以key为根节点的树枝，不会把树枝从树中切下，key不存在时迭代器为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let mut tree = BSTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// tree.insert(4, 'd');
+    /// let res: Vec<(&i32, &char)> = tree.subtree_iter(&2).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// assert!(tree.contains(&2));
+    /// ```
+    pub fn subtree_iter(&self, key: &K) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        if let Some(node) = self.root.as_ref().and_then(|node| node.search_node(key)) {
+            node.in_order_pairs(&mut queue);
+        }
+        TraverseIter::new(queue)
+    }
 }
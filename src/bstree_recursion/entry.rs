@@ -0,0 +1,63 @@
+use crate::allocator::{Allocator, Global};
+use crate::bstree_recursion::node::{Link, Node};
+use crate::pool::NodePool;
+
+/// 树中一个已经存在的键值对的入口，可以原地修改或删除该键值对
+pub struct OccupiedEntry<'a, K, V, A: Allocator = Global> {
+    // 始终指向一个非空的链接
+    slot: &'a mut Link<K, V, A>,
+    // 删除入口时节点放回树的节点池
+    pool: &'a mut NodePool<Node<K, V, A>, A>,
+}
+
+impl<'a, K: PartialOrd + Clone, V, A: Allocator + Clone> OccupiedEntry<'a, K, V, A> {
+    pub(crate) fn new(slot: &'a mut Link<K, V, A>, pool: &'a mut NodePool<Node<K, V, A>, A>) -> Option<Self> {
+        if slot.is_some() {
+            Some(OccupiedEntry { slot, pool })
+        } else {
+            None
+        }
+    }
+
+    fn node(&self) -> &Node<K, V, A> {
+        self.slot.as_ref().unwrap()
+    }
+
+    /// 入口的键
+    pub fn key(&self) -> &K {
+        &self.node().key
+    }
+
+    /// 入口值的不可变借用
+    pub fn get(&self) -> &V {
+        &self.node().value
+    }
+
+    /// 入口值的可变借用
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.slot.as_mut().unwrap().value
+    }
+
+    /// 消耗入口，返回生命周期与树相同的值的可变借用
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.slot.as_mut().unwrap().value
+    }
+
+    /// 替换入口的值，并返回旧值
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// 从树中删除入口对应的键值对，并返回键和值
+    pub fn remove_entry(self) -> (K, V) {
+        let (new_root, node) = Node::unlink(self.slot.take().unwrap());
+        *self.slot = new_root;
+        let node = self.pool.release(node);
+        (node.key, node.value)
+    }
+
+    /// 从树中删除入口对应的键值对，并返回值
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
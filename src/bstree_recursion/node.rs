@@ -1,57 +1,255 @@
+use std::cmp::Reverse;
 use std::collections::VecDeque;
+use std::fmt;
+use std::iter::Peekable;
+#[cfg(not(feature = "allocator_api"))]
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{ControlFlow, RangeBounds};
+use std::str::FromStr;
+use crate::allocator::{Allocator, Global};
+use crate::cursor::Neighbor;
+use crate::error::DecodeError;
+use crate::metrics;
+use crate::pool::NodePool;
+use crate::random;
+use crate::range;
+use crate::trace;
+use crate::walk::{Order, Visit};
 
-pub type Link<K, V> = Option<Box<Node<K, V>>>;
-pub struct Node<K, V> {
+// 并行构建时，子树的元素个数不超过该值就不再拆分
+#[cfg(feature = "rayon")]
+const PAR_BUILD_THRESHOLD: usize = 4096;
+
+pub type Link<K, V, A = Global> = Option<NodeBox<K, V, A>>;
+#[cfg(feature = "allocator_api")]
+pub type NodeBox<K, V, A = Global> = Box<Node<K, V, A>, A>;
+#[cfg(not(feature = "allocator_api"))]
+pub type NodeBox<K, V, A = Global> = Box<Node<K, V, A>>;
+pub struct Node<K, V, A: Allocator = Global> {
     pub key: K,
-    value: V,
-    left: Link<K, V>,
-    right: Link<K, V>,
+    pub value: V,
+    left: Link<K, V, A>,
+    right: Link<K, V, A>,
+    // 稳定版的Box没有分配器参数，由这里标记节点所属的分配器
+    #[cfg(not(feature = "allocator_api"))]
+    alloc: PhantomData<A>,
 }
 
-impl<K: PartialOrd + Clone, V> Node<K, V> {
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> Node<K, V, A> {
     pub fn new(key: K, value: V) -> Self {
         Node {
             key,
             value,
             left: None,
             right: None,
+            #[cfg(not(feature = "allocator_api"))]
+            alloc: PhantomData,
         }
     }
 
-    // 插入键值对
-    pub fn insert(&mut self, key: K, value: V) {
-        if self.key > key {
-            match self.left {
-                None => {
-                    self.left = Some(Box::new(Node::new(key, value)));
-                }
-                Some(ref mut node) => node.insert(key, value),
+    // 返回左子节点
+    pub fn left(&self) -> Option<&Node<K, V, A>> {
+        self.left.as_deref()
+    }
+
+    // 返回右子节点
+    pub fn right(&self) -> Option<&Node<K, V, A>> {
+        self.right.as_deref()
+    }
+
+    // 由按键严格升序排列的键值对自底向上构建一棵平衡的树，从items中恰好取出n个元素，节点从pool中分配
+    pub fn build_sorted<I: Iterator<Item = (K, V)>>(items: &mut I, n: usize, pool: &mut NodePool<Node<K, V, A>, A>) -> Link<K, V, A> {
+        if n == 0 {
+            return None;
+        }
+        let left = Self::build_sorted(items, n / 2, pool);
+        let (key, value) = items.next()?;
+        let mut node = pool.alloc(Node::new(key, value));
+        node.left = left;
+        node.right = Self::build_sorted(items, n - n / 2 - 1, pool);
+        Some(node)
+    }
+
+    // 将子树挂到当前树最大节点的右侧，调用者需保证子树中的键都大于当前树的最大键
+    pub fn attach_max(&mut self, subtree: Link<K, V, A>) {
+        match self.right {
+            None => self.right = subtree,
+            Some(ref mut right) => right.attach_max(subtree),
+        }
+    }
+
+    // 将按键严格升序排列的键值对一次性插入以link为根的树，键已经存在时替换旧值
+    // 每个节点处把待插入的键值对分为左右两部分分别插入，到达空链接时直接构建平衡的子树
+    pub fn insert_sorted(link: &mut Link<K, V, A>, mut items: Vec<(K, V)>, pool: &mut NodePool<Node<K, V, A>, A>) {
+        if items.is_empty() {
+            return;
+        }
+        match link {
+            None => {
+                let n = items.len();
+                *link = Self::build_sorted(&mut items.into_iter(), n, pool);
             }
-        } else if self.key < key {
-            match self.right {
-                None => {
-                    self.right = Some(Box::new(Node::new(key, value)));
+            Some(node) => {
+                let lower = items.partition_point(|(k, _)| *k < node.key);
+                let upper = items.partition_point(|(k, _)| *k <= node.key);
+                let right = items.split_off(upper);
+                if upper > lower {
+                    if let Some((_, value)) = items.pop() {
+                        node.value = value;
+                    }
                 }
-                Some(ref mut node) => node.insert(key, value),
+                Self::insert_sorted(&mut node.left, items, pool);
+                Self::insert_sorted(&mut node.right, right, pool);
             }
+        }
+    }
+
+    // 返回键key所在的链接，key不存在时返回它应当插入的空链接
+    pub fn find_slot<'a>(link: &'a mut Link<K, V, A>, key: &K) -> &'a mut Link<K, V, A> {
+        Self::seek_slot(link, key, &mut 0)
+    }
+
+    // 插入使用的find_slot，同时发出insert事件，所有插入键值对的方法都经过这里
+    pub fn insert_slot<'a>(link: &'a mut Link<K, V, A>, key: &K) -> &'a mut Link<K, V, A> {
+        let mut path_len = 0;
+        let slot = Self::seek_slot(link, key, &mut path_len);
+        trace::insert(|| path_len);
+        slot
+    }
+
+    // find_slot的实现，path_len累加查找途中经过的节点个数
+    fn seek_slot<'a>(link: &'a mut Link<K, V, A>, key: &K, path_len: &mut usize) -> &'a mut Link<K, V, A> {
+        if !link.as_ref().is_some_and(|node| node.key != *key) {
+            if link.is_some() {
+                metrics::visit(1);
+                *path_len += 1;
+            }
+            return link;
+        }
+        metrics::visit(2);
+        *path_len += 1;
+        let node = link.as_mut().unwrap();
+        if node.key > *key {
+            Self::seek_slot(&mut node.left, key, path_len)
+        } else {
+            Self::seek_slot(&mut node.right, key, path_len)
+        }
+    }
+
+    // 判断键在[min, max]范围内的树能否整体挂到link下的同一个空链接上
+    pub fn can_graft(link: &Link<K, V, A>, min: &K, max: &K) -> bool {
+        match link {
+            None => true,
+            Some(node) if *max < node.key => Self::can_graft(&node.left, min, max),
+            Some(node) if *min > node.key => Self::can_graft(&node.right, min, max),
+            Some(_) => false,
+        }
+    }
+
+    // 返回最小节点所在的链接
+    pub fn min_slot(link: &mut Link<K, V, A>) -> &mut Link<K, V, A> {
+        if link.as_ref().is_some_and(|node| node.left.is_some()) {
+            Self::min_slot(&mut link.as_mut().unwrap().left)
+        } else {
+            link
+        }
+    }
+
+    // 返回最大节点所在的链接
+    pub fn max_slot(link: &mut Link<K, V, A>) -> &mut Link<K, V, A> {
+        if link.as_ref().is_some_and(|node| node.right.is_some()) {
+            Self::max_slot(&mut link.as_mut().unwrap().right)
         } else {
-            self.value = value;
+            link
         }
     }
 
+    // 按升序收集keys中各个键对应值的可变借用，keys必须严格升序，不存在的键会被跳过
+    pub fn values_mut<'a>(link: &'a mut Link<K, V, A>, keys: &[&K], buf: &mut Vec<&'a mut V>) {
+        let node = match link {
+            Some(node) if !keys.is_empty() => node,
+            _ => return,
+        };
+        let Node { key, value, left, right, .. } = &mut **node;
+        // keys有序，小于、等于、大于当前键的部分各自连续
+        let lower = keys.iter().take_while(|k| **k < key).count();
+        let upper = keys.iter().take_while(|k| **k <= key).count();
+        Self::values_mut(left, &keys[..lower], buf);
+        if upper > lower {
+            buf.push(value);
+        }
+        Self::values_mut(right, &keys[upper..], buf);
+    }
+
+    // 返回a和b的最近公共祖先，调用者需保证a和b都在以当前节点为根的树中
+    pub fn lca(&self, a: &K, b: &K) -> &Node<K, V, A> {
+        let child = if *a < self.key && *b < self.key {
+            self.left.as_ref()
+        } else if *a > self.key && *b > self.key {
+            self.right.as_ref()
+        } else {
+            None
+        };
+        // a、b分处两侧或其中一个就是当前节点时，当前节点就是最近公共祖先
+        child.map_or(self, |child| child.lca(a, b))
+    }
+
+    // 返回从当前节点到键key所在节点的边数，调用者需保证key在以当前节点为根的树中
+    pub fn depth_of(&self, key: &K) -> usize {
+        let child = if *key < self.key {
+            self.left.as_ref()
+        } else if *key > self.key {
+            self.right.as_ref()
+        } else {
+            None
+        };
+        child.map_or(0, |child| 1 + child.depth_of(key))
+    }
+
     // 返回查找的键值对的不可变借用
     pub fn search_pair(&self, key: &K,) -> Option<(&K, &V)> {
         if self.key < *key {
+            metrics::visit(1);
             self.right
                 .as_ref()
                 .and_then(|right| right.search_pair(key))
         } else if self.key > *key {
+            metrics::visit(2);
             self.left.as_ref().and_then(|left| left.search_pair(key))
         } else {
+            metrics::visit(2);
             Some((&self.key, &self.value))
         }
     }
 
+    // 返回键为key的节点
+    pub fn search_node(&self, key: &K) -> Option<&Node<K, V, A>> {
+        if self.key < *key {
+            self.right.as_ref().and_then(|right| right.search_node(key))
+        } else if self.key > *key {
+            self.left.as_ref().and_then(|left| left.search_node(key))
+        } else {
+            Some(self)
+        }
+    }
+
+    // 返回键为key的节点的父节点，key就是当前节点或不在树中时返回None
+    pub fn parent_node(&self, key: &K) -> Option<&Node<K, V, A>> {
+        let child = if *key < self.key {
+            self.left.as_ref()?
+        } else if *key > self.key {
+            self.right.as_ref()?
+        } else {
+            return None;
+        };
+        if child.key == *key {
+            Some(self)
+        } else {
+            child.parent_node(key)
+        }
+    }
+
     // 根据键查找对应的值
     pub fn search(&self, key: &K) -> Option<&V> {
         self.search_pair(key).map(|(_, v)| v)
@@ -103,117 +301,760 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
         }
     }
 
+    // 一次向下查找同时返回不大于key的最大键值对和不小于key的最小键值对，floor和ceiling为上层已经找到的候选
+    pub fn neighbors<'a>(&'a self, key: &K, floor: Neighbor<'a, K, V>, ceiling: Neighbor<'a, K, V>) -> (Neighbor<'a, K, V>, Neighbor<'a, K, V>) {
+        let pair = Some((&self.key, &self.value));
+        if self.key < *key {
+            match self.right {
+                None => (pair, ceiling),
+                Some(ref right) => right.neighbors(key, pair, ceiling),
+            }
+        } else if self.key > *key {
+            match self.left {
+                None => (floor, pair),
+                Some(ref left) => left.neighbors(key, floor, pair),
+            }
+        } else {
+            (pair, pair)
+        }
+    }
+
+    // 返回第一个大于key的键值对，值为可变借用
+    pub fn successor_mut(&mut self, key: &K) -> Option<(&K, &mut V)> {
+        let Node { key: k, value, left, right, .. } = self;
+        if *k > *key {
+            left.as_mut()
+                .and_then(|left| left.successor_mut(key))
+                .or(Some((k, value)))
+        } else if *k < *key {
+            right.as_mut().and_then(|right| right.successor_mut(key))
+        } else {
+            right.as_mut().map(|right| right.min_pair_mut())
+        }
+    }
+
+    // 返回第一个小于key的键值对，值为可变借用
+    pub fn predecessor_mut(&mut self, key: &K) -> Option<(&K, &mut V)> {
+        let Node { key: k, value, left, right, .. } = self;
+        if *k < *key {
+            right.as_mut()
+                .and_then(|right| right.predecessor_mut(key))
+                .or(Some((k, value)))
+        } else if *k > *key {
+            left.as_mut().and_then(|left| left.predecessor_mut(key))
+        } else {
+            left.as_mut().map(|left| left.max_pair_mut())
+        }
+    }
+
+    // 返回最小键值对，值为可变借用
+    pub fn min_pair_mut(&mut self) -> (&K, &mut V) {
+        match self.left {
+            Some(ref mut left) => left.min_pair_mut(),
+            None => (&self.key, &mut self.value),
+        }
+    }
+
+    // 返回最大键值对，值为可变借用
+    pub fn max_pair_mut(&mut self) -> (&K, &mut V) {
+        match self.right {
+            Some(ref mut right) => right.max_pair_mut(),
+            None => (&self.key, &mut self.value),
+        }
+    }
+
+    // 按键的升序收集不小于key的前n个键值对
+    pub fn collect_ge<'a>(&'a self, key: &K, n: usize, buf: &mut Vec<(&'a K, &'a V)>) {
+        if self.key >= *key {
+            if let Some(ref left) = self.left {
+                left.collect_ge(key, n, buf);
+            }
+            if buf.len() < n {
+                buf.push((&self.key, &self.value));
+            }
+        }
+        if buf.len() < n {
+            if let Some(ref right) = self.right {
+                right.collect_ge(key, n, buf);
+            }
+        }
+    }
+
+    // 按键的降序收集小于key的前n个键值对
+    pub fn collect_lt<'a>(&'a self, key: &K, n: usize, buf: &mut Vec<(&'a K, &'a V)>) {
+        if self.key < *key {
+            if let Some(ref right) = self.right {
+                right.collect_lt(key, n, buf);
+            }
+            if buf.len() < n {
+                buf.push((&self.key, &self.value));
+            }
+        }
+        if buf.len() < n {
+            if let Some(ref left) = self.left {
+                left.collect_lt(key, n, buf);
+            }
+        }
+    }
+
     //找出当前树中值最小的节点，返回元组:(除去最小节点后剩下的树，最小节点)
-    fn remove_min(mut self) -> (Link<K, V>, Box<Node<K, V>>) {
-        match self.left.take() {
+    fn remove_min(mut node: NodeBox<K, V, A>) -> (Link<K, V, A>, NodeBox<K, V, A>) {
+        match node.left.take() {
             Some(left) => {
-                let (new_left, min) = left.remove_min();
-                self.left = new_left;
-                (Some(Box::new(self)), min)
+                let (new_left, min) = Self::remove_min(left);
+                node.left = new_left;
+                (Some(node), min)
             }
-            None => (self.right.take(), Box::new(self)),
+            None => (node.right.take(), node),
         }
     }
 
     //将两棵子树合并为一棵，返回新生成树的根节点
     fn combine_two_subtrees(
-        left: Node<K, V>,
-        right: Node<K, V>,
-    ) -> Box<Node<K, V>> {
+        left: NodeBox<K, V, A>,
+        right: NodeBox<K, V, A>,
+    ) -> NodeBox<K, V, A> {
         // 得到右子树中最小的节点和去除最小节点后剩余的树
-        let (remain_tree, min) = right.remove_min();
+        let (remain_tree, min) = Self::remove_min(right);
         // 最小节点作为两个子树的新根节点
         let mut new_root = min;
         new_root.right = remain_tree;
-        new_root.left = Some(Box::new(left));
+        new_root.left = Some(left);
         new_root
     }
 
-    //删除当前节点，并返回新的根节点
-    pub fn delete_root(mut self) -> Link<K, V> {
+    //删除节点后，将它的左右子树合并为一棵，返回新的根节点
+    fn merge_children(left: Link<K, V, A>, right: Link<K, V, A>) -> Link<K, V, A> {
         // 二叉搜索树树删除节点的三种情况：
         // 1.如果是叶子节点，则直接删除
         // 2.如果待删除节点只有左子树或只有右子树，删除该节点，然后将左子树或右子树移动到该节点
         // 3.如果待删除节点左右子树都有，就选取右子树中最小的节点代替待删除节点的位置(或者取左子树中最大节点代替也可以)。
-        match (self.left.take(), self.right.take()) {
-            (None, None) => None,
-            (Some(left), None) => Some(left),
-            (None, Some(right)) => Some(right),
-            (Some(left), Some(right)) => Some(Self::combine_two_subtrees(*left, *right)),
+        match (left, right) {
+            (None, None) => {
+                trace::delete("leaf");
+                None
+            }
+            (Some(child), None) | (None, Some(child)) => {
+                trace::delete("one child");
+                Some(child)
+            }
+            (Some(left), Some(right)) => {
+                trace::delete("two children");
+                Some(Self::combine_two_subtrees(left, right))
+            }
         }
     }
 
-    //删除节点key，返回的新的根节点
-    pub fn delete(mut self, key: K) -> Link<K, V> {
-        if self.key < key {
-            if let Some(right) = self.right.take() {
-                self.right = right.delete(key);
-                return Some(Box::new(self));
-            }
-        } else if self.key > key {
-            if let Some(left) = self.left.take() {
-                self.left = left.delete(key);
-                return Some(Box::new(self));
+    //把节点从树中摘下，返回新的根节点以及摘下的节点，摘下的节点没有子树，可以重新挂到树上
+    pub fn unlink(mut node: NodeBox<K, V, A>) -> (Link<K, V, A>, NodeBox<K, V, A>) {
+        let new_root = Self::merge_children(node.left.take(), node.right.take());
+        (new_root, node)
+    }
+
+    // 用f(键, 旧值)得到新值写回link处的节点，节点仍留在原位置；
+    // f发生panic时旧值已经交给了f，ValueHole把节点的左右子树合并后接回link，树的其余部分保持不变
+    pub fn resolve_value<F: FnOnce(&K, V) -> V>(link: &mut Link<K, V, A>, pool: &mut NodePool<Node<K, V, A>, A>, f: F) {
+        let node = match link.take() {
+            Some(node) => pool.release(node),
+            None => return,
+        };
+        let Node { key, value, left, right, .. } = node;
+        let mut hole = ValueHole { link, children: Some((left, right)) };
+        let value = f(&key, value);
+        let (left, right) = hole.children.take().unwrap();
+        let mut node = Node::new(key, value);
+        node.left = left;
+        node.right = right;
+        *hole.link = Some(pool.alloc(node));
+    }
+
+    // 从以link为根的树中删除keys中的键，keys必须严格升序，返回实际删除的个数
+    pub fn remove_sorted(link: &mut Link<K, V, A>, keys: &[K], pool: &mut NodePool<Node<K, V, A>, A>) -> usize {
+        let node = match link {
+            Some(node) if !keys.is_empty() => node,
+            _ => return 0,
+        };
+        let lower = keys.partition_point(|k| *k < node.key);
+        let upper = keys.partition_point(|k| *k <= node.key);
+        let mut removed = Self::remove_sorted(&mut node.left, &keys[..lower], pool)
+            + Self::remove_sorted(&mut node.right, &keys[upper..], pool);
+        // 子树处理完后再删除当前节点
+        if upper > lower {
+            if let Some(node) = link.take() {
+                let (new_root, node) = Self::unlink(node);
+                *link = new_root;
+                pool.release(node);
             }
+            removed += 1;
         }
-        else {
-            return self.delete_root()
-        }
-        Some(Box::new(self))
+        removed
     }
 
-    // 删除以key为根节点的树枝,无法直接删除根节点
-    pub fn delete_tree(&mut self, key: K) {
+    // 删除以key为根节点的树枝, 并返回切掉的树枝
+    // 无法直接删除根节点
+    pub fn remove_tree(&mut self, key: K) -> Link<K, V, A> {
         if self.key < key {
             if let Some(ref mut right) = self.right {
                 if right.key == key {
-                    self.right = None;
+                    return self.right.take();
                 }
                 else {
-                    right.delete_tree(key);
+                    return right.remove_tree(key);
                 }
             }
         }
         else if self.key > key {
             if let Some(ref mut left) = self.left {
                 if left.key == key {
-                    self.left = None;
+                    return self.left.take();
                 }
                 else {
-                    left.delete_tree(key);
+                    return left.remove_tree(key);
                 }
             }
         }
+        None
     }
 
-    // 删除以key为根节点的树枝, 并返回切掉的树枝
-    // 无法直接删除根节点
-    pub fn remove_tree(&mut self, key: K) -> Link<K, V> {
-        if self.key < key {
-            if let Some(ref mut right) = self.right {
-                if right.key == key {
-                    return self.right.take();
+    // 删除以link为根的树中键在range范围内的节点，被删除的键值对按键的升序放入buf，节点放回pool，返回新的根节点
+    pub fn remove_range<R: RangeBounds<K>>(
+        link: Link<K, V, A>,
+        range: &R,
+        buf: &mut Vec<(K, V)>,
+        pool: &mut NodePool<Node<K, V, A>, A>,
+    ) -> Link<K, V, A> {
+        let mut node = link?;
+        if range::below(&node.key, range) {
+            node.right = Self::remove_range(node.right.take(), range, buf, pool);
+            Some(node)
+        } else if range::above(&node.key, range) {
+            node.left = Self::remove_range(node.left.take(), range, buf, pool);
+            Some(node)
+        } else {
+            // 当前节点在范围内，删除后把剩余的左右子树拼接起来
+            let left = Self::remove_range(node.left.take(), range, buf, pool);
+            let right = node.right.take();
+            let node = pool.release(node);
+            buf.push((node.key, node.value));
+            let right = Self::remove_range(right, range, buf, pool);
+            match left {
+                None => right,
+                Some(mut left) => {
+                    left.attach_max(right);
+                    Some(left)
                 }
-                else {
-                    return right.remove_tree(key);
+            }
+        }
+    }
+
+    // 只保留以link为根的树中键在range范围内的节点，范围外的节点连同整棵子树一起放回pool
+    pub fn retain_range<R: RangeBounds<K>>(link: &mut Link<K, V, A>, range: &R, pool: &mut NodePool<Node<K, V, A>, A>) {
+        match link {
+            None => {}
+            Some(node) if range::below(&node.key, range) => {
+                // 当前节点及其左子树都低于下界
+                let right = node.right.take();
+                Self::release_in_order(mem::replace(link, right), pool, &mut |_, _| ());
+                Self::retain_range(link, range, pool);
+            }
+            Some(node) if range::above(&node.key, range) => {
+                // 当前节点及其右子树都高于上界
+                let left = node.left.take();
+                Self::release_in_order(mem::replace(link, left), pool, &mut |_, _| ());
+                Self::retain_range(link, range, pool);
+            }
+            Some(node) => {
+                Self::retain_range(&mut node.left, range, pool);
+                Self::retain_range(&mut node.right, range, pool);
+            }
+        }
+    }
+
+    // 按键的升序把以link为根的树中的节点逐个放回pool，取出的键值对依次交给f
+    pub fn release_in_order<F: FnMut(K, V)>(link: Link<K, V, A>, pool: &mut NodePool<Node<K, V, A>, A>, f: &mut F) {
+        if let Some(mut node) = link {
+            Self::release_in_order(node.left.take(), pool, f);
+            let right = node.right.take();
+            let node = pool.release(node);
+            f(node.key, node.value);
+            Self::release_in_order(right, pool, f);
+        }
+    }
+
+    // 按键的升序用f转换树中的每个值，保持树的结构不变，新节点从pool中分配
+    pub fn map_values<U, F: FnMut(&K, V) -> U>(link: Link<K, V, A>, f: &mut F, pool: &mut NodePool<Node<K, U, A>, A>) -> Link<K, U, A> {
+        let node = *link?;
+        let left = Self::map_values(node.left, f, pool);
+        let value = f(&node.key, node.value);
+        let right = Self::map_values(node.right, f, pool);
+        let mut mapped = pool.alloc(Node::new(node.key, value));
+        mapped.left = left;
+        mapped.right = right;
+        Some(mapped)
+    }
+
+    // 按键的升序用f转换树中的每个键，f必须严格单调递增，因此可以保持树的结构不变，新节点从pool中分配
+    // prev记录上一个转换后的键，仅在debug构建中用于检查单调性
+    pub fn map_keys<J: PartialOrd + Clone, F: FnMut(K) -> J>(
+        link: Link<K, V, A>,
+        f: &mut F,
+        prev: &mut Option<J>,
+        pool: &mut NodePool<Node<J, V, A>, A>,
+    ) -> Link<J, V, A> {
+        let node = *link?;
+        let left = Self::map_keys(node.left, f, prev, pool);
+        let key = f(node.key);
+        if cfg!(debug_assertions) {
+            debug_assert!(
+                prev.as_ref().is_none_or(|p| *p < key),
+                "map_keys requires a strictly increasing key mapping"
+            );
+            *prev = Some(key.clone());
+        }
+        let right = Self::map_keys(node.right, f, prev, pool);
+        let mut mapped = pool.alloc(Node::new(key, node.value));
+        mapped.left = left;
+        mapped.right = right;
+        Some(mapped)
+    }
+
+    // 按键的升序取出树中全部的键值对，放入buf
+    pub fn drain_in_order(link: Link<K, V, A>, buf: &mut Vec<(K, V)>) {
+        if let Some(node) = link {
+            let node = *node;
+            Self::drain_in_order(node.left, buf);
+            buf.push((node.key, node.value));
+            Self::drain_in_order(node.right, buf);
+        }
+    }
+
+    // 按中序访问全部键值对，不使用栈、队列，也不分配内存
+    // 下降时把走过的链接反转指向父节点，沿反转的链接回到父节点时再恢复，比较键的大小即可知道是从哪一侧回来的
+    pub fn for_each_in_place<F: FnMut(&K, &V)>(root: &mut Link<K, V, A>, f: &mut F) {
+        let cur = root.take();
+        let mut walk = InPlaceWalk { root, cur, up: None };
+        walk.run(f);
+    }
+
+    // 返回每个节点所在的列和键值对，按列从左到右排列，同一列中按从上到下、从左到右排列
+    // 根节点在第0列，左孩子在父节点左边一列，右孩子在右边一列
+    pub fn columns(root: &Link<K, V, A>) -> Vec<(isize, &K, &V)> {
+        let mut buf = Vec::new();
+        Self::collect_columns(root, 0, 0, &mut buf);
+        // 前序遍历中同一深度的节点已经是从左到右的，稳定排序不会打乱
+        buf.sort_by_key(|&(col, depth, _, _)| (col, depth));
+        buf.into_iter().map(|(col, _, k, v)| (col, k, v)).collect()
+    }
+
+    // 按前序收集每个节点的列、深度和键值对
+    fn collect_columns<'a>(root: &'a Link<K, V, A>, col: isize, depth: usize, buf: &mut Vec<(isize, usize, &'a K, &'a V)>) {
+        if let Some(node) = root {
+            buf.push((col, depth, &node.key, &node.value));
+            Self::collect_columns(&node.left, col - 1, depth + 1, buf);
+            Self::collect_columns(&node.right, col + 1, depth + 1, buf);
+        }
+    }
+
+    // 后序计算以root为根的树的高度（节点数），同时用经过每个节点的最长路径的边数更新best
+    pub fn diameter(root: &Link<K, V, A>, best: &mut usize) -> usize {
+        match root {
+            None => 0,
+            Some(node) => {
+                let left = Self::diameter(&node.left, best);
+                let right = Self::diameter(&node.right, best);
+                *best = (*best).max(left + right);
+                1 + left.max(right)
+            }
+        }
+    }
+
+    // 判断两棵树的形状和每个位置上的键值对是否都相同
+    pub fn same_structure(a: &Link<K, V, A>, b: &Link<K, V, A>) -> bool
+    where
+        V: PartialEq,
+    {
+        match (a, b) {
+            (None, None) => true,
+            (Some(x), Some(y)) => {
+                x.key == y.key
+                    && x.value == y.value
+                    && Self::same_structure(&x.left, &y.left)
+                    && Self::same_structure(&x.right, &y.right)
+            }
+            _ => false,
+        }
+    }
+
+    // 判断树的形状是否关于根节点左右对称，只比较形状，不比较键值对
+    pub fn is_symmetric(root: &Link<K, V, A>) -> bool {
+        root.as_ref().is_none_or(|node| Self::is_mirror(&node.left, &node.right))
+    }
+
+    // 判断两棵树的形状是否互为镜像
+    fn is_mirror(a: &Link<K, V, A>, b: &Link<K, V, A>) -> bool {
+        match (a, b) {
+            (None, None) => true,
+            (Some(x), Some(y)) => Self::is_mirror(&x.left, &y.right) && Self::is_mirror(&x.right, &y.left),
+            _ => false,
+        }
+    }
+
+    // 消耗以link为根的树，交换每个节点的左右子树，键用Reverse包装，得到的仍是一棵合法的查找树，新节点从pool中分配
+    pub fn mirror(link: Link<K, V, A>, pool: &mut NodePool<Node<Reverse<K>, V, A>, A>) -> Link<Reverse<K>, V, A> {
+        link.map(|node| {
+            let Node { key, value, left, right, .. } = *node;
+            let mut mirrored = pool.alloc(Node::new(Reverse(key), value));
+            mirrored.left = Self::mirror(right, pool);
+            mirrored.right = Self::mirror(left, pool);
+            mirrored
+        })
+    }
+
+    // 按从根到下的顺序收集查找key时经过的键值对，key不存在时到应该插入的位置为止
+    pub fn path_to<'a>(root: &'a Link<K, V, A>, key: &K, buf: &mut VecDeque<(&'a K, &'a V)>) {
+        if let Some(node) = root {
+            buf.push_back((&node.key, &node.value));
+            if *key < node.key {
+                Self::path_to(&node.left, key, buf);
+            } else if *key > node.key {
+                Self::path_to(&node.right, key, buf);
+            }
+        }
+    }
+
+    // 按前序把键写入buf，空链接写为#，记号之间以空格分隔
+    pub fn encode(root: &Link<K, V, A>, buf: &mut String)
+    where
+        K: fmt::Display,
+    {
+        if !buf.is_empty() {
+            buf.push(' ');
+        }
+        match root {
+            None => buf.push('#'),
+            Some(node) => {
+                buf.push_str(&node.key.to_string());
+                Self::encode(&node.left, buf);
+                Self::encode(&node.right, buf);
+            }
+        }
+    }
+
+    // 对link处的节点左旋，右孩子成为该位置的新根，调用者需保证右孩子存在
+    fn rotate_left(link: &mut Link<K, V, A>) {
+        if let Some(mut node) = link.take() {
+            match node.right.take() {
+                Some(mut right) => {
+                    metrics::rotate();
+                    node.right = right.left.take();
+                    right.left = Some(node);
+                    *link = Some(right);
                 }
+                None => *link = Some(node),
             }
         }
-        else if self.key > key {
-            if let Some(ref mut left) = self.left {
-                if left.key == key {
-                    return self.left.take();
+    }
+
+    // 对link处的节点右旋，左孩子成为该位置的新根，调用者需保证左孩子存在
+    fn rotate_right(link: &mut Link<K, V, A>) {
+        if let Some(mut node) = link.take() {
+            match node.left.take() {
+                Some(mut left) => {
+                    metrics::rotate();
+                    node.left = left.right.take();
+                    left.right = Some(node);
+                    *link = Some(left);
                 }
-                else {
-                    return left.remove_tree(key);
+                None => *link = Some(node),
+            }
+        }
+    }
+
+    // 用伸展操作把键为key的节点旋转到link处，路径上的节点按一字形或之字形两两旋转；
+    // 返回是否找到key，找不到时树保持不变
+    pub fn splay(link: &mut Link<K, V, A>, key: &K) -> bool {
+        let node = match link.as_mut() {
+            Some(node) => node,
+            None => return false,
+        };
+        if *key < node.key {
+            let child = match node.left.as_mut() {
+                Some(child) => child,
+                None => return false,
+            };
+            if *key < child.key {
+                if !Self::splay(&mut child.left, key) {
+                    return false;
+                }
+                Self::rotate_right(link);
+            } else if *key > child.key {
+                if !Self::splay(&mut child.right, key) {
+                    return false;
+                }
+                Self::rotate_left(&mut node.left);
+            }
+            Self::rotate_right(link);
+        } else if *key > node.key {
+            let child = match node.right.as_mut() {
+                Some(child) => child,
+                None => return false,
+            };
+            if *key > child.key {
+                if !Self::splay(&mut child.right, key) {
+                    return false;
+                }
+                Self::rotate_left(link);
+            } else if *key < child.key {
+                if !Self::splay(&mut child.left, key) {
+                    return false;
                 }
+                Self::rotate_right(&mut node.right);
             }
+            Self::rotate_left(link);
         }
-        None
+        true
+    }
+
+    // 不断右旋把以link为根的树原地展开成只有右孩子的有序链，不分配内存
+    pub fn flatten(link: &mut Link<K, V, A>) {
+        while link.as_ref().is_some_and(|node| node.left.is_some()) {
+            Self::rotate_right(link);
+        }
+        if let Some(node) = link {
+            Self::flatten(&mut node.right);
+        }
+    }
+
+    // 按键的升序对每个键值对调用f，f可以修改值
+    pub fn for_each_mut<F: FnMut(&K, &mut V)>(root: &mut Link<K, V, A>, f: &mut F) {
+        if let Some(node) = root {
+            Self::for_each_mut(&mut node.left, f);
+            f(&node.key, &mut node.value);
+            Self::for_each_mut(&mut node.right, f);
+        }
+    }
+
+    // 按中序把名次在[start, end)内的键值对放入queue，rank记录已经访问过的节点个数，到达end后不再继续访问
+    pub fn slice<'a>(
+        link: &'a Link<K, V, A>,
+        start: usize,
+        end: usize,
+        rank: &mut usize,
+        queue: &mut VecDeque<(&'a K, &'a V)>,
+    ) {
+        if let Some(node) = link {
+            if *rank >= end {
+                return;
+            }
+            Self::slice(&node.left, start, end, rank, queue);
+            if *rank >= end {
+                return;
+            }
+            if *rank >= start {
+                queue.push_back((&node.key, &node.value));
+            }
+            *rank += 1;
+            Self::slice(&node.right, start, end, rank, queue);
+        }
+    }
+
+    // 按中序把以link为根的树中每个节点的键和平衡因子(左子树高度 - 右子树高度)放入out，返回树的高度，空树高度为0
+    pub fn balance_factors<'a>(link: &'a Link<K, V, A>, out: &mut Vec<(&'a K, isize)>) -> usize {
+        match link {
+            None => 0,
+            Some(node) => {
+                let left = Self::balance_factors(&node.left, out);
+                out.push((&node.key, 0));
+                let index = out.len() - 1;
+                let right = Self::balance_factors(&node.right, out);
+                out[index].1 = left as isize - right as isize;
+                1 + left.max(right)
+            }
+        }
+    }
+
+    // 按中序从最小的节点开始，把满足f的节点依次摘下放入buf，遇到第一个不满足f的节点就停止；
+    // 返回是否整棵树都被摘下，只有这时调用者才需要继续检查更大的节点
+    pub fn pop_while<F: FnMut(&K, &V) -> bool>(link: &mut Link<K, V, A>, f: &mut F, buf: &mut Vec<NodeBox<K, V, A>>) -> bool {
+        loop {
+            let node = match link.as_mut() {
+                Some(node) => node,
+                None => return true,
+            };
+            if !Self::pop_while(&mut node.left, f, buf) || !f(&node.key, &node.value) {
+                return false;
+            }
+            let mut node = link.take().unwrap();
+            *link = node.right.take();
+            buf.push(node);
+        }
+    }
+
+    // 返回以root为根的树中节点的个数
+    pub fn size(root: &Link<K, V, A>) -> usize {
+        match root {
+            None => 0,
+            Some(node) => 1 + Self::size(&node.left) + Self::size(&node.right),
+        }
+    }
+
+    // 返回以当前节点为根的树中节点的个数
+    pub fn subtree_size(&self) -> usize {
+        1 + Self::size(&self.left) + Self::size(&self.right)
+    }
+
+    // 统计以root为根的树中节点的个数，数到limit就停止，返回min(个数, limit)
+    pub fn size_capped(root: &Link<K, V, A>, limit: usize) -> usize {
+        match root {
+            Some(node) if limit > 0 => {
+                let left = Self::size_capped(&node.left, limit - 1);
+                1 + left + Self::size_capped(&node.right, limit - 1 - left)
+            }
+            _ => 0,
+        }
+    }
+
+    // 随机化插入：在大小为s的子树处以1/(s+1)的概率把新节点插为该子树的根，否则继续向下，
+    // 调用者需保证新节点没有子树，且它的键不在树中
+    pub fn insert_randomized(link: &mut Link<K, V, A>, mut new: NodeBox<K, V, A>) {
+        let limit = random::root_insert_limit();
+        if Self::size_capped(link, limit) < limit {
+            let (left, right) = Self::split_at(link.take(), &new.key);
+            new.left = left;
+            new.right = right;
+            *link = Some(new);
+        } else if let Some(node) = link {
+            if new.key < node.key {
+                Self::insert_randomized(&mut node.left, new);
+            } else {
+                Self::insert_randomized(&mut node.right, new);
+            }
+        }
+    }
+
+    // 将不含key的树分成键都小于key和键都大于key的两棵树
+    fn split_at(link: Link<K, V, A>, key: &K) -> (Link<K, V, A>, Link<K, V, A>) {
+        match link {
+            None => (None, None),
+            Some(mut node) => {
+                if node.key < *key {
+                    let (left, right) = Self::split_at(node.right.take(), key);
+                    node.right = left;
+                    (Some(node), right)
+                } else {
+                    let (left, right) = Self::split_at(node.left.take(), key);
+                    node.left = right;
+                    (left, Some(node))
+                }
+            }
+        }
+    }
+
+    // 前序检查每个节点，删除键值对满足f的节点为根的树枝，节点放回pool，返回删除的节点个数
+    pub fn prune<F: FnMut(&K, &V) -> bool>(root: &mut Link<K, V, A>, f: &mut F, pool: &mut NodePool<Node<K, V, A>, A>) -> usize {
+        match root {
+            None => 0,
+            Some(node) if f(&node.key, &node.value) => {
+                let mut removed = 0;
+                Self::release_in_order(root.take(), pool, &mut |_, _| removed += 1);
+                removed
+            }
+            Some(node) => Self::prune(&mut node.left, f, pool) + Self::prune(&mut node.right, f, pool),
+        }
+    }
+
+    // 按order遍历以root为根的树，f返回Break时立即停止，不生成中间结果
+    pub fn walk<'a, B, C, F>(root: &'a Link<K, V, A>, order: Order, f: &mut F) -> ControlFlow<B>
+    where
+        C: Into<Visit>,
+        F: FnMut(&'a K, &'a V) -> ControlFlow<B, C>,
+    {
+        if order == Order::LevelOrder {
+            let mut queue: VecDeque<&NodeBox<K, V, A>> = root.iter().collect();
+            while let Some(node) = queue.pop_front() {
+                if f(&node.key, &node.value)?.into() == Visit::Children {
+                    queue.extend(node.left.iter());
+                    queue.extend(node.right.iter());
+                }
+            }
+            return ControlFlow::Continue(());
+        }
+        if order == Order::ZigZag {
+            let mut level: Vec<&NodeBox<K, V, A>> = root.iter().collect();
+            let mut left_to_right = true;
+            while !level.is_empty() {
+                // level始终按从左到右保存，从右到左访问时倒着取，并倒着收集下一层
+                let mut next = Vec::new();
+                for i in 0..level.len() {
+                    let node = if left_to_right { level[i] } else { level[level.len() - 1 - i] };
+                    if f(&node.key, &node.value)?.into() == Visit::Children {
+                        if left_to_right {
+                            next.extend(node.left.iter());
+                            next.extend(node.right.iter());
+                        } else {
+                            next.extend(node.right.iter());
+                            next.extend(node.left.iter());
+                        }
+                    }
+                }
+                if !left_to_right {
+                    next.reverse();
+                }
+                level = next;
+                left_to_right = !left_to_right;
+            }
+            return ControlFlow::Continue(());
+        }
+        let node = match root {
+            None => return ControlFlow::Continue(()),
+            Some(node) => node,
+        };
+        match order {
+            Order::PreOrder => {
+                if f(&node.key, &node.value)?.into() == Visit::Children {
+                    Self::walk(&node.left, order, f)?;
+                    Self::walk(&node.right, order, f)?;
+                }
+            }
+            Order::InOrder => {
+                Self::walk(&node.left, order, f)?;
+                if f(&node.key, &node.value)?.into() == Visit::Children {
+                    Self::walk(&node.right, order, f)?;
+                }
+            }
+            _ => {
+                Self::walk(&node.left, order, f)?;
+                Self::walk(&node.right, order, f)?;
+                f(&node.key, &node.value)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    // 按键的升序对range范围内的键值对累积计算，跳过不在范围内的子树
+    pub fn fold_range<'a, B, R, F>(root: &'a Link<K, V, A>, range: &R, mut acc: B, f: &mut F) -> B
+    where
+        R: RangeBounds<K>,
+        F: FnMut(B, &'a K, &'a V) -> B,
+    {
+        if let Some(node) = root {
+            let below = range::below(&node.key, range);
+            let above = range::above(&node.key, range);
+            if !below {
+                acc = Self::fold_range(&node.left, range, acc, f);
+            }
+            if !below && !above {
+                acc = f(acc, &node.key, &node.value);
+            }
+            if !above {
+                acc = Self::fold_range(&node.right, range, acc, f);
+            }
+        }
+        acc
     }
 
     // 前序遍历
-    pub fn prev_order(root: &Link<K, V>, buf: &mut Vec<K>) {
+    pub fn prev_order(root: &Link<K, V, A>, buf: &mut Vec<K>) {
         if let Some(node) = root {
             buf.push(node.key.clone());
             Self::prev_order(&node.left, buf);
@@ -221,8 +1062,26 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
         }
     }
 
+    // 带深度的前序遍历
+    pub fn depth_order<'a>(root: &'a Link<K, V, A>, depth: usize, buf: &mut VecDeque<(usize, &'a K, &'a V)>) {
+        if let Some(node) = root {
+            buf.push_back((depth, &node.key, &node.value));
+            Self::depth_order(&node.left, depth + 1, buf);
+            Self::depth_order(&node.right, depth + 1, buf);
+        }
+    }
+
+    // 前序遍历，同时记录每个节点的父节点的键
+    pub fn parent_order<'a>(root: &'a Link<K, V, A>, parent: Option<&'a K>, buf: &mut VecDeque<(Option<&'a K>, &'a K, &'a V)>) {
+        if let Some(node) = root {
+            buf.push_back((parent, &node.key, &node.value));
+            Self::parent_order(&node.left, Some(&node.key), buf);
+            Self::parent_order(&node.right, Some(&node.key), buf);
+        }
+    }
+
     // 中序遍历
-    pub fn in_order(root: &Link<K, V>, buf: &mut Vec<K>) {
+    pub fn in_order(root: &Link<K, V, A>, buf: &mut Vec<K>) {
         if let Some(node) = root {
             Self::in_order(&node.left, buf);
             buf.push(node.key.clone());
@@ -230,8 +1089,19 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
         }
     }
 
+    // 中序遍历以当前节点为根的树，收集键值对
+    pub fn in_order_pairs<'a>(&'a self, buf: &mut VecDeque<(&'a K, &'a V)>) {
+        if let Some(ref left) = self.left {
+            left.in_order_pairs(buf);
+        }
+        buf.push_back((&self.key, &self.value));
+        if let Some(ref right) = self.right {
+            right.in_order_pairs(buf);
+        }
+    }
+
     // 后序遍历
-    pub fn post_order(root: &Link<K, V>, buf: &mut Vec<K>) {
+    pub fn post_order(root: &Link<K, V, A>, buf: &mut Vec<K>) {
         if let Some(node) = root {
             Self::post_order(&node.left, buf);
             Self::post_order(&node.right, buf);
@@ -239,8 +1109,20 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
         }
     }
 
+    // 按层收集键值对，depth为当前节点所在的层
+    pub fn levels<'a>(root: &'a Link<K, V, A>, depth: usize, buf: &mut Vec<Vec<(&'a K, &'a V)>>) {
+        if let Some(node) = root {
+            if buf.len() == depth {
+                buf.push(Vec::new());
+            }
+            buf[depth].push((&node.key, &node.value));
+            Self::levels(&node.left, depth + 1, buf);
+            Self::levels(&node.right, depth + 1, buf);
+        }
+    }
+
     // 层序遍历
-    pub fn level_order(root: &Link<K, V>, buf: &mut Vec<K>) {
+    pub fn level_order(root: &Link<K, V, A>, buf: &mut Vec<K>) {
         let mut queue = VecDeque::new();
         if let Some(node) = root {
             queue.push_back(node);
@@ -259,3 +1141,208 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
     }
 }
 
+impl<K: PartialOrd + Clone, V> Node<K, V> {
+    // 并行地由按键严格升序排列的键值对构建平衡的树，取走items中的元素，左右子树交给rayon在不同线程上构建
+    #[cfg(feature = "rayon")]
+    pub fn par_build_sorted(items: &mut [Option<(K, V)>]) -> Link<K, V>
+    where
+        K: Send,
+        V: Send,
+    {
+        // 数据量较小时并行的开销超过收益，直接顺序构建
+        if items.len() <= PAR_BUILD_THRESHOLD {
+            let n = items.len();
+            return Self::build_sorted(&mut items.iter_mut().filter_map(Option::take), n, &mut NodePool::new_in(Global));
+        }
+        // 与顺序构建取相同的根节点，保证树的形状一致，切分切片不需要移动数据
+        let (left, rest) = items.split_at_mut(items.len() / 2);
+        let (mid, right) = rest.split_first_mut()?;
+        let (key, value) = mid.take()?;
+        let (left, right) = rayon::join(
+            || Self::par_build_sorted(left),
+            || Self::par_build_sorted(right),
+        );
+        let mut node = Node::new(key, value);
+        node.left = left;
+        node.right = right;
+        Some(Box::new(node))
+    }
+
+    // 由键的前序序列还原子树，从items的开头取出键在(low, high)范围内的部分
+    pub fn from_preorder<I: Iterator<Item = (K, V)>>(items: &mut Peekable<I>, low: Option<&K>, high: Option<&K>) -> Link<K, V> {
+        let fits = items.peek().is_some_and(|(key, _)| {
+            low.is_none_or(|low| *key > *low) && high.is_none_or(|high| *key < *high)
+        });
+        if !fits {
+            return None;
+        }
+        let (key, value) = items.next()?;
+        let left = Self::from_preorder(items, low, Some(&key));
+        let right = Self::from_preorder(items, Some(&key), high);
+        let mut node = Node::new(key, value);
+        node.left = left;
+        node.right = right;
+        Some(Box::new(node))
+    }
+
+    // 由层序序列还原树，不属于层序序列的部分留在items中
+    pub fn from_level_order<I: Iterator<Item = (K, V)>>(items: &mut Peekable<I>) -> Link<K, V> {
+        let mut root = None;
+        // 队列中按层序保存等待填充的链接，以及该位置上的键必须满足的(low, high)范围，下一个键不在范围内时该位置为空
+        let mut slots = VecDeque::new();
+        slots.push_back((&mut root, None, None));
+        while let Some((slot, low, high)) = slots.pop_front() {
+            let fits = items.peek().is_some_and(|(key, _)| {
+                low.as_ref().is_none_or(|low| *key > *low) && high.as_ref().is_none_or(|high| *key < *high)
+            });
+            if !fits {
+                continue;
+            }
+            let (key, value) = match items.next() {
+                Some(item) => item,
+                None => break,
+            };
+            let node = slot.insert(Box::new(Node::new(key.clone(), value)));
+            let Node { left, right, .. } = &mut **node;
+            slots.push_back((left, low, Some(key.clone())));
+            slots.push_back((right, Some(key), high));
+        }
+        root
+    }
+
+    // 从前序记号中还原一棵树，值取默认值；嵌套的深度由输入决定，因此用显式的栈代替递归，
+    // 任意深的输入都不会耗尽调用栈
+    pub fn decode<'s, I>(tokens: &mut I) -> Result<Link<K, V>, DecodeError>
+    where
+        I: Iterator<Item = &'s str>,
+        K: FromStr,
+        V: Default,
+    {
+        let mut stack: Vec<DecodeFrame<K, V>> = Vec::new();
+        loop {
+            // 下一个记号所在位置的键的范围，由栈顶节点和它正在等待的孩子决定
+            let (low, high) = match stack.last() {
+                None => (None, None),
+                Some(frame) if frame.left.is_none() => (frame.low, Some(stack.len() - 1)),
+                Some(frame) => (Some(stack.len() - 1), frame.high),
+            };
+            let token = tokens.next().ok_or(DecodeError::UnexpectedEnd)?;
+            if token != "#" {
+                let key: K = token.parse().map_err(|_| DecodeError::InvalidKey(token.to_string()))?;
+                if low.is_some_and(|low| key <= stack[low].key) || high.is_some_and(|high| key >= stack[high].key) {
+                    return Err(DecodeError::OutOfOrder(token.to_string()));
+                }
+                stack.push(DecodeFrame { key, low, high, left: None });
+                continue;
+            }
+            // 得到一棵完整的子树，接到等待它的节点上；右子树完整时该节点也完整了，继续向上
+            let mut done = None;
+            loop {
+                match stack.last_mut() {
+                    None => return Ok(done),
+                    Some(frame) if frame.left.is_none() => {
+                        frame.left = Some(done);
+                        break;
+                    }
+                    Some(_) => {
+                        let frame = stack.pop().unwrap();
+                        let mut node = Node::new(frame.key, V::default());
+                        node.left = frame.left.unwrap();
+                        node.right = done;
+                        done = Some(Box::new(node));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// decode中还未完整的节点：low和high是限定它的键范围的祖先在栈中的下标，
+// left在左子树完整之前为None，之后保存左子树并等待右子树
+struct DecodeFrame<K: PartialOrd, V> {
+    key: K,
+    low: Option<usize>,
+    high: Option<usize>,
+    left: Option<Link<K, V>>,
+}
+
+// for_each_in_place遍历中的状态：cur是当前节点，up是由反转的链接串起来的祖先节点
+// 离开作用域时沿up恢复全部反转的链接并把树放回root，f发生panic时栈展开也会经过这里，树不会随之被释放
+struct InPlaceWalk<'a, K: PartialOrd, V, A: Allocator + Clone> {
+    root: &'a mut Link<K, V, A>,
+    cur: Link<K, V, A>,
+    up: Link<K, V, A>,
+}
+
+impl<K: PartialOrd, V, A: Allocator + Clone> InPlaceWalk<'_, K, V, A> {
+    fn run<F: FnMut(&K, &V)>(&mut self, f: &mut F) {
+        'down: loop {
+            while let Some(left) = self.cur.as_mut().and_then(|cur| cur.left.take()) {
+                if let Some(cur) = self.cur.as_mut() {
+                    cur.left = self.up.take();
+                }
+                self.up = self.cur.replace(left);
+            }
+            loop {
+                // cur的左子树已经访问完毕
+                let cur = match self.cur.as_mut() {
+                    Some(cur) => cur,
+                    None => return,
+                };
+                f(&cur.key, &cur.value);
+                if let Some(right) = cur.right.take() {
+                    cur.right = self.up.take();
+                    self.up = self.cur.replace(right);
+                    continue 'down;
+                }
+                // cur的右子树也访问完毕，向上回溯直到从某个节点的左侧回来
+                loop {
+                    match self.up.take() {
+                        Some(parent) => {
+                            if self.ascend(parent) {
+                                break;
+                            }
+                        }
+                        None => return,
+                    }
+                }
+            }
+        }
+    }
+
+    // 沿反转的链接回到父节点parent，恢复parent指向cur的链接，返回是否从parent的左侧回来
+    fn ascend(&mut self, mut parent: NodeBox<K, V, A>) -> bool {
+        let from_left = self.cur.as_ref().is_some_and(|cur| cur.key < parent.key);
+        let link = if from_left { &mut parent.left } else { &mut parent.right };
+        self.up = mem::replace(link, self.cur.take());
+        self.cur = Some(parent);
+        from_left
+    }
+}
+
+impl<K: PartialOrd, V, A: Allocator + Clone> Drop for InPlaceWalk<'_, K, V, A> {
+    fn drop(&mut self) {
+        while let Some(parent) = self.up.take() {
+            self.ascend(parent);
+        }
+        *self.root = self.cur.take();
+    }
+}
+
+// resolve_value调用f期间，节点的键值已经取出，children暂存它的左右子树；
+// f发生panic时栈展开经过这里，把两棵子树合并后接回link，只丢失正在合并的键值对
+struct ValueHole<'a, K: PartialOrd + Clone, V, A: Allocator + Clone> {
+    link: &'a mut Link<K, V, A>,
+    children: Option<Children<K, V, A>>,
+}
+
+// 节点的左右子树
+type Children<K, V, A> = (Link<K, V, A>, Link<K, V, A>);
+
+impl<K: PartialOrd + Clone, V, A: Allocator + Clone> Drop for ValueHole<'_, K, V, A> {
+    fn drop(&mut self) {
+        if let Some((left, right)) = self.children.take() {
+            *self.link = Node::merge_children(left, right);
+        }
+    }
+}
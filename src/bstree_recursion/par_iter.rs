@@ -0,0 +1,90 @@
+use std::iter;
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::ParallelIterator;
+use crate::allocator::{Allocator, Global};
+use crate::bstree_recursion::node::Node;
+
+/// 树的并行迭代器，按子树划分任务，由`BSTree::par_iter`创建
+pub struct ParIter<'a, K, V, A: Allocator = Global> {
+    root: Option<&'a Node<K, V, A>>,
+}
+
+impl<'a, K, V, A: Allocator + Clone> ParIter<'a, K, V, A> {
+    pub(crate) fn new(root: Option<&'a Node<K, V, A>>) -> Self {
+        ParIter { root }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone + Sync, V: Sync, A: Allocator + Clone + Sync> ParallelIterator for ParIter<'a, K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        let parts = self.root.map(Part::Subtree).into_iter().collect();
+        bridge_unindexed(SubtreeProducer { parts }, consumer)
+    }
+}
+
+// 待处理的一段数据，要么是单个键值对，要么是一棵完整的子树
+enum Part<'a, K, V, A: Allocator> {
+    Entry(&'a K, &'a V),
+    Subtree(&'a Node<K, V, A>),
+}
+
+// 按中序排列的若干段数据，拆分时从中间一分为二
+struct SubtreeProducer<'a, K, V, A: Allocator> {
+    parts: Vec<Part<'a, K, V, A>>,
+}
+
+impl<'a, K: PartialOrd + Clone + Sync, V: Sync, A: Allocator + Clone + Sync> UnindexedProducer for SubtreeProducer<'a, K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        // 只剩一棵子树时，将其拆成左子树、根节点、右子树三段
+        if let [Part::Subtree(node)] = self.parts[..] {
+            self.parts = node
+                .left()
+                .map(Part::Subtree)
+                .into_iter()
+                .chain(iter::once(Part::Entry(&node.key, &node.value)))
+                .chain(node.right().map(Part::Subtree))
+                .collect();
+        }
+        if self.parts.len() < 2 {
+            return (self, None);
+        }
+        let rest = self.parts.split_off(self.parts.len() / 2);
+        (self, Some(SubtreeProducer { parts: rest }))
+    }
+
+    fn fold_with<F: Folder<Self::Item>>(self, mut folder: F) -> F {
+        for part in self.parts {
+            folder = match part {
+                Part::Entry(key, value) => folder.consume((key, value)),
+                Part::Subtree(node) => fold_subtree(node, folder),
+            };
+            if folder.full() {
+                break;
+            }
+        }
+        folder
+    }
+}
+
+// 按中序将子树中的键值对交给folder
+fn fold_subtree<'a, K, V, A: Allocator + Clone, F>(node: &'a Node<K, V, A>, mut folder: F) -> F
+where
+    K: PartialOrd + Clone,
+    F: Folder<(&'a K, &'a V)>,
+{
+    if let Some(left) = node.left() {
+        folder = fold_subtree(left, folder);
+    }
+    if folder.full() {
+        return folder;
+    }
+    folder = folder.consume((&node.key, &node.value));
+    match node.right() {
+        Some(right) if !folder.full() => fold_subtree(right, folder),
+        _ => folder,
+    }
+}
@@ -0,0 +1,81 @@
+use crate::allocator::{Allocator, Global};
+use crate::bstree_recursion::node::Node;
+
+/// 可以跳过子树的前序遍历迭代器，由`BSTree::walker`创建
+///
+/// 每次调用`next`产生一个键值对后，可以调用`skip_subtree`跳过刚产生的节点的全部子孙节点，
+/// 用法类似于遍历目录时跳过某个子目录。
+pub struct Walker<'a, K, V, A: Allocator = Global> {
+    stack: Vec<&'a Node<K, V, A>>,
+    // 最近一次产生的节点，它的子节点在下一次调用next时才入栈
+    last: Option<&'a Node<K, V, A>>,
+}
+
+impl<'a, K: PartialOrd + Clone, V, A: Allocator + Clone> Walker<'a, K, V, A> {
+    pub(crate) fn new(root: Option<&'a Node<K, V, A>>) -> Self {
+        Walker {
+            stack: root.into_iter().collect(),
+            last: None,
+        }
+    }
+
+    /// 跳过最近一次产生的节点的子树，之后不再产生其中的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::RecursiveBSTree as BSTree;
+    /// let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+    /// let mut walker = tree.walker();
+    /// assert_eq!(walker.next(), Some((&4, &40)));
+    /// assert_eq!(walker.next(), Some((&2, &20)));
+    /// walker.skip_subtree();
+    /// let res: Vec<&i32> = walker.map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&6, &5, &7]);
+    /// ```
+    pub fn skip_subtree(&mut self) {
+        self.last = None;
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V, A: Allocator + Clone> Iterator for Walker<'a, K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(last) = self.last.take() {
+            self.stack.extend(last.right());
+            self.stack.extend(last.left());
+        }
+        let node = self.stack.pop()?;
+        self.last = Some(node);
+        Some((&node.key, &node.value))
+    }
+}
+
+// 按中序产生键值对的迭代器，栈中保存还没有访问的祖先节点，每产生一个键值对均摊只需O(1)，
+// 集合比较这类需要同时遍历两棵树的操作使用它，不必对每个键从根开始查找
+pub(crate) struct InOrder<'a, K, V, A: Allocator> {
+    stack: Vec<&'a Node<K, V, A>>,
+    current: Option<&'a Node<K, V, A>>,
+}
+
+impl<'a, K, V, A: Allocator + Clone> InOrder<'a, K, V, A> {
+    pub(crate) fn new(root: Option<&'a Node<K, V, A>>) -> Self {
+        InOrder {
+            stack: Vec::new(),
+            current: root,
+        }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V, A: Allocator + Clone> Iterator for InOrder<'a, K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current {
+            self.stack.push(node);
+            self.current = node.left();
+        }
+        let node = self.stack.pop()?;
+        self.current = node.right();
+        Some((&node.key, &node.value))
+    }
+}
@@ -0,0 +1,10 @@
+/// 两棵树之间的一处差异，由`diff`按键的升序产生
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<K, V> {
+    /// 只在新树中出现的键值对
+    Added(K, V),
+    /// 只在旧树中出现的键值对
+    Removed(K, V),
+    /// 两棵树中都存在但值不同的键：(键, 旧值, 新值)
+    Modified(K, V, V),
+}
@@ -0,0 +1,110 @@
+use std::ops::Bound;
+
+// 某个键一侧最近的键值对，不存在时为None
+pub(crate) type Neighbor<'a, K, V> = Option<(&'a K, &'a V)>;
+
+// 游标定位和移动时需要的查询，由递归实现和迭代实现的树分别提供
+pub(crate) trait Neighbors<K, V> {
+    fn get_pair(&self, key: &K) -> Option<(&K, &V)>;
+    fn successor(&self, key: &K) -> Option<(&K, &V)>;
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)>;
+    fn min_pair(&self) -> Option<(&K, &V)>;
+    fn max_pair(&self) -> Option<(&K, &V)>;
+}
+
+/// 位于两个相邻键值对之间的游标，由`lower_bound`和`upper_bound`创建
+///
+/// 游标可以向前或向后移动，每次移动只需一次O(h)的查找，适合从某个边界出发逐个访问相邻的键值对。
+/// 作为迭代器使用时按键的升序向前移动。
+pub struct Cursor<'a, K, V> {
+    tree: &'a dyn Neighbors<K, V>,
+    prev: Option<(&'a K, &'a V)>,
+    next: Option<(&'a K, &'a V)>,
+}
+
+impl<'a, K: PartialOrd + Clone, V> Cursor<'a, K, V> {
+    // 游标位于第一个在下界之内的键值对之前
+    pub(crate) fn lower_bound(tree: &'a dyn Neighbors<K, V>, bound: Bound<&K>) -> Self {
+        let next = match bound {
+            Bound::Included(key) => tree.get_pair(key).or_else(|| tree.successor(key)),
+            Bound::Excluded(key) => tree.successor(key),
+            Bound::Unbounded => tree.min_pair(),
+        };
+        let prev = match next {
+            Some((key, _)) => tree.predecessor(key),
+            None => tree.max_pair(),
+        };
+        Cursor { tree, prev, next }
+    }
+
+    // 游标位于最后一个在上界之内的键值对之后
+    pub(crate) fn upper_bound(tree: &'a dyn Neighbors<K, V>, bound: Bound<&K>) -> Self {
+        let prev = match bound {
+            Bound::Included(key) => tree.get_pair(key).or_else(|| tree.predecessor(key)),
+            Bound::Excluded(key) => tree.predecessor(key),
+            Bound::Unbounded => tree.max_pair(),
+        };
+        let next = match prev {
+            Some((key, _)) => tree.successor(key),
+            None => tree.min_pair(),
+        };
+        Cursor { tree, prev, next }
+    }
+
+    /// 查看游标之后的键值对，不移动游标
+    /// # Example
+    /// ```
+    /// use std::ops::Bound;
+    /// use an_ok_bstree::BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (3, 'c')]);
+    /// let cursor = tree.lower_bound(Bound::Included(&2));
+    /// assert_eq!(cursor.peek_next(), Some((&3, &'c')));
+    /// ```
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.next
+    }
+
+    /// 查看游标之前的键值对，不移动游标
+    /// # Example
+    /// ```
+    /// use std::ops::Bound;
+    /// use an_ok_bstree::BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (3, 'c')]);
+    /// let cursor = tree.lower_bound(Bound::Included(&2));
+    /// assert_eq!(cursor.peek_prev(), Some((&1, &'a')));
+    /// ```
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.prev
+    }
+
+    /// 向后移动游标，返回越过的键值对，已经位于最前时返回None且游标不动
+    /// # Example
+    /// ```
+    /// use std::ops::Bound;
+    /// use an_ok_bstree::BSTree;
+    /// let tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// let mut cursor = tree.upper_bound(Bound::Excluded(&3));
+    /// assert_eq!(cursor.prev(), Some((&2, &'b')));
+    /// assert_eq!(cursor.prev(), Some((&1, &'a')));
+    /// assert_eq!(cursor.prev(), None);
+    /// assert_eq!(cursor.next(), Some((&1, &'a')));
+    /// ```
+    pub fn prev(&mut self) -> Option<(&'a K, &'a V)> {
+        let current = self.prev?;
+        self.next = Some(current);
+        self.prev = self.tree.predecessor(current.0);
+        Some(current)
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for Cursor<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    // 向前移动游标，返回越过的键值对，已经位于最后时返回None且游标不动
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.prev = Some(current);
+        self.next = self.tree.successor(current.0);
+        Some(current)
+    }
+}
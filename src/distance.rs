@@ -0,0 +1,40 @@
+/// 键之间的距离，用于`nearest`等按距离查找的操作
+pub trait Distance {
+    /// 距离的类型
+    type Output: PartialOrd;
+
+    /// 返回两个键之间的距离
+    fn distance(&self, other: &Self) -> Self::Output;
+}
+
+macro_rules! impl_int_distance {
+    ($($t:ty => $out:ty),*) => {
+        $(
+            impl Distance for $t {
+                type Output = $out;
+
+                fn distance(&self, other: &Self) -> $out {
+                    self.abs_diff(*other)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_float_distance {
+    ($($t:ty),*) => {
+        $(
+            impl Distance for $t {
+                type Output = $t;
+
+                fn distance(&self, other: &Self) -> $t {
+                    (self - other).abs()
+                }
+            }
+        )*
+    };
+}
+
+impl_int_distance!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+impl_int_distance!(u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize);
+impl_float_distance!(f32, f64);
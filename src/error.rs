@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fmt;
+
+/// `try_insert`在键已经存在时返回的错误，带回未能插入的键值对
+#[derive(Debug, PartialEq)]
+pub struct OccupiedError<K, V> {
+    /// 未能插入的键
+    pub key: K,
+    /// 未能插入的值
+    pub value: V,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Display for OccupiedError<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key {:?} already exists, failed to insert value {:?}", self.key, self.value)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> Error for OccupiedError<K, V> {}
+
+/// `rekey`失败的原因，带回未能使用的新键
+#[derive(Debug, PartialEq)]
+pub enum RekeyError<K> {
+    /// 旧键不存在
+    NotFound(K),
+    /// 新键已经存在
+    Occupied(K),
+}
+
+impl<K: fmt::Debug> fmt::Display for RekeyError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RekeyError::NotFound(key) => write!(f, "old key not found, failed to rekey to {:?}", key),
+            RekeyError::Occupied(key) => write!(f, "key {:?} already exists", key),
+        }
+    }
+}
+
+impl<K: fmt::Debug> Error for RekeyError<K> {}
+
+/// 要求存在的键在树中找不到，带回该键
+#[derive(Debug, PartialEq)]
+pub struct KeyNotFound<K>(pub K);
+
+impl<K: fmt::Debug> fmt::Display for KeyNotFound<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key {:?} not found", self.0)
+    }
+}
+
+impl<K: fmt::Debug> Error for KeyNotFound<K> {}
+
+/// `get_many_mut`失败的原因，带回出问题的键
+#[derive(Debug, PartialEq)]
+pub enum GetManyMutError<K> {
+    /// 同一个键出现了多次
+    Duplicate(K),
+    /// 键在树中不存在
+    NotFound(K),
+}
+
+impl<K: fmt::Debug> fmt::Display for GetManyMutError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GetManyMutError::Duplicate(key) => write!(f, "key {:?} requested more than once", key),
+            GetManyMutError::NotFound(key) => write!(f, "key {:?} not found", key),
+        }
+    }
+}
+
+impl<K: fmt::Debug> Error for GetManyMutError<K> {}
+
+/// `decode_structure`失败的原因，带回出问题的记号
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    /// 记号无法解析为键
+    InvalidKey(String),
+    /// 键不满足查找树的顺序或与已有的键重复
+    OutOfOrder(String),
+    /// 树还不完整输入就结束了
+    UnexpectedEnd,
+    /// 树已经完整但输入还有剩余
+    TrailingInput(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidKey(token) => write!(f, "invalid key {:?}", token),
+            DecodeError::OutOfOrder(token) => write!(f, "key {:?} violates the search tree order", token),
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::TrailingInput(token) => write!(f, "unexpected trailing input starting at {:?}", token),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// `graft`失败时返回的错误，带回未能接入的子树
+pub struct GraftError<T>(pub T);
+
+impl<T> fmt::Debug for GraftError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GraftError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for GraftError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "subtree key range does not fit into a single empty link of the tree")
+    }
+}
+
+impl<T> Error for GraftError<T> {}
@@ -0,0 +1,91 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+// save_to的文件和预写日志中的键、值都写成`字节数:文本`的字段，文本原样写出，
+// 因此键和值的文本形式可以包含空格、换行等任意字符，读取时不需要转义
+
+// 把text作为一个字段追加到buf
+pub(crate) fn push_field(buf: &mut String, text: &str) {
+    buf.push_str(&text.len().to_string());
+    buf.push(':');
+    buf.push_str(text);
+}
+
+// 读取失败的原因
+pub(crate) enum Malformed {
+    // 输入在一条记录的中途结束，例如写入时崩溃
+    Truncated,
+    // 输入不符合格式
+    Invalid,
+}
+
+// 从input的开头读出一个字段，返回字段的文本和之后剩余的输入
+pub(crate) fn split_field(input: &str) -> Result<(&str, &str), Malformed> {
+    let digits = input.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == input.len() {
+        return Err(Malformed::Truncated);
+    }
+    if digits == 0 || input.as_bytes()[digits] != b':' {
+        return Err(Malformed::Invalid);
+    }
+    let len: usize = input[..digits].parse().map_err(|_| Malformed::Invalid)?;
+    let rest = &input[digits + 1..];
+    if rest.len() < len {
+        return Err(Malformed::Truncated);
+    }
+    if !rest.is_char_boundary(len) {
+        return Err(Malformed::Invalid);
+    }
+    Ok(rest.split_at(len))
+}
+
+// 去掉input开头的分隔符sep，返回剩余的输入
+pub(crate) fn split_sep(input: &str, sep: char) -> Result<&str, Malformed> {
+    match input.strip_prefix(sep) {
+        Some(rest) => Ok(rest),
+        None if input.is_empty() => Err(Malformed::Truncated),
+        None => Err(Malformed::Invalid),
+    }
+}
+
+// 把bytes按UTF-8解码；末尾不完整的字符视为写入中断而丢弃，其余位置的非法字节返回InvalidData错误
+pub(crate) fn utf8_prefix(bytes: &[u8]) -> io::Result<&str> {
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(text) => return Ok(text),
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+    };
+    std::str::from_utf8(&bytes[..valid]).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// 先把contents写入同一目录下的临时文件并落盘，再重命名为path，
+// 写入中途崩溃时path仍然是之前完整的内容，不会留下写了一半的文件
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(err);
+    }
+    // 重命名记录在目录中，目录也要落盘
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
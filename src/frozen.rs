@@ -0,0 +1,283 @@
+use std::convert::TryInto;
+use std::fs::{self, File};
+use std::io;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::binary_search_tree::BinarySearchTree;
+use crate::range;
+
+// 文件头：8字节魔数、8字节节点个数、8字节根节点编号，整数均为小端序
+const MAGIC: &[u8; 8] = b"BSTFRZ01";
+const HEADER_SIZE: usize = 24;
+// 空链接的编号
+const NIL: u64 = u64::MAX;
+
+/// 可以按固定长度存放在冻结树文件中的类型
+pub trait FixedSize: Sized {
+    /// 编码后的字节数
+    const SIZE: usize;
+
+    /// 把自身写入长度为SIZE的buf
+    fn write_bytes(&self, buf: &mut [u8]);
+
+    /// 从长度为SIZE的buf中读出
+    fn read_bytes(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_fixed_size {
+    ($($t:ty),*) => {
+        $(
+            impl FixedSize for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_bytes(&self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_bytes(buf: &[u8]) -> Self {
+                    <$t>::from_le_bytes(buf.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_size!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// 存放在内存映射文件中的只读二叉查找树
+///
+/// 文件由`write_to`生成：文件头之后按前序依次存放每个节点，每个节点是一条定长记录，
+/// 包括键、值以及左右孩子的编号，编号乘以记录长度即为孩子在文件中的偏移。
+/// `get`和`range`直接在映射的字节上沿树查找，只解码经过的节点，不需要把整棵树读入内存。
+pub struct FrozenBSTree<K, V> {
+    map: Mmap,
+    len: u64,
+    root: u64,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K: FixedSize + PartialOrd + Clone, V: FixedSize> FrozenBSTree<K, V> {
+    // 每条节点记录的长度
+    const RECORD_SIZE: usize = K::SIZE + V::SIZE + 16;
+
+    /// 把tree按冻结格式写入文件path，保持树的形状不变，两种实现的树都可以写入
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BSTree, FrozenBSTree};
+    /// let tree = BSTree::bulk_load((1..=3).map(|i| (i, i as f64 * 0.5)));
+    /// let path = std::env::temp_dir().join("an_ok_bstree_write_to.bin");
+    /// FrozenBSTree::write_to(&tree, &path).unwrap();
+    /// let frozen: FrozenBSTree<i32, f64> = FrozenBSTree::open_mmap(&path).unwrap();
+    /// assert_eq!(frozen.len(), 3);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn write_to<T: BinarySearchTree<K, V>, P: AsRef<Path>>(tree: &T, path: P) -> io::Result<()> {
+        let nodes: Vec<(&K, &V)> = tree.preorder_iter().collect();
+        // 由前序序列还原孩子编号：新节点小于栈顶时是栈顶的左孩子，
+        // 否则弹出所有小于它的节点，它是最后弹出的节点的右孩子
+        let mut children = vec![(NIL, NIL); nodes.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        for (i, (key, _)) in nodes.iter().enumerate() {
+            match stack.last() {
+                Some(&top) if *key < nodes[top].0 => children[top].0 = i as u64,
+                _ => {
+                    let mut parent = None;
+                    while let Some(&top) = stack.last() {
+                        if *key > nodes[top].0 {
+                            parent = stack.pop();
+                        } else {
+                            break;
+                        }
+                    }
+                    if let Some(parent) = parent {
+                        children[parent].1 = i as u64;
+                    }
+                }
+            }
+            stack.push(i);
+        }
+        let mut buf = vec![0; HEADER_SIZE + nodes.len() * Self::RECORD_SIZE];
+        buf[..8].copy_from_slice(MAGIC);
+        buf[8..16].copy_from_slice(&(nodes.len() as u64).to_le_bytes());
+        let root = if nodes.is_empty() { NIL } else { 0 };
+        buf[16..24].copy_from_slice(&root.to_le_bytes());
+        for (i, ((key, value), (left, right))) in nodes.iter().zip(children).enumerate() {
+            let record = &mut buf[HEADER_SIZE + i * Self::RECORD_SIZE..][..Self::RECORD_SIZE];
+            let (key_bytes, rest) = record.split_at_mut(K::SIZE);
+            let (value_bytes, links) = rest.split_at_mut(V::SIZE);
+            key.write_bytes(key_bytes);
+            value.write_bytes(value_bytes);
+            links[..8].copy_from_slice(&left.to_le_bytes());
+            links[8..].copy_from_slice(&right.to_le_bytes());
+        }
+        fs::write(path, buf)
+    }
+
+    /// 以只读方式映射`write_to`生成的文件；文件头不合法、长度不符或孩子编号越界时返回`InvalidData`错误。
+    /// 映射期间文件不能被修改或截断
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BSTree, FrozenBSTree};
+    /// let path = std::env::temp_dir().join("an_ok_bstree_open_mmap.bin");
+    /// std::fs::write(&path, b"not a tree").unwrap();
+    /// assert!(FrozenBSTree::<u32, u32>::open_mmap(&path).is_err());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn open_mmap<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: 映射是只读的，文档要求调用者在映射期间不修改文件
+        let map = unsafe { Mmap::map(&file)? };
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        if map.len() < HEADER_SIZE || &map[..8] != MAGIC {
+            return Err(invalid("not a frozen tree file"));
+        }
+        let len = u64::read_bytes(&map[8..16]);
+        let root = u64::read_bytes(&map[16..24]);
+        let expected = (len as usize)
+            .checked_mul(Self::RECORD_SIZE)
+            .and_then(|size| size.checked_add(HEADER_SIZE));
+        if expected != Some(map.len()) {
+            return Err(invalid("file size does not match the node count"));
+        }
+        let frozen = FrozenBSTree {
+            map,
+            len,
+            root,
+            marker: PhantomData,
+        };
+        let in_bounds = |link: u64| link == NIL || link < len;
+        if !in_bounds(root) || (0..len).any(|i| {
+            let (left, right) = frozen.links(i);
+            !in_bounds(left) || !in_bounds(right)
+        }) {
+            return Err(invalid("child offset out of bounds"));
+        }
+        Ok(frozen)
+    }
+
+    /// 返回树中节点的个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BSTree, FrozenBSTree};
+    /// let path = std::env::temp_dir().join("an_ok_bstree_len.bin");
+    /// FrozenBSTree::write_to(&BSTree::<u8, u8>::new(), &path).unwrap();
+    /// let frozen: FrozenBSTree<u8, u8> = FrozenBSTree::open_mmap(&path).unwrap();
+    /// assert_eq!(frozen.len(), 0);
+    /// assert!(frozen.is_empty());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// 判断树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BSTree, FrozenBSTree};
+    /// let mut tree = BSTree::new();
+    /// tree.insert(1u8, 1u8);
+    /// let path = std::env::temp_dir().join("an_ok_bstree_is_empty.bin");
+    /// FrozenBSTree::write_to(&tree, &path).unwrap();
+    /// let frozen: FrozenBSTree<u8, u8> = FrozenBSTree::open_mmap(&path).unwrap();
+    /// assert!(!frozen.is_empty());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root == NIL
+    }
+
+    /// 根据键获取对应的值，只解码查找路径上的节点
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BSTree, FrozenBSTree};
+    /// let tree = BSTree::bulk_load((0..100u32).map(|i| (i, i * i)));
+    /// let path = std::env::temp_dir().join("an_ok_bstree_frozen_get.bin");
+    /// FrozenBSTree::write_to(&tree, &path).unwrap();
+    /// let frozen: FrozenBSTree<u32, u32> = FrozenBSTree::open_mmap(&path).unwrap();
+    /// assert_eq!(frozen.get(&12), Some(144));
+    /// assert_eq!(frozen.get(&100), None);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut current = self.root;
+        // 最多经过len个节点，防止损坏的文件中出现环
+        for _ in 0..self.len {
+            if current == NIL {
+                break;
+            }
+            let node_key = self.key(current);
+            let (left, right) = self.links(current);
+            if node_key < *key {
+                current = right;
+            } else if node_key > *key {
+                current = left;
+            } else {
+                return Some(self.value(current));
+            }
+        }
+        None
+    }
+
+    /// 按键的升序返回键在range范围内的键值对，范围之外的子树不会被访问
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::{BSTree, FrozenBSTree};
+    /// let tree = BSTree::bulk_load((0..10i64).map(|i| (i, -i)));
+    /// let path = std::env::temp_dir().join("an_ok_bstree_frozen_range.bin");
+    /// FrozenBSTree::write_to(&tree, &path).unwrap();
+    /// let frozen: FrozenBSTree<i64, i64> = FrozenBSTree::open_mmap(&path).unwrap();
+    /// assert_eq!(frozen.range(3..6), vec![(3, -3), (4, -4), (5, -5)]);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Vec<(K, V)> {
+        let mut res = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = self.root;
+        let mut visited = 0;
+        while (current != NIL || !stack.is_empty()) && visited <= self.len {
+            // 向左下降，低于下界的节点及其左子树都可以跳过
+            while current != NIL && visited <= self.len {
+                visited += 1;
+                let key = self.key(current);
+                let (left, right) = self.links(current);
+                if range::below(&key, &range) {
+                    current = right;
+                } else {
+                    stack.push((current, key));
+                    current = left;
+                }
+            }
+            if let Some((node, key)) = stack.pop() {
+                if range::above(&key, &range) {
+                    break;
+                }
+                res.push((key, self.value(node)));
+                current = self.links(node).1;
+            }
+        }
+        res
+    }
+
+    // 返回编号为i的节点记录
+    fn record(&self, i: u64) -> &[u8] {
+        &self.map[HEADER_SIZE + i as usize * Self::RECORD_SIZE..][..Self::RECORD_SIZE]
+    }
+
+    // 解码编号为i的节点的键
+    fn key(&self, i: u64) -> K {
+        K::read_bytes(&self.record(i)[..K::SIZE])
+    }
+
+    // 解码编号为i的节点的值
+    fn value(&self, i: u64) -> V {
+        V::read_bytes(&self.record(i)[K::SIZE..K::SIZE + V::SIZE])
+    }
+
+    // 返回编号为i的节点的左右孩子编号
+    fn links(&self, i: u64) -> (u64, u64) {
+        let links = &self.record(i)[K::SIZE + V::SIZE..];
+        (u64::read_bytes(&links[..8]), u64::read_bytes(&links[8..]))
+    }
+}
@@ -17,4 +17,88 @@ impl<'a, K: PartialOrd + Clone, V> Iterator for TraverseIter<'a, K, V> {
     fn next(&mut self) -> Option<Self::Item> {
         self.data.pop_front()
     }
-}
\ No newline at end of file
+}
+
+//带深度的遍历迭代器，按前序产生(深度, 键, 值)，根节点的深度为0
+pub struct DepthIter<'a, K, V> {
+    data: VecDeque<(usize, &'a K, &'a V)>,
+}
+
+impl<'a, K, V> DepthIter<'a, K, V> {
+    pub fn new(queue: VecDeque<(usize, &'a K, &'a V)>) -> Self {
+        DepthIter { data: queue }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for DepthIter<'a, K, V> {
+    type Item = (usize, &'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.pop_front()
+    }
+}
+
+//带父节点的遍历迭代器，按前序产生(父节点的键, 键, 值)，根节点的父节点为None
+pub struct ParentIter<'a, K, V> {
+    data: VecDeque<(Option<&'a K>, &'a K, &'a V)>,
+}
+
+impl<'a, K, V> ParentIter<'a, K, V> {
+    pub fn new(queue: VecDeque<(Option<&'a K>, &'a K, &'a V)>) -> Self {
+        ParentIter { data: queue }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for ParentIter<'a, K, V> {
+    type Item = (Option<&'a K>, &'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.pop_front()
+    }
+}
+
+//消耗树的键迭代器，按键的升序产生键
+pub struct IntoKeys<K, V> {
+    data: std::vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> IntoKeys<K, V> {
+    pub fn new(entries: Vec<(K, V)>) -> Self {
+        IntoKeys { data: entries.into_iter() }
+    }
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+}
+
+//消耗树的值迭代器，按键的升序产生值
+pub struct IntoValues<K, V> {
+    data: std::vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> IntoValues<K, V> {
+    pub fn new(entries: Vec<(K, V)>) -> Self {
+        IntoValues { data: entries.into_iter() }
+    }
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+}
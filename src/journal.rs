@@ -0,0 +1,216 @@
+use crate::BSTree;
+
+// 一条日志记录撤销一次操作所需的恢复步骤：按逆序把每个键恢复为记录的旧值，None表示键原本不存在
+type Entry<K, V> = Vec<(K, Option<V>)>;
+
+/// 带修改日志的二叉查找树，可以撤销、重做最近的插入、删除和批量操作
+///
+/// 每次修改都把被覆盖或删除的旧值记入撤销日志，`undo`按日志恢复旧值，
+/// 同时把恢复前的值记入重做日志供`redo`使用；发生新的修改后重做日志被清空。
+pub struct JournaledBSTree<K, V> {
+    tree: BSTree<K, V>,
+    undo_log: Vec<Entry<K, V>>,
+    redo_log: Vec<Entry<K, V>>,
+}
+
+impl<K: PartialOrd + Clone, V> Default for JournaledBSTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V> From<BSTree<K, V>> for JournaledBSTree<K, V> {
+    fn from(tree: BSTree<K, V>) -> Self {
+        JournaledBSTree {
+            tree,
+            undo_log: Vec::new(),
+            redo_log: Vec::new(),
+        }
+    }
+}
+
+impl<K: PartialOrd + Clone, V> JournaledBSTree<K, V> {
+    /// 构建一棵空的带日志的树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let tree: JournaledBSTree<i32, char> = JournaledBSTree::new();
+    /// assert!(tree.tree().is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::from(BSTree::new())
+    }
+
+    /// 返回底层的树，用于只读查询
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.tree().get(&1), Some(&'a'));
+    /// ```
+    pub fn tree(&self) -> &BSTree<K, V> {
+        &self.tree
+    }
+
+    /// 消耗当前对象，丢弃日志，返回底层的树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let tree = tree.into_inner();
+    /// assert!(tree.contains(&1));
+    /// ```
+    pub fn into_inner(self) -> BSTree<K, V> {
+        self.tree
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值，作为一次可撤销的操作
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(1, 'b');
+    /// assert!(tree.undo());
+    /// assert_eq!(tree.tree().get(&1), Some(&'a'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        let entry = vec![self.set(key, Some(value))];
+        self.record(entry);
+    }
+
+    /// 从树中删除键值对，作为一次可撤销的操作，被删除的值保存在日志中；找不到键值对时返回false，不记录日志
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert!(tree.delete(&1));
+    /// assert!(!tree.delete(&1));
+    /// assert!(tree.undo());
+    /// assert_eq!(tree.tree().get(&1), Some(&'a'));
+    /// ```
+    pub fn delete(&mut self, key: &K) -> bool {
+        match self.tree.take(key) {
+            Some((key, value)) => {
+                self.record(vec![(key, Some(value))]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 批量插入键值对，整批作为一次可撤销的操作
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert_many(vec![(1, 'x'), (2, 'b'), (3, 'c')]);
+    /// assert!(tree.undo());
+    /// let res: Vec<(&i32, &char)> = tree.tree().inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a')]);
+    /// ```
+    pub fn insert_many<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let entry: Entry<K, V> = iter
+            .into_iter()
+            .map(|(key, value)| self.set(key, Some(value)))
+            .collect();
+        if !entry.is_empty() {
+            self.record(entry);
+        }
+    }
+
+    /// 批量删除键值对，整批作为一次可撤销的操作，返回实际删除的个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert_many((1..=5).map(|i| (i, i)));
+    /// assert_eq!(tree.delete_many(vec![2, 4, 6]), 2);
+    /// assert!(tree.undo());
+    /// assert_eq!(tree.tree().inorder_iter().count(), 5);
+    /// ```
+    pub fn delete_many<I: IntoIterator<Item = K>>(&mut self, keys: I) -> usize {
+        let entry: Entry<K, V> = keys
+            .into_iter()
+            .filter_map(|key| self.tree.take(&key))
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        let removed = entry.len();
+        if removed > 0 {
+            self.record(entry);
+        }
+        removed
+    }
+
+    /// 撤销最近一次操作，没有可撤销的操作时返回false
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// assert!(!tree.undo());
+    /// tree.insert(1, 'a');
+    /// assert!(tree.undo());
+    /// assert!(tree.tree().is_empty());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        match self.undo_log.pop() {
+            Some(entry) => {
+                let inverse = self.apply(entry);
+                self.redo_log.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 重做最近一次被撤销的操作，没有可重做的操作时返回false
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::JournaledBSTree;
+    /// let mut tree = JournaledBSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.undo();
+    /// assert!(tree.redo());
+    /// assert_eq!(tree.tree().get(&1), Some(&'a'));
+    /// assert!(!tree.redo());
+    /// ```
+    pub fn redo(&mut self) -> bool {
+        match self.redo_log.pop() {
+            Some(entry) => {
+                let inverse = self.apply(entry);
+                self.undo_log.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 记录一次新的修改，此后之前撤销的操作不能再重做
+    fn record(&mut self, entry: Entry<K, V>) {
+        self.undo_log.push(entry);
+        self.redo_log.clear();
+    }
+
+    // 把key设为value（None表示删除），返回恢复原状所需的步骤
+    fn set(&mut self, key: K, value: Option<V>) -> (K, Option<V>) {
+        let old = match value {
+            Some(value) => self.tree.replace(key.clone(), value),
+            None => self.tree.take(&key),
+        };
+        (key, old.map(|(_, value)| value))
+    }
+
+    // 按逆序执行日志记录中的恢复步骤，返回再次恢复所需的步骤；
+    // 这些步骤按执行的先后排列，下次同样逆序执行，才能正确处理同一个键出现多次的批量操作
+    fn apply(&mut self, entry: Entry<K, V>) -> Entry<K, V> {
+        entry
+            .into_iter()
+            .rev()
+            .map(|(key, value)| self.set(key, value))
+            .collect()
+    }
+}
@@ -0,0 +1,220 @@
+use std::ops::{AddAssign, Bound, RangeBounds};
+use crate::augmented::{AugTree, Augment, Link, Node};
+use crate::iterator::TraverseIter;
+use crate::range;
+
+// 尚未下推的增量，作用于以该节点为根的整棵子树（包括该节点自身）
+struct Tag<V>(Option<V>);
+
+impl<V> Default for Tag<V> {
+    fn default() -> Self {
+        Tag(None)
+    }
+}
+
+impl<K, V: AddAssign + Clone> Augment<K, V> for Tag<V> {
+    // 修改节点的孩子之前标记已经下推，不需要重新计算
+    fn update(_node: &mut Node<K, V, Tag<V>>) {}
+
+    // 把节点上的标记作用到自身的值上，并转交给左右孩子
+    fn push(node: &mut Node<K, V, Tag<V>>) {
+        if let Some(tag) = node.aug.0.take() {
+            if let Some(left) = node.left.as_mut() {
+                add_tag(&mut left.aug.0, &tag);
+            }
+            if let Some(right) = node.right.as_mut() {
+                add_tag(&mut right.aug.0, &tag);
+            }
+            node.value += tag;
+        }
+    }
+}
+
+// 把增量delta累加到标记tag上
+fn add_tag<V: AddAssign + Clone>(tag: &mut Option<V>, delta: &V) {
+    match tag {
+        Some(tag) => *tag += delta.clone(),
+        None => *tag = Some(delta.clone()),
+    }
+}
+
+/// 支持区间懒更新的二叉查找树
+///
+/// `range_apply`只在O(h)个节点上记录增量标记，而不逐个修改范围内的值；
+/// 之后沿路径访问节点时才把标记下推给孩子。增量通过`+=`累加，要求加法满足交换律（例如各种数值类型）。
+/// 节点按随机秩保持堆序，期望高度为O(log n)。
+pub struct LazyTree<K, V> {
+    tree: AugTree<K, V, Tag<V>>,
+}
+
+impl<K: PartialOrd + Clone, V: AddAssign + Clone> Default for LazyTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: AddAssign + Clone> LazyTree<K, V> {
+    /// 构建一棵空的懒更新树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let tree: LazyTree<i32, i32> = LazyTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        LazyTree { tree: AugTree::new() }
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(1, 10);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(1, 20);
+    /// assert_eq!(tree.get(&1), Some(20));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tree.insert(key, value);
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(1, 10);
+    /// tree.range_apply(.., 5);
+    /// assert_eq!(tree.take(&1), Some((1, 15)));
+    /// assert_eq!(tree.take(&1), None);
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        self.tree.take(key)
+    }
+
+    /// 根据键获取对应的值，返回的值已经累加了路径上所有尚未下推的增量，不会修改树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(1, 10);
+    /// tree.range_apply(1..2, 3);
+    /// assert_eq!(tree.get(&1), Some(13));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut pending: Option<V> = None;
+        let mut current = self.tree.root.as_ref();
+        while let Some(node) = current {
+            if let Some(tag) = &node.aug.0 {
+                add_tag(&mut pending, tag);
+            }
+            if node.key < *key {
+                current = node.right.as_ref();
+            } else if node.key > *key {
+                current = node.left.as_ref();
+            } else {
+                let mut value = node.value.clone();
+                if let Some(delta) = pending {
+                    value += delta;
+                }
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// 根据键获取对应值的可变引用，查找路径上的增量会先下推
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(1, 10);
+    /// tree.range_apply(.., 1);
+    /// *tree.get_mut(&1).unwrap() *= 2;
+    /// assert_eq!(tree.get(&1), Some(22));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    /// 查找是否存在键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(1, 10);
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.contains(key)
+    }
+
+    /// 给键在range范围内的每个值加上delta；完全落在范围内的子树只在根节点记录标记，
+    /// 只需访问范围两条边界上的O(h)个节点，而不逐个修改范围内的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// for (name, score) in [(60, 1), (75, 2), (90, 3)] {
+    ///     tree.insert(name, score);
+    /// }
+    /// tree.range_apply(70..=90, 10);
+    /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&60, &1), (&75, &12), (&90, &13)]);
+    /// ```
+    pub fn range_apply<R: RangeBounds<K>>(&mut self, range: R, delta: V) {
+        let low_in = matches!(range.start_bound(), Bound::Unbounded);
+        let high_in = matches!(range.end_bound(), Bound::Unbounded);
+        Self::apply(&mut self.tree.root, &range, &delta, low_in, high_in);
+    }
+
+    // low_in、high_in表示子树中的键是否一定满足范围的下界、上界，两者都满足时整棵子树都在范围内，只记录标记
+    fn apply<R: RangeBounds<K>>(link: &mut Link<K, V, Tag<V>>, range: &R, delta: &V, low_in: bool, high_in: bool) {
+        let node = match link {
+            Some(node) => node,
+            None => return,
+        };
+        if low_in && high_in {
+            add_tag(&mut node.aug.0, delta);
+        } else if range::below(&node.key, range) {
+            Self::apply(&mut node.right, range, delta, low_in, high_in);
+        } else if range::above(&node.key, range) {
+            Self::apply(&mut node.left, range, delta, low_in, high_in);
+        } else {
+            // 当前节点在范围内，左子树的键都小于它，一定满足上界；右子树的键都大于它，一定满足下界
+            node.value += delta.clone();
+            Self::apply(&mut node.left, range, delta, low_in, true);
+            Self::apply(&mut node.right, range, delta, true, high_in);
+        }
+    }
+
+    /// 中序遍历迭代器，遍历前会把全部标记下推，使每个值都是最新的
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::LazyTree;
+    /// let mut tree = LazyTree::new();
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 10);
+    /// tree.range_apply(..2, 1);
+    /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &11), (&2, &20)]);
+    /// ```
+    pub fn inorder_iter(&mut self) -> TraverseIter<'_, K, V> {
+        self.tree.push_all();
+        self.tree.inorder_iter()
+    }
+}
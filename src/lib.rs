@@ -1,12 +1,85 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+mod allocator;
+mod arena;
+mod augmented;
+mod binary_search_tree;
+mod bounded;
+mod change;
+mod cursor;
+mod distance;
+mod error;
+#[cfg(feature = "persist")]
+mod framing;
+#[cfg(feature = "mmap")]
+mod frozen;
 mod iterator;
+mod journal;
+mod lazy_tree;
+mod merge;
+mod merkle;
+mod metrics;
+mod multiset;
+mod order_stat;
+mod policy;
+mod pool;
+#[cfg(feature = "persist")]
+mod persist;
+mod random;
+mod range;
+mod snapshot;
+mod sorted;
+mod sum_tree;
+mod trace;
+mod validate;
+mod versioned;
+mod walk;
+#[cfg(feature = "persist")]
+mod wal;
+mod zip_tree;
 
+pub use allocator::{Allocator, Global};
+pub use arena::{ArenaBSTree, NodeId};
+pub use binary_search_tree::BinarySearchTree;
+pub use bounded::BoundedBSTree;
+pub use change::Change;
+pub use cursor::Cursor;
+pub use distance::Distance;
+pub use error::{DecodeError, GetManyMutError, GraftError, KeyNotFound, OccupiedError, RekeyError};
+#[cfg(feature = "mmap")]
+pub use frozen::{FixedSize, FrozenBSTree};
+pub use iterator::{DepthIter, IntoKeys, IntoValues, ParentIter, TraverseIter};
+pub use journal::JournaledBSTree;
+pub use lazy_tree::LazyTree;
+pub use merge::{Difference, Intersection, MergeIter};
+pub use merkle::MerkleBSTree;
+#[cfg(feature = "metrics")]
+pub use metrics::{metrics, reset_metrics, Metrics};
+pub use multiset::MultiBSTree;
+pub use order_stat::OrderStatTree;
+pub use policy::{DuplicatePolicy, Eviction, KeepOld, Overwrite, Tie};
+pub use snapshot::Snapshot;
+pub use sum_tree::SumTree;
+pub use versioned::VersionedBSTree;
+pub use walk::{Order, Visit};
+#[cfg(feature = "persist")]
+pub use wal::WalWriter;
+pub use zip_tree::ZipTree;
+
+
+// 两种实现同时编译，可以在同一个程序中分别使用；BSTree是由no_recur特性选择的默认实现
+pub mod bstree_no_recursion;
+pub mod bstree_recursion;
+
+pub use bstree_no_recursion::BSTree as IterativeBSTree;
+pub use bstree_recursion::BSTree as RecursiveBSTree;
 
 #[cfg(feature = "no_recur")]
-mod bstree_no_recursion;
-#[cfg(feature = "no_recur")]
-pub use bstree_no_recursion::BSTree;
+pub use bstree_no_recursion::{BSTree, OccupiedEntry, Walker};
+#[cfg(all(feature = "no_recur", feature = "rayon"))]
+pub use bstree_no_recursion::ParIter;
 
 #[cfg(not(feature = "no_recur"))]
-mod bstree_recursion;
-#[cfg(not(feature = "no_recur"))]
-pub use bstree_recursion::BSTree;
\ No newline at end of file
+pub use bstree_recursion::{BSTree, OccupiedEntry, Walker};
+#[cfg(all(not(feature = "no_recur"), feature = "rayon"))]
+pub use bstree_recursion::ParIter;
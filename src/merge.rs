@@ -0,0 +1,105 @@
+use crate::cursor::Cursor;
+use crate::policy::Tie;
+
+/// 按键的升序交错产生两棵树中全部键值对的迭代器，由`merge_iter`创建
+///
+/// 两棵树各用一个游标同步向前移动，不会把任何一棵树先收集到Vec中，每产生一个键值对只需一次O(h)的查找。
+/// 相同的键按创建时给出的[`Tie`]处理。
+pub struct MergeIter<'a, K, V> {
+    left: Cursor<'a, K, V>,
+    right: Cursor<'a, K, V>,
+    tie: Tie,
+}
+
+impl<'a, K: PartialOrd + Clone, V> MergeIter<'a, K, V> {
+    pub(crate) fn new(left: Cursor<'a, K, V>, right: Cursor<'a, K, V>, tie: Tie) -> Self {
+        MergeIter { left, right, tie }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for MergeIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.left.peek_next(), self.right.peek_next()) {
+            (Some(l), Some(r)) if l.0 < r.0 => self.left.next(),
+            (Some(l), Some(r)) if l.0 > r.0 => self.right.next(),
+            (Some(_), Some(_)) => match self.tie {
+                // 先产生左边的，右边相同的键留到下一次，届时它小于左边剩下的键
+                Tie::Both => self.left.next(),
+                Tie::Left => {
+                    self.right.next();
+                    self.left.next()
+                }
+                Tie::Right => {
+                    self.left.next();
+                    self.right.next()
+                }
+            },
+            (Some(_), None) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+}
+
+/// 按键的升序产生左边树中键也在右边树中的键值对的迭代器，由`intersection_iter`创建
+pub struct Intersection<'a, K, V> {
+    left: Cursor<'a, K, V>,
+    right: Cursor<'a, K, V>,
+}
+
+impl<'a, K: PartialOrd + Clone, V> Intersection<'a, K, V> {
+    pub(crate) fn new(left: Cursor<'a, K, V>, right: Cursor<'a, K, V>) -> Self {
+        Intersection { left, right }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for Intersection<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (l, r) = (self.left.peek_next()?, self.right.peek_next()?);
+            if l.0 < r.0 {
+                self.left.next();
+            } else if l.0 > r.0 {
+                self.right.next();
+            } else {
+                self.right.next();
+                return self.left.next();
+            }
+        }
+    }
+}
+
+/// 按键的升序产生左边树中键不在右边树中的键值对的迭代器，由`difference_iter`创建
+pub struct Difference<'a, K, V> {
+    left: Cursor<'a, K, V>,
+    right: Cursor<'a, K, V>,
+}
+
+impl<'a, K: PartialOrd + Clone, V> Difference<'a, K, V> {
+    pub(crate) fn new(left: Cursor<'a, K, V>, right: Cursor<'a, K, V>) -> Self {
+        Difference { left, right }
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for Difference<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let l = self.left.peek_next()?;
+            match self.right.peek_next() {
+                Some(r) if l.0 > r.0 => {
+                    self.right.next();
+                }
+                Some(r) if l.0 == r.0 => {
+                    self.left.next();
+                    self.right.next();
+                }
+                _ => return self.left.next(),
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::hash::{Hash, Hasher};
+use crate::augmented::{AugTree, Augment, Link, Node};
+use crate::iterator::TraverseIter;
+
+// 64位FNV-1a哈希，算法和参数都是固定的，不像DefaultHasher那样可能随Rust版本变化；
+// 多字节整数一律按小端序写入，usize和isize扩展为64位，结果与平台的字节序和字长无关
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Fnv1a(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64);
+    }
+}
+
+// 子树的默克尔哈希，由节点的键、值和左右孩子的哈希计算
+#[derive(Default)]
+struct Digest(u64);
+
+// 返回link处子树的哈希，空树为0
+fn hash_of<K, V>(link: &Link<K, V, Digest>) -> u64 {
+    link.as_ref().map_or(0, |node| node.aug.0)
+}
+
+// 根据当前的键、值和孩子计算节点的哈希
+fn compute_hash<K: Hash, V: Hash>(node: &Node<K, V, Digest>) -> u64 {
+    let mut hasher = Fnv1a::new();
+    node.key.hash(&mut hasher);
+    node.value.hash(&mut hasher);
+    hash_of(&node.left).hash(&mut hasher);
+    hash_of(&node.right).hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<K: Hash, V: Hash> Augment<K, V> for Digest {
+    fn update(node: &mut Node<K, V, Digest>) {
+        node.aug = Digest(compute_hash(node));
+    }
+
+    // 秩由键的哈希决定，树的形状因此只取决于键的集合而与操作顺序无关；
+    // 经过splitmix64的混合后尾随零的个数近似服从几何分布，期望高度仍为O(log n)
+    fn rank(key: &K) -> u32 {
+        let mut hasher = Fnv1a::new();
+        key.hash(&mut hasher);
+        let mut z = hasher.finish().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)).trailing_zeros()
+    }
+}
+
+/// 带默克尔哈希的二叉查找树，每个节点保存由自身键值和左右孩子哈希计算出的哈希
+///
+/// 修改时只需重新计算查找路径上的O(log n)个哈希，`root_hash`在O(1)时间内概括整棵树的内容，
+/// 两个副本比较根哈希即可发现分歧。树建立在平衡的拉链树上，节点的秩由键的哈希决定，
+/// 树的形状只取决于键的集合，因此内容相同的两棵树根哈希相同，与插入和删除的顺序无关。
+///
+/// 节点哈希使用固定的64位FNV-1a算法，输入是键和值的`Hash`实现写出的字节，多字节整数按小端序编码，
+/// `usize`和`isize`按64位编码，因此只要键和值的`Hash`实现不变，根哈希在不同的Rust版本和平台上都相同，
+/// 可以持久化或在不同机器的副本之间比较。
+pub struct MerkleBSTree<K, V> {
+    tree: AugTree<K, V, Digest>,
+}
+
+impl<K: PartialOrd + Clone + Hash, V: Hash> Default for MerkleBSTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone + Hash, V: Hash> MerkleBSTree<K, V> {
+    /// 构建一棵空的默克尔树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let tree: MerkleBSTree<i32, char> = MerkleBSTree::new();
+    /// assert!(tree.is_empty());
+    /// assert_eq!(tree.root_hash(), 0);
+    /// ```
+    pub fn new() -> Self {
+        MerkleBSTree {
+            tree: AugTree::new(),
+        }
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut tree = MerkleBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值，并更新路径上的哈希
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut tree = MerkleBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let before = tree.root_hash();
+    /// tree.insert(1, 'b');
+    /// assert_ne!(tree.root_hash(), before);
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tree.insert(key, value);
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut tree = MerkleBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let before = tree.root_hash();
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.take(&2), Some((2, 'b')));
+    /// assert_eq!(tree.root_hash(), before);
+    /// assert_eq!(tree.take(&2), None);
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        self.tree.take(key)
+    }
+
+    /// 根据键获取对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut tree = MerkleBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    /// 返回根节点的哈希，空树为0；内容相同的两棵树根哈希相同，与操作顺序无关
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut a = MerkleBSTree::new();
+    /// let mut b = MerkleBSTree::new();
+    /// for i in [2, 1, 3] {
+    ///     a.insert(i, i * 10);
+    /// }
+    /// for i in [3, 2, 1] {
+    ///     b.insert(i, i * 10);
+    /// }
+    /// assert_eq!(a.root_hash(), b.root_hash());
+    /// b.insert(3, 31);
+    /// assert_ne!(a.root_hash(), b.root_hash());
+    /// ```
+    pub fn root_hash(&self) -> u64 {
+        hash_of(&self.tree.root)
+    }
+
+    /// 重新计算每个节点的哈希并与保存的哈希比较，全部一致时返回true，用于检查树是否被破坏
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut tree = MerkleBSTree::new();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.take(&4);
+    /// assert!(tree.verify());
+    /// ```
+    pub fn verify(&self) -> bool {
+        let mut stack: Vec<&Node<K, V, Digest>> = self.tree.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.aug.0 != compute_hash(node) {
+                return false;
+            }
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+        true
+    }
+
+    /// 中序遍历迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MerkleBSTree;
+    /// let mut tree = MerkleBSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.tree.inorder_iter()
+    }
+}
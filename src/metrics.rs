@@ -0,0 +1,90 @@
+#[cfg(feature = "metrics")]
+use std::cell::Cell;
+
+/// 操作计数，统计按键定位节点时的查找路径，需要开启`metrics`特性
+///
+/// 只有按给定的键向下查找节点的操作计入访问和比较：`get`、`get_mut`、`contains`等查找，所有插入方法，
+/// 按键删除(`delete`、`take`等)以及`splay_to_root`，路径上的每个节点计一次访问，并累计在该节点上进行的键比较次数；
+/// 前驱后继、范围查询和删除、取出最小最大值等其余的下降不保证计入，而内部借助按键查找实现的方法(例如`inorder_iter`)
+/// 会计入其中的每次查找。展开、重建等操作中的每次旋转计一次旋转。
+///
+/// 计数保存在线程局部变量中：同一线程上所有的树共用一份计数，在其他线程(包括`rayon`的工作线程)上进行的操作
+/// 记在那个线程上，不会出现在当前线程的`metrics`中。
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// 按键查找时键的比较次数
+    pub comparisons: u64,
+    /// 旋转次数
+    pub rotations: u64,
+    /// 按键查找经过的节点个数
+    pub node_visits: u64,
+}
+
+#[cfg(feature = "metrics")]
+thread_local! {
+    static COUNTERS: Cell<Metrics> = Cell::new(Metrics::default());
+}
+
+/// 返回当前线程自上次`reset_metrics`以来累计的操作计数，需要开启`metrics`特性
+/// # Example
+/// ```
+/// use an_ok_bstree::{metrics, reset_metrics, BSTree};
+/// let mut tree = BSTree::new();
+/// tree.insert(2, 'b');
+/// tree.insert(1, 'a');
+/// reset_metrics();
+/// tree.get(&1);
+/// assert_eq!(metrics().node_visits, 2);
+/// // 其他线程上的操作不计入当前线程
+/// std::thread::spawn(move || tree.contains(&1)).join().unwrap();
+/// assert_eq!(metrics().node_visits, 2);
+/// ```
+#[cfg(feature = "metrics")]
+pub fn metrics() -> Metrics {
+    COUNTERS.with(|counters| counters.get())
+}
+
+/// 把当前线程的操作计数清零，需要开启`metrics`特性
+/// # Example
+/// ```
+/// use an_ok_bstree::{metrics, reset_metrics, BSTree, Metrics};
+/// let mut tree = BSTree::new();
+/// tree.insert(1, 'a');
+/// tree.insert(2, 'b');
+/// reset_metrics();
+/// assert_eq!(metrics(), Metrics::default());
+/// ```
+#[cfg(feature = "metrics")]
+pub fn reset_metrics() {
+    COUNTERS.with(|counters| counters.set(Metrics::default()));
+}
+
+// 记录一次节点访问以及在该节点上进行的键比较次数，未开启metrics特性时为空操作
+#[cfg(feature = "metrics")]
+pub(crate) fn visit(comparisons: u64) {
+    COUNTERS.with(|counters| {
+        let mut m = counters.get();
+        m.node_visits += 1;
+        m.comparisons += comparisons;
+        counters.set(m);
+    });
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn visit(_comparisons: u64) {}
+
+// 记录一次旋转，未开启metrics特性时为空操作
+#[cfg(feature = "metrics")]
+pub(crate) fn rotate() {
+    COUNTERS.with(|counters| {
+        let mut m = counters.get();
+        m.rotations += 1;
+        counters.set(m);
+    });
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn rotate() {}
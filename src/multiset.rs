@@ -0,0 +1,182 @@
+use crate::BSTree;
+use std::iter;
+
+/// 允许重复键的二叉查找树，每个不同的键只占一个节点，节点中保存该键出现的次数
+///
+/// 插入已经存在的键只会把次数加一，适合统计频率或维护可以重复的有序数据。
+pub struct MultiBSTree<K> {
+    tree: BSTree<K, usize>,
+    len: usize,
+}
+
+impl<K: PartialOrd + Clone> Default for MultiBSTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone> MultiBSTree<K> {
+    /// 构建一棵空树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let tree: MultiBSTree<i32> = MultiBSTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        MultiBSTree { tree: BSTree::new(), len: 0 }
+    }
+
+    /// 返回键的总个数，重复的键按出现次数计算
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// tree.insert(1);
+    /// tree.insert(1);
+    /// tree.insert(2);
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert('a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 返回底层的树，值为每个键出现的次数，用于只读查询
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// tree.insert(1);
+    /// tree.insert(1);
+    /// assert_eq!(tree.tree().get(&1), Some(&2));
+    /// ```
+    pub fn tree(&self) -> &BSTree<K, usize> {
+        &self.tree
+    }
+
+    /// 返回键key出现的次数，不存在时返回0
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// tree.insert('a');
+    /// tree.insert('a');
+    /// assert_eq!(tree.count(&'a'), 2);
+    /// assert_eq!(tree.count(&'b'), 0);
+    /// ```
+    pub fn count(&self, key: &K) -> usize {
+        self.tree.get(key).copied().unwrap_or(0)
+    }
+
+    /// 判断树中是否有键key
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// tree.insert(1);
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.contains(key)
+    }
+
+    /// 插入一个键，键已经存在时把它的次数加一，返回插入后的次数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// assert_eq!(tree.insert(5), 1);
+    /// assert_eq!(tree.insert(5), 2);
+    /// ```
+    pub fn insert(&mut self, key: K) -> usize {
+        let count = self.tree.get_or_insert_with(key, || 0);
+        *count += 1;
+        self.len += 1;
+        *count
+    }
+
+    /// 删除一个键，次数减为0时删除节点，返回删除后的次数，键不存在时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// tree.insert(5);
+    /// tree.insert(5);
+    /// assert_eq!(tree.remove_one(&5), Some(1));
+    /// assert_eq!(tree.remove_one(&5), Some(0));
+    /// assert_eq!(tree.remove_one(&5), None);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn remove_one(&mut self, key: &K) -> Option<usize> {
+        let count = self.tree.get_mut(key)?;
+        *count -= 1;
+        let remaining = *count;
+        if remaining == 0 {
+            self.tree.take(key);
+        }
+        self.len -= 1;
+        Some(remaining)
+    }
+
+    /// 删除键key的全部重复，返回删除的个数，键不存在时返回0
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// tree.insert(5);
+    /// tree.insert(5);
+    /// tree.insert(6);
+    /// assert_eq!(tree.remove_all(&5), 2);
+    /// assert_eq!(tree.remove_all(&5), 0);
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn remove_all(&mut self, key: &K) -> usize {
+        let removed = self.tree.take(key).map_or(0, |(_, count)| count);
+        self.len -= removed;
+        removed
+    }
+
+    /// 按键的升序返回每个不同的键及其出现次数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// for key in [3, 1, 3] {
+    ///     tree.insert(key);
+    /// }
+    /// let res: Vec<(&i32, usize)> = tree.iter().collect();
+    /// assert_eq!(res, vec![(&1, 1), (&3, 2)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.tree.inorder_iter().map(|(key, count)| (key, *count))
+    }
+
+    /// 按键的升序返回全部键，重复的键按出现次数重复产生
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::MultiBSTree;
+    /// let mut tree = MultiBSTree::new();
+    /// for key in [3, 1, 3] {
+    ///     tree.insert(key);
+    /// }
+    /// let res: Vec<&i32> = tree.iter_expanded().collect();
+    /// assert_eq!(res, vec![&1, &3, &3]);
+    /// ```
+    pub fn iter_expanded(&self) -> impl Iterator<Item = &K> {
+        self.iter().flat_map(|(key, count)| iter::repeat_n(key, count))
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::ops::RangeBounds;
+use crate::augmented::{AugTree, Augment, Link, Node};
+use crate::iterator::TraverseIter;
+use crate::random;
+use crate::range;
+
+// 以该节点为根的子树中节点的个数（包括该节点自身）
+#[derive(Default)]
+struct Size(usize);
+
+impl<K, V> Augment<K, V> for Size {
+    fn update(node: &mut Node<K, V, Size>) {
+        node.aug = Size(1 + size(&node.left) + size(&node.right));
+    }
+}
+
+// 以link为根的子树中节点的个数，空树为0
+fn size<K, V>(link: &Link<K, V, Size>) -> usize {
+    link.as_ref().map_or(0, |node| node.aug.0)
+}
+
+/// 每个节点保存子树大小的顺序统计树
+///
+/// 插入和删除沿查找路径维护子树大小，按名次定位节点(`select`)、求键的名次(`rank`)
+/// 和按名次窗口遍历(`iter_slice`)都只需沿树向下走一遍，不必从最小的键开始逐个数过去。
+/// 节点按随机秩保持堆序，期望高度为O(log n)。
+pub struct OrderStatTree<K, V> {
+    tree: AugTree<K, V, Size>,
+}
+
+impl<K: PartialOrd + Clone, V> Default for OrderStatTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V> OrderStatTree<K, V> {
+    /// 构建一棵空的顺序统计树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let tree: OrderStatTree<i32, char> = OrderStatTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        OrderStatTree { tree: AugTree::new() }
+    }
+
+    /// 返回树中键值对的个数，只需O(1)时间
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'c');
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        size(&self.tree.root)
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(1, 'a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(1, 'b');
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tree.insert(key, value);
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.take(&1), Some((1, 'a')));
+    /// assert_eq!(tree.take(&1), None);
+    /// assert_eq!(tree.len(), 0);
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        self.tree.take(key)
+    }
+
+    /// 根据键获取对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    /// 查找是否存在键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// tree.insert(1, 'a');
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.contains(key)
+    }
+
+    /// 返回名次(从0开始)为rank的键值对，rank不小于树中键值对的个数时返回None；
+    /// 借助子树大小直接向下定位，只需O(h)时间
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// for k in [30, 10, 20] {
+    ///     tree.insert(k, k / 10);
+    /// }
+    /// assert_eq!(tree.select(1), Some((&20, &2)));
+    /// assert_eq!(tree.select(3), None);
+    /// ```
+    pub fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        let mut rank = rank;
+        let mut current = self.tree.root.as_deref();
+        while let Some(node) = current {
+            let left = size(&node.left);
+            if rank < left {
+                current = node.left.as_deref();
+            } else if rank == left {
+                return Some((&node.key, &node.value));
+            } else {
+                rank -= left + 1;
+                current = node.right.as_deref();
+            }
+        }
+        None
+    }
+
+    /// 返回键key的名次，即树中小于key的键的个数，key不在树中时返回None；只需O(h)时间
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// for k in [30, 10, 20] {
+    ///     tree.insert(k, ());
+    /// }
+    /// assert_eq!(tree.rank(&30), Some(2));
+    /// assert_eq!(tree.rank(&15), None);
+    /// ```
+    pub fn rank(&self, key: &K) -> Option<usize> {
+        let mut rank = 0;
+        let mut current = self.tree.root.as_deref();
+        while let Some(node) = current {
+            if node.key < *key {
+                rank += size(&node.left) + 1;
+                current = node.right.as_deref();
+            } else if node.key > *key {
+                current = node.left.as_deref();
+            } else {
+                return Some(rank + size(&node.left));
+            }
+        }
+        None
+    }
+
+    /// 按键的升序产生名次(从0开始)落在range内的键值对，例如排序后的第1000到1049行，用于分页；
+    /// 借助子树大小直接下降到名次为start的节点，再按中序继续，只需O(h + k)时间，k为产生的键值对个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// for i in [5, 2, 8, 1, 3, 7, 9, 0, 4, 6] {
+    ///     tree.insert(i * 10, i);
+    /// }
+    /// let res: Vec<&i32> = tree.iter_slice(3..6).map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&30, &40, &50]);
+    /// assert_eq!(tree.iter_slice(8..).count(), 2);
+    /// assert_eq!(tree.iter_slice(20..30).count(), 0);
+    /// ```
+    pub fn iter_slice<R: RangeBounds<usize>>(&self, range: R) -> TraverseIter<'_, K, V> {
+        let (start, end) = range::rank_window(&range);
+        let mut queue = VecDeque::new();
+        // 栈中保存按中序还没有产生的祖先节点，栈顶是名次为start的节点
+        let mut stack = Vec::new();
+        let mut skip = start;
+        let mut current = self.tree.root.as_deref();
+        while let Some(node) = current {
+            let left = size(&node.left);
+            if skip < left {
+                stack.push(node);
+                current = node.left.as_deref();
+            } else if skip == left {
+                stack.push(node);
+                break;
+            } else {
+                skip -= left + 1;
+                current = node.right.as_deref();
+            }
+        }
+        let mut count = end.saturating_sub(start);
+        while count > 0 {
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            queue.push_back((&node.key, &node.value));
+            count -= 1;
+            let mut current = node.right.as_deref();
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+        }
+        TraverseIter::new(queue)
+    }
+
+    /// 从树中等概率随机选取一个键值对，树为空时返回None；rng每次调用返回一个均匀分布的64位随机数，
+    /// 可以包装任意随机数生成器。先均匀地选出名次，再借助子树大小直接定位，只需O(h)时间
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// for i in [3, 1, 4, 2, 5] {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// let mut state = 7u64;
+    /// let mut rng = || {
+    ///     state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     state
+    /// };
+    /// let (k, v) = tree.sample(&mut rng).unwrap();
+    /// assert_eq!(*v, k * 10);
+    /// assert_eq!(OrderStatTree::<i32, i32>::new().sample(&mut rng), None);
+    /// ```
+    pub fn sample<F: FnMut() -> u64>(&self, rng: &mut F) -> Option<(&K, &V)> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        self.select(random::below(rng(), len))
+    }
+
+    /// 从树中等概率随机选取n个互不相同的键值对，按键的升序返回，n超过树中键值对的个数时返回全部键值对；
+    /// 用Floyd算法均匀地选出n个互不相同的名次，再逐个借助子树大小定位，只需O(n(h + log n))时间
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// for i in 1..=100 {
+    ///     tree.insert(i, ());
+    /// }
+    /// let mut state = 7u64;
+    /// let mut rng = || {
+    ///     state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     state
+    /// };
+    /// let picked: Vec<&i32> = tree.sample_n(10, &mut rng).into_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(picked.len(), 10);
+    /// assert!(picked.windows(2).all(|w| w[0] < w[1]));
+    /// assert_eq!(tree.sample_n(200, &mut rng).len(), 100);
+    /// ```
+    pub fn sample_n<F: FnMut() -> u64>(&self, n: usize, rng: &mut F) -> Vec<(&K, &V)> {
+        let len = self.len();
+        let n = n.min(len);
+        // 依次在[0, j]中选一个名次，已经选过时改选j，得到的n元子集是均匀分布的
+        let mut ranks = BTreeSet::new();
+        for j in len - n..len {
+            let rank = random::below(rng(), j + 1);
+            if !ranks.insert(rank) {
+                ranks.insert(j);
+            }
+        }
+        ranks.into_iter().filter_map(|rank| self.select(rank)).collect()
+    }
+
+    /// 中序遍历迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::OrderStatTree;
+    /// let mut tree = OrderStatTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.tree.inorder_iter()
+    }
+}
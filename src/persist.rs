@@ -0,0 +1,92 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use crate::framing::{push_field, split_field, split_sep, write_atomic, Malformed};
+use crate::{IterativeBSTree, RecursiveBSTree};
+
+// 读出一行保存的节点，返回键、值的文本和之后剩余的输入
+fn split_node(input: &str) -> Result<(&str, &str, &str), Malformed> {
+    let (key, rest) = split_field(input)?;
+    let (value, rest) = split_field(split_sep(rest, ' ')?)?;
+    Ok((key, value, split_sep(rest, '\n')?))
+}
+
+// 为两种实现的树分别实现保存和加载
+macro_rules! impl_persist {
+    ($tree:ident) => {
+        impl<K: PartialOrd + Clone, V> $tree<K, V> {
+            /// 把树保存到文件path，重新加载后形状完全相同：按前序每行写出一个节点的键和值，
+            /// 键和值都写成`字节数:文本`，文本中可以包含空格和换行；
+            /// 先写入同一目录下的临时文件再重命名为path，保存中途失败时原来的文件保持不变
+            /// # Example
+            /// ```
+            /// use an_ok_bstree::BSTree;
+            /// let mut tree = BSTree::new();
+            /// for (k, v) in [(8, "h"), (5, "e e"), (15, "o\no")] {
+            ///     tree.insert(k, v.to_string());
+            /// }
+            /// let path = std::env::temp_dir().join("an_ok_bstree_save_to.txt");
+            /// tree.save_to(&path).unwrap();
+            /// let loaded: BSTree<i32, String> = BSTree::load_from(&path).unwrap();
+            /// assert!(loaded.same_structure(&tree));
+            /// assert_eq!(loaded.get(&15), Some(&"o\no".to_string()));
+            /// # std::fs::remove_file(&path).unwrap();
+            /// ```
+            pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()>
+            where
+                K: fmt::Display,
+                V: fmt::Display,
+            {
+                let mut buf = String::new();
+                for (key, value) in self.preorder_iter() {
+                    push_field(&mut buf, &key.to_string());
+                    buf.push(' ');
+                    push_field(&mut buf, &value.to_string());
+                    buf.push('\n');
+                }
+                write_atomic(path.as_ref(), buf.as_bytes())
+            }
+
+            /// 从`save_to`保存的文件中还原出形状完全相同的树；
+            /// 文件格式不合法、键或值无法解析、键的顺序不是查找树的前序序列时返回`InvalidData`错误
+            /// # Example
+            /// ```
+            /// use an_ok_bstree::BSTree;
+            /// let path = std::env::temp_dir().join("an_ok_bstree_load_from.txt");
+            /// std::fs::write(&path, "1:2 2:20\n1:1 2:10\n").unwrap();
+            /// let tree: BSTree<i32, i32> = BSTree::load_from(&path).unwrap();
+            /// let res: Vec<(&i32, &i32)> = tree.preorder_iter().collect();
+            /// assert_eq!(res, vec![(&2, &20), (&1, &10)]);
+            /// std::fs::write(&path, "1:2 2:20\n1:1 3:10\n").unwrap();
+            /// assert!(BSTree::<i32, i32>::load_from(&path).is_err());
+            /// # std::fs::remove_file(&path).unwrap();
+            /// ```
+            pub fn load_from<P: AsRef<Path>>(path: P) -> io::Result<Self>
+            where
+                K: FromStr,
+                V: FromStr,
+            {
+                let text = fs::read_to_string(path)?;
+                let mut keys = Vec::new();
+                let mut values = Vec::new();
+                let mut rest = text.as_str();
+                while !rest.is_empty() {
+                    let offset = text.len() - rest.len();
+                    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid record at byte {}", offset));
+                    let (key, value, next) = split_node(rest).map_err(|_| invalid())?;
+                    keys.push(key.parse::<K>().map_err(|_| invalid())?);
+                    values.push(value.parse::<V>().map_err(|_| invalid())?);
+                    rest = next;
+                }
+                $tree::from_preorder(keys, values).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "keys are not the preorder of a binary search tree")
+                })
+            }
+        }
+    };
+}
+
+impl_persist!(RecursiveBSTree);
+impl_persist!(IterativeBSTree);
@@ -0,0 +1,56 @@
+/// 插入的键已经存在时如何处理，用于`insert_with_policy`
+///
+/// 除了[`Overwrite`]和[`KeepOld`]，任何`FnOnce(&K, V, V) -> V`的闭包都可以作为策略，
+/// 闭包的参数依次为键、旧值和新值，返回合并后的值。
+pub trait DuplicatePolicy<K, V> {
+    /// 根据键、旧值和新值得到最终保存的值
+    fn resolve(self, key: &K, old: V, new: V) -> V;
+}
+
+/// 用新值替换旧值，与`insert`的行为相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overwrite;
+
+/// 保留旧值，丢弃新值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepOld;
+
+impl<K, V> DuplicatePolicy<K, V> for Overwrite {
+    fn resolve(self, _key: &K, _old: V, new: V) -> V {
+        new
+    }
+}
+
+impl<K, V> DuplicatePolicy<K, V> for KeepOld {
+    fn resolve(self, _key: &K, old: V, _new: V) -> V {
+        old
+    }
+}
+
+impl<K, V, F: FnOnce(&K, V, V) -> V> DuplicatePolicy<K, V> for F {
+    fn resolve(self, key: &K, old: V, new: V) -> V {
+        self(key, old, new)
+    }
+}
+
+/// `BoundedBSTree`已满时插入新键的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eviction {
+    /// 插入后淘汰最小的键，树中保留最大的若干个键
+    EvictMin,
+    /// 插入后淘汰最大的键，树中保留最小的若干个键
+    EvictMax,
+    /// 拒绝插入新键
+    Reject,
+}
+
+/// `merge_iter`遇到两棵树中相同的键时产生哪些键值对
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tie {
+    /// 两个键值对都产生，先产生左边树中的
+    Both,
+    /// 只产生左边树中的键值对
+    Left,
+    /// 只产生右边树中的键值对
+    Right,
+}
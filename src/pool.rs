@@ -0,0 +1,97 @@
+use std::mem::MaybeUninit;
+use crate::allocator::{Allocator, Global};
+
+// 回收被删除节点的堆内存，供之后的插入复用，减少频繁插入删除时的内存分配次数
+pub(crate) struct NodePool<T, A: Allocator = Global> {
+    // 空闲的内存块，其中的值已经移出或从未初始化
+    #[cfg(feature = "allocator_api")]
+    free: Vec<Box<MaybeUninit<T>, A>>,
+    #[cfg(not(feature = "allocator_api"))]
+    free: Vec<Box<MaybeUninit<T>>>,
+    // 新的内存块从alloc中分配
+    alloc: A,
+}
+
+impl<T, A: Allocator + Clone> NodePool<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        NodePool { free: Vec::new(), alloc }
+    }
+
+    // 分配内存块使用的分配器
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // 优先复用空闲的内存块存放value，没有空闲内存块时才从分配器中分配
+    #[cfg(feature = "allocator_api")]
+    pub fn alloc(&mut self, value: T) -> Box<T, A> {
+        match self.free.pop() {
+            Some(slot) => Box::write(slot, value),
+            None => Box::new_in(value, self.alloc.clone()),
+        }
+    }
+
+    #[cfg(not(feature = "allocator_api"))]
+    pub fn alloc(&mut self, value: T) -> Box<T> {
+        match self.free.pop() {
+            Some(slot) => Box::write(slot, value),
+            None => Box::new(value),
+        }
+    }
+
+    // 取出boxed中的值，把它的内存块放入空闲列表
+    #[cfg(feature = "allocator_api")]
+    pub fn release(&mut self, boxed: Box<T, A>) -> T {
+        let (raw, alloc) = Box::into_raw_with_allocator(boxed);
+        // SAFETY: raw来自Box::into_raw_with_allocator，指向alloc分配的已初始化的T；读出值后内存块中只剩未初始化的数据，
+        // MaybeUninit<T>与T的布局相同，连同原来的分配器按MaybeUninit<T>重新接管内存不会再次析构其中的值
+        unsafe {
+            let value = raw.read();
+            self.free.push(Box::from_raw_in(raw.cast::<MaybeUninit<T>>(), alloc));
+            value
+        }
+    }
+
+    #[cfg(not(feature = "allocator_api"))]
+    pub fn release(&mut self, boxed: Box<T>) -> T {
+        let raw = Box::into_raw(boxed);
+        // SAFETY: raw来自Box::into_raw，指向已初始化的T；读出值后内存块中只剩未初始化的数据，
+        // MaybeUninit<T>与T的布局相同，按MaybeUninit<T>重新接管内存不会再次析构其中的值
+        unsafe {
+            let value = raw.read();
+            self.free.push(Box::from_raw(raw.cast::<MaybeUninit<T>>()));
+            value
+        }
+    }
+
+    // 保证空闲内存块至少有n个
+    pub fn reserve(&mut self, n: usize) {
+        if let Some(extra) = n.checked_sub(self.free.len()) {
+            self.free.reserve(extra);
+            for _ in 0..extra {
+                self.free.push(self.new_slot());
+            }
+        }
+    }
+
+    // 从分配器中分配一个未初始化的内存块
+    #[cfg(feature = "allocator_api")]
+    fn new_slot(&self) -> Box<MaybeUninit<T>, A> {
+        Box::new_uninit_in(self.alloc.clone())
+    }
+
+    #[cfg(not(feature = "allocator_api"))]
+    fn new_slot(&self) -> Box<MaybeUninit<T>> {
+        Box::new_uninit()
+    }
+
+    // 释放全部空闲内存块
+    pub fn shrink_to_fit(&mut self) {
+        self.free = Vec::new();
+    }
+
+    // 空闲内存块的个数
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+}
@@ -0,0 +1,39 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+thread_local! {
+    static STATE: Cell<u64> = Cell::new(seed());
+}
+
+// 借助标准库带随机种子的哈希器得到初始状态，状态不能为0
+fn seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0x9E37_79B9_7F4A_7C15);
+    hasher.finish() | 1
+}
+
+// 使用xorshift64*生成下一个伪随机数
+pub fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    })
+}
+
+// 随机化插入要在大小为s的子树处以1/(s+1)的概率做根插入
+// 取u在(0, 1]上均匀分布，该事件等价于s + 1 <= 1/u，因此返回floor(1/u)，子树大小小于它时做根插入
+// 这样判断时只需把子树数到这个随机阈值为止，期望代价为O(log s)
+pub fn root_insert_limit() -> usize {
+    let u = ((next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    (1.0 / u) as usize
+}
+
+// 用乘法把64位随机数x均匀映射到[0, n)
+pub fn below(x: u64, n: usize) -> usize {
+    ((x as u128 * n as u128) >> 64) as usize
+}
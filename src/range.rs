@@ -0,0 +1,34 @@
+use std::ops::{Bound, RangeBounds};
+
+// 判断key是否小于范围的下界
+pub fn below<K: PartialOrd, R: RangeBounds<K>>(key: &K, range: &R) -> bool {
+    match range.start_bound() {
+        Bound::Included(start) => *key < *start,
+        Bound::Excluded(start) => *key <= *start,
+        Bound::Unbounded => false,
+    }
+}
+
+// 判断key是否大于范围的上界
+pub fn above<K: PartialOrd, R: RangeBounds<K>>(key: &K, range: &R) -> bool {
+    match range.end_bound() {
+        Bound::Included(end) => *key > *end,
+        Bound::Excluded(end) => *key >= *end,
+        Bound::Unbounded => false,
+    }
+}
+
+// 把名次范围转换为左闭右开的区间[start, end)
+pub fn rank_window<R: RangeBounds<usize>>(range: &R) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => usize::MAX,
+    };
+    (start, end)
+}
@@ -0,0 +1,99 @@
+use std::iter;
+use crate::versioned::{Link, Node};
+
+/// `VersionedBSTree`的只读快照，与树共享节点，克隆只增加引用计数
+pub struct Snapshot<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> Clone for Snapshot<K, V> {
+    fn clone(&self) -> Self {
+        Snapshot {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: PartialOrd, V> Snapshot<K, V> {
+    pub(crate) fn new(root: Link<K, V>, len: usize) -> Self {
+        Snapshot { root, len }
+    }
+
+    /// 快照中键值对的个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.snapshot().len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 判断快照是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let tree: VersionedBSTree<i32, char> = VersionedBSTree::new();
+    /// assert!(tree.snapshot().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// 根据键查找快照中对应的值，找不到返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let snap = tree.snapshot();
+    /// tree.insert(1, 'z');
+    /// assert_eq!(snap.get(&1), Some(&'a'));
+    /// assert_eq!(snap.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if node.key < *key {
+                current = node.right.as_deref();
+            } else if node.key > *key {
+                current = node.left.as_deref();
+            } else {
+                return Some(&node.value);
+            }
+        }
+        None
+    }
+
+    /// 按键的升序遍历快照中的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let snap = tree.snapshot();
+    /// tree.remove(&1);
+    /// let res: Vec<(&i32, &char)> = snap.iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        // 栈中保存还没有访问的祖先节点，每次取出栈顶后把它右子树的左侧链压入栈中
+        let mut stack: Vec<&Node<K, V>> = Vec::new();
+        let mut current = self.root.as_deref();
+        iter::from_fn(move || {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            let node = stack.pop()?;
+            current = node.right.as_deref();
+            Some((&node.key, &node.value))
+        })
+    }
+}
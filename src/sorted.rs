@@ -0,0 +1,33 @@
+use std::cmp::Ordering;
+
+// 判断键值对是否已按键严格升序排列
+pub fn is_strictly_sorted<K: PartialOrd, V>(items: &[(K, V)]) -> bool {
+    items.windows(2).all(|w| w[0].0 < w[1].0)
+}
+
+// 将键值对按键升序排列并去重，键相同时保留最后出现的值，与逐个insert的效果一致
+// 输入已经严格升序时直接返回，不再排序
+pub fn sort_dedup<K: PartialOrd, V>(mut items: Vec<(K, V)>) -> Vec<(K, V)> {
+    if is_strictly_sorted(&items) {
+        return items;
+    }
+    // 稳定排序，相同的键保持原有的先后顺序
+    items.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    let mut res: Vec<(K, V)> = Vec::with_capacity(items.len());
+    for item in items {
+        match res.last_mut() {
+            Some(last) if last.0 == item.0 => *last = item,
+            _ => res.push(item),
+        }
+    }
+    res
+}
+
+// 将键按升序排列并去重
+pub fn sort_dedup_keys<K: PartialOrd>(mut keys: Vec<K>) -> Vec<K> {
+    if !keys.windows(2).all(|w| w[0] < w[1]) {
+        keys.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        keys.dedup_by(|a, b| a == b);
+    }
+    keys
+}
@@ -0,0 +1,167 @@
+use std::ops::{Add, Bound, RangeBounds};
+use crate::augmented::{AugTree, Augment, Link, Node};
+use crate::iterator::TraverseIter;
+use crate::range;
+
+// 以该节点为根的子树中全部值按键的升序相加的和
+#[derive(Default)]
+struct Sum<V>(V);
+
+impl<K, V: Add<Output = V> + Default + Clone> Augment<K, V> for Sum<V> {
+    fn update(node: &mut Node<K, V, Sum<V>>) {
+        node.aug = Sum(sum(&node.left) + node.value.clone() + sum(&node.right));
+    }
+}
+
+// 以link为根的子树中值的和，空树为V::default()
+fn sum<K, V: Default + Clone>(link: &Link<K, V, Sum<V>>) -> V {
+    link.as_ref().map_or_else(V::default, |node| node.aug.0.clone())
+}
+
+/// 每个节点保存子树中值的和的二叉查找树
+///
+/// 插入和删除沿查找路径重新计算子树和，`range_sum`对完全落在范围内的子树直接取用保存的和，
+/// 只需访问范围两条边界上的O(h)个节点。值按键的升序相加，要求加法满足结合律（例如各种数值类型）。
+/// 节点按随机秩保持堆序，期望高度为O(log n)。
+pub struct SumTree<K, V> {
+    tree: AugTree<K, V, Sum<V>>,
+}
+
+impl<K: PartialOrd + Clone, V: Add<Output = V> + Default + Clone> Default for SumTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Add<Output = V> + Default + Clone> SumTree<K, V> {
+    /// 构建一棵空的求和树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let tree: SumTree<i32, u64> = SumTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        SumTree { tree: AugTree::new() }
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值，沿途的子树和随之更新
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 5);
+    /// assert_eq!(tree.range_sum(..), 25);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tree.insert(key, value);
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(2, 20);
+    /// assert_eq!(tree.take(&1), Some((1, 10)));
+    /// assert_eq!(tree.take(&1), None);
+    /// assert_eq!(tree.range_sum(..), 20);
+    /// ```
+    pub fn take(&mut self, key: &K) -> Option<(K, V)> {
+        self.tree.take(key)
+    }
+
+    /// 根据键获取对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert_eq!(tree.get(&1), Some(&10));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    /// 查找是否存在键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.contains(key)
+    }
+
+    /// 对键在range范围内的值按键的升序求和，范围为空时返回V::default()；
+    /// 完全落在范围内的子树直接取用保存的和，只需O(h)时间，与范围内键值对的个数无关
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// for i in [5, 2, 8, 1, 3, 7, 9, 4, 6, 10] {
+    ///     tree.insert(i, i as f64 / 2.0);
+    /// }
+    /// assert_eq!(tree.range_sum(3..=5), 6.0);
+    /// assert_eq!(tree.range_sum(..), 27.5);
+    /// assert_eq!(tree.range_sum(20..), 0.0);
+    /// ```
+    pub fn range_sum<R: RangeBounds<K>>(&self, range: R) -> V {
+        let low_in = matches!(range.start_bound(), Bound::Unbounded);
+        let high_in = matches!(range.end_bound(), Bound::Unbounded);
+        Self::sum_range(&self.tree.root, &range, low_in, high_in)
+    }
+
+    // low_in、high_in表示子树中的键是否一定满足范围的下界、上界，两者都满足时直接返回子树和
+    fn sum_range<R: RangeBounds<K>>(link: &Link<K, V, Sum<V>>, range: &R, low_in: bool, high_in: bool) -> V {
+        let node = match link {
+            Some(node) => node,
+            None => return V::default(),
+        };
+        if low_in && high_in {
+            node.aug.0.clone()
+        } else if range::below(&node.key, range) {
+            Self::sum_range(&node.right, range, low_in, high_in)
+        } else if range::above(&node.key, range) {
+            Self::sum_range(&node.left, range, low_in, high_in)
+        } else {
+            // 当前节点在范围内，左子树的键都小于它，一定满足上界；右子树的键都大于它，一定满足下界
+            Self::sum_range(&node.left, range, low_in, true)
+                + node.value.clone()
+                + Self::sum_range(&node.right, range, true, high_in)
+        }
+    }
+
+    /// 中序遍历迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 10);
+    /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &10), (&2, &20)]);
+    /// ```
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.tree.inorder_iter()
+    }
+}
@@ -0,0 +1,32 @@
+// 结构性操作的tracing事件，开启tracing特性时发出，否则为空操作；
+// 事件的字段只在订阅者需要该事件时才会计算
+
+// 插入一个键，path_len为查找插入位置时经过的节点个数
+#[cfg(feature = "tracing")]
+pub(crate) fn insert<F: FnOnce() -> usize>(path_len: F) {
+    tracing::trace!(path_len = path_len(), "insert");
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn insert<F: FnOnce() -> usize>(_path_len: F) {}
+
+// 删除一个节点，case为删除时遇到的情况：leaf、one child或two children
+#[cfg(feature = "tracing")]
+pub(crate) fn delete(case: &'static str) {
+    tracing::trace!(case, "delete");
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn delete(_case: &'static str) {}
+
+// 由len个有序的键值对重建一棵平衡的树或子树，op为触发重建的操作
+#[cfg(feature = "tracing")]
+pub(crate) fn rebuild(op: &'static str, len: usize) {
+    tracing::debug!(op, len, "rebuild");
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn rebuild(_op: &'static str, _len: usize) {}
@@ -0,0 +1,42 @@
+#[cfg(feature = "debug-validate")]
+use std::cmp::Ordering;
+
+// 开启debug-validate特性的调试构建中，修改树结构的公开操作结束后都会检查查找树的性质
+pub(crate) const ENABLED: bool = cfg!(all(feature = "debug-validate", debug_assertions));
+
+// 检查按中序给出的键严格升序，否则panic，报告op、违反顺序的键的中序位置以及它和前一个键的关系；
+// 只用到键的比较，不要求键实现Debug，开启特性不会给键增加额外的约束
+#[cfg(feature = "debug-validate")]
+pub(crate) fn check_order<'a, K, I>(mut keys: I, op: &str)
+where
+    K: PartialOrd + 'a,
+    I: Iterator<Item = &'a K>,
+{
+    let mut prev = match keys.next() {
+        Some(key) => key,
+        None => return,
+    };
+    for (index, key) in keys.enumerate().map(|(i, key)| (i + 1, key)) {
+        let violation = match prev.partial_cmp(key) {
+            Some(Ordering::Less) => {
+                prev = key;
+                continue;
+            }
+            Some(Ordering::Equal) => "equal to",
+            Some(Ordering::Greater) => "less than",
+            None => "not comparable with",
+        };
+        panic!(
+            "binary search tree invariant violated after `{}`: the key at in-order index {} is {} its in-order predecessor",
+            op, index, violation
+        );
+    }
+}
+
+#[cfg(not(feature = "debug-validate"))]
+pub(crate) fn check_order<'a, K, I>(_keys: I, _op: &str)
+where
+    K: PartialOrd + 'a,
+    I: Iterator<Item = &'a K>,
+{
+}
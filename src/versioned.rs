@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use crate::iterator::TraverseIter;
+use crate::snapshot::Snapshot;
+
+// 节点由Arc共享，快照可以交给其他线程
+pub(crate) type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+pub(crate) struct Node<K, V> {
+    pub(crate) key: K,
+    pub(crate) value: V,
+    pub(crate) left: Link<K, V>,
+    pub(crate) right: Link<K, V>,
+}
+
+/// 保存历史版本的二叉查找树，可以查询任意已提交版本中的内容
+///
+/// 节点是不可变的，修改时只复制从根到被修改节点的路径，其余子树在各个版本之间共享，
+/// 因此每次修改只需O(h)的额外空间。`commit`把当前内容记录为一个新版本。
+pub struct VersionedBSTree<K, V> {
+    // 当前正在修改的版本
+    root: Link<K, V>,
+    // 当前版本中键值对的个数
+    len: usize,
+    // 已提交的各个版本的根节点，下标即版本号
+    versions: Vec<Link<K, V>>,
+}
+
+impl<K: PartialOrd + Clone, V: Clone> Default for VersionedBSTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone> VersionedBSTree<K, V> {
+    /// 构建一棵空的版本树，还没有任何已提交的版本
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let tree: VersionedBSTree<i32, char> = VersionedBSTree::new();
+    /// assert!(tree.is_empty());
+    /// assert_eq!(tree.version_count(), 0);
+    /// ```
+    pub fn new() -> Self {
+        VersionedBSTree {
+            root: None,
+            len: 0,
+            versions: Vec::new(),
+        }
+    }
+
+    /// 判断当前版本是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// 返回当前版本中键值对的个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(1, 'b');
+    /// tree.insert(2, 'c');
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 向当前版本插入键值对，如果键已经存在，则替换旧值为新值，已提交的版本不受影响
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let v0 = tree.commit();
+    /// tree.insert(1, 'b');
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// assert_eq!(tree.get_at(v0, &1), Some(&'a'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        if Self::search(&self.root, &key).is_none() {
+            self.len += 1;
+        }
+        self.root = Some(Self::insert_link(&self.root, key, value));
+    }
+
+    // 复制查找路径上的节点，返回插入后的新根节点
+    fn insert_link(link: &Link<K, V>, key: K, value: V) -> Arc<Node<K, V>> {
+        match link {
+            None => Arc::new(Node {
+                key,
+                value,
+                left: None,
+                right: None,
+            }),
+            Some(node) => {
+                let (left, right, value) = if key < node.key {
+                    (Some(Self::insert_link(&node.left, key, value)), node.right.clone(), node.value.clone())
+                } else if key > node.key {
+                    (node.left.clone(), Some(Self::insert_link(&node.right, key, value)), node.value.clone())
+                } else {
+                    (node.left.clone(), node.right.clone(), value)
+                };
+                Arc::new(Node {
+                    key: node.key.clone(),
+                    value,
+                    left,
+                    right,
+                })
+            }
+        }
+    }
+
+    /// 从当前版本删除键值对，并返回被删除的值，如果找不到键值对，则返回None，已提交的版本不受影响
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let v0 = tree.commit();
+    /// assert_eq!(tree.remove(&1), Some('a'));
+    /// assert_eq!(tree.remove(&1), None);
+    /// assert_eq!(tree.get_at(v0, &1), Some(&'a'));
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.get(key)?.clone();
+        self.root = Self::remove_link(&self.root, key);
+        self.len -= 1;
+        Some(value)
+    }
+
+    // 复制查找路径上的节点，返回删除key后的新根节点，调用者需保证key在树中
+    fn remove_link(link: &Link<K, V>, key: &K) -> Link<K, V> {
+        let node = link.as_ref()?;
+        let (key, value, left, right) = if *key < node.key {
+            (node.key.clone(), node.value.clone(), Self::remove_link(&node.left, key), node.right.clone())
+        } else if *key > node.key {
+            (node.key.clone(), node.value.clone(), node.left.clone(), Self::remove_link(&node.right, key))
+        } else {
+            // 删除当前节点，左右子树都存在时用右子树中的最小节点代替它
+            let right = match &node.right {
+                None => return node.left.clone(),
+                Some(right) => right,
+            };
+            if node.left.is_none() {
+                return node.right.clone();
+            }
+            let mut min = right;
+            while let Some(left) = &min.left {
+                min = left;
+            }
+            (min.key.clone(), min.value.clone(), node.left.clone(), Self::remove_min(right))
+        };
+        Some(Arc::new(Node {
+            key,
+            value,
+            left,
+            right,
+        }))
+    }
+
+    // 复制最左侧路径上的节点，返回删除最小节点后的新根节点
+    fn remove_min(node: &Arc<Node<K, V>>) -> Link<K, V> {
+        match &node.left {
+            None => node.right.clone(),
+            Some(left) => Some(Arc::new(Node {
+                key: node.key.clone(),
+                value: node.value.clone(),
+                left: Self::remove_min(left),
+                right: node.right.clone(),
+            })),
+        }
+    }
+
+    /// 根据键获取当前版本中对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        Self::search(&self.root, key)
+    }
+
+    // 在以link为根的树中查找key对应的值
+    fn search<'a>(link: &'a Link<K, V>, key: &K) -> Option<&'a V> {
+        let mut current = link.as_ref();
+        while let Some(node) = current {
+            if node.key < *key {
+                current = node.right.as_ref();
+            } else if node.key > *key {
+                current = node.left.as_ref();
+            } else {
+                return Some(&node.value);
+            }
+        }
+        None
+    }
+
+    /// 把当前内容记录为一个新版本，返回它的版本号，版本号从0开始依次递增；
+    /// 新版本与当前版本共享全部节点，不需要复制
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// assert_eq!(tree.commit(), 0);
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.commit(), 1);
+    /// assert_eq!(tree.version_count(), 2);
+    /// ```
+    pub fn commit(&mut self) -> usize {
+        self.versions.push(self.root.clone());
+        self.versions.len() - 1
+    }
+
+    /// 返回当前版本的只读快照，快照与树共享全部节点，之后的修改只复制路径，不影响快照
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(1, 'a');
+    /// let snap = tree.snapshot();
+    /// tree.insert(1, 'b');
+    /// tree.insert(2, 'c');
+    /// assert_eq!(snap.get(&1), Some(&'a'));
+    /// assert_eq!(snap.len(), 1);
+    /// ```
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        Snapshot::new(self.root.clone(), self.len)
+    }
+
+    /// 返回已提交的版本个数
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree: VersionedBSTree<i32, char> = VersionedBSTree::new();
+    /// tree.commit();
+    /// assert_eq!(tree.version_count(), 1);
+    /// ```
+    pub fn version_count(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// 根据键获取版本version中对应的值
+    /// # Panics
+    /// version不是已提交的版本号时panic
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// let v0 = tree.commit();
+    /// tree.insert(1, 'a');
+    /// let v1 = tree.commit();
+    /// assert_eq!(tree.get_at(v0, &1), None);
+    /// assert_eq!(tree.get_at(v1, &1), Some(&'a'));
+    /// ```
+    pub fn get_at(&self, version: usize, key: &K) -> Option<&V> {
+        Self::search(self.version(version), key)
+    }
+
+    /// 按键的升序遍历版本version中的键值对
+    /// # Panics
+    /// version不是已提交的版本号时panic
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let v0 = tree.commit();
+    /// tree.remove(&2);
+    /// let res: Vec<(&i32, &char)> = tree.iter_at(v0).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn iter_at(&self, version: usize) -> TraverseIter<'_, K, V> {
+        Self::in_order(self.version(version))
+    }
+
+    /// 按键的升序遍历当前版本中的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::VersionedBSTree;
+    /// let mut tree = VersionedBSTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        Self::in_order(&self.root)
+    }
+
+    // 返回版本version的根节点
+    fn version(&self, version: usize) -> &Link<K, V> {
+        match self.versions.get(version) {
+            Some(root) => root,
+            None => panic!("version {} has not been committed", version),
+        }
+    }
+
+    // 按中序收集以link为根的树中的键值对
+    fn in_order(link: &Link<K, V>) -> TraverseIter<'_, K, V> {
+        let mut queue = VecDeque::new();
+        let mut stack = Vec::new();
+        let mut current = link.as_ref();
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_ref();
+            }
+            if let Some(node) = stack.pop() {
+                queue.push_back((&node.key, &node.value));
+                current = node.right.as_ref();
+            }
+        }
+        TraverseIter::new(queue)
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use crate::framing::{push_field, split_field, split_sep, utf8_prefix, Malformed};
+use crate::{IterativeBSTree, RecursiveBSTree};
+
+/// 预写日志的写入端，把插入和删除操作依次追加到日志中，配合`BSTree::replay`在重启后重建树
+///
+/// 每个操作是一条以换行结束的记录：插入写为`+ 键 值`，删除写为`- 键`，其中键和值都写成`字节数:文本`，
+/// 文本中可以包含空格和换行。每条记录通过一次`write_all`写入；写入文件时在提交点调用`commit`落盘，
+/// 之前追加的记录在断电后才不会丢失。
+pub struct WalWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> WalWriter<W> {
+    /// 在writer上构建日志写入端，writer通常是以追加方式打开的文件
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::WalWriter;
+    /// let wal = WalWriter::new(Vec::new());
+    /// assert!(wal.into_inner().is_empty());
+    /// ```
+    pub fn new(writer: W) -> Self {
+        WalWriter { writer }
+    }
+
+    /// 记录一次插入操作
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::WalWriter;
+    /// let mut wal = WalWriter::new(Vec::new());
+    /// wal.log_insert(&1, &"one").unwrap();
+    /// assert_eq!(wal.into_inner(), b"+ 1:1 3:one\n");
+    /// ```
+    pub fn log_insert<K: fmt::Display, V: fmt::Display>(&mut self, key: &K, value: &V) -> io::Result<()> {
+        let mut record = String::from("+ ");
+        push_field(&mut record, &key.to_string());
+        record.push(' ');
+        push_field(&mut record, &value.to_string());
+        record.push('\n');
+        self.writer.write_all(record.as_bytes())
+    }
+
+    /// 记录一次删除操作
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::WalWriter;
+    /// let mut wal = WalWriter::new(Vec::new());
+    /// wal.log_delete(&1).unwrap();
+    /// assert_eq!(wal.into_inner(), b"- 1:1\n");
+    /// ```
+    pub fn log_delete<K: fmt::Display>(&mut self, key: &K) -> io::Result<()> {
+        let mut record = String::from("- ");
+        push_field(&mut record, &key.to_string());
+        record.push('\n');
+        self.writer.write_all(record.as_bytes())
+    }
+
+    /// 把缓冲的日志写入底层的writer
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::WalWriter;
+    /// let mut wal = WalWriter::new(Vec::new());
+    /// wal.log_delete(&1).unwrap();
+    /// wal.flush().unwrap();
+    /// ```
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// 消耗写入端，返回底层的writer
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::WalWriter;
+    /// let wal = WalWriter::new(Vec::<u8>::new());
+    /// let buf: Vec<u8> = wal.into_inner();
+    /// assert!(buf.is_empty());
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl WalWriter<File> {
+    /// 提交点：把缓冲的日志写入文件并调用`sync_data`落盘，返回后之前追加的操作在崩溃或断电后都能重放
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::WalWriter;
+    /// let path = std::env::temp_dir().join("an_ok_bstree_wal_commit.log");
+    /// let mut wal = WalWriter::new(std::fs::File::create(&path).unwrap());
+    /// wal.log_insert(&1, &10).unwrap();
+    /// wal.commit().unwrap();
+    /// assert_eq!(std::fs::read(&path).unwrap(), b"+ 1:1 2:10\n");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn commit(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.sync_data()
+    }
+}
+
+// 日志中的一条记录
+enum Record<'a> {
+    Insert(&'a str, &'a str),
+    Delete(&'a str),
+}
+
+// 读出一条记录，返回记录和之后剩余的输入
+fn split_record(input: &str) -> Result<(Record<'_>, &str), Malformed> {
+    let (op, rest) = match input.char_indices().nth(1) {
+        Some((i, _)) => input.split_at(i),
+        None => return Err(Malformed::Truncated),
+    };
+    let rest = split_sep(rest, ' ')?;
+    let (key, rest) = split_field(rest)?;
+    match op {
+        "+" => {
+            let (value, rest) = split_field(split_sep(rest, ' ')?)?;
+            Ok((Record::Insert(key, value), split_sep(rest, '\n')?))
+        }
+        "-" => Ok((Record::Delete(key), split_sep(rest, '\n')?)),
+        _ => Err(Malformed::Invalid),
+    }
+}
+
+// 为两种实现的树分别实现日志重放
+macro_rules! impl_replay {
+    ($tree:ident) => {
+        impl<K: PartialOrd + Clone, V> $tree<K, V> {
+            /// 从空树开始依次重放日志log中的操作，重建崩溃前的树；
+            /// 末尾不完整的记录视为写入中断而忽略，其余不合法的记录返回`InvalidData`错误
+            /// # Example
+            /// ```
+            /// use an_ok_bstree::{BSTree, WalWriter};
+            /// let mut wal = WalWriter::new(Vec::new());
+            /// wal.log_insert(&1, &10).unwrap();
+            /// wal.log_insert(&2, &20).unwrap();
+            /// wal.log_delete(&1).unwrap();
+            /// let mut log = wal.into_inner();
+            /// log.extend_from_slice(b"+ 1:3 1:3");
+            /// let tree: BSTree<i32, i32> = BSTree::replay(&log[..]).unwrap();
+            /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+            /// assert_eq!(res, vec![(&2, &20)]);
+            /// ```
+            pub fn replay<R: BufRead>(log: R) -> io::Result<Self>
+            where
+                K: FromStr,
+                V: FromStr,
+            {
+                let mut tree = $tree::new();
+                tree.replay_into(log)?;
+                Ok(tree)
+            }
+
+            /// 在当前树上依次重放日志log中的操作，用于从快照加上之后的日志恢复
+            /// # Example
+            /// ```
+            /// use an_ok_bstree::BSTree;
+            /// let mut tree = BSTree::new();
+            /// tree.insert(1, 10);
+            /// tree.replay_into(&b"+ 1:2 2:20\n- 1:1\n"[..]).unwrap();
+            /// let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+            /// assert_eq!(res, vec![(&2, &20)]);
+            /// assert!(tree.replay_into(&b"* 1:2\n"[..]).is_err());
+            /// ```
+            pub fn replay_into<R: BufRead>(&mut self, mut log: R) -> io::Result<()>
+            where
+                K: FromStr,
+                V: FromStr,
+            {
+                let mut bytes = Vec::new();
+                log.read_to_end(&mut bytes)?;
+                let text = utf8_prefix(&bytes)?;
+                let mut rest = text;
+                while !rest.is_empty() {
+                    let offset = text.len() - rest.len();
+                    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid log record at byte {}", offset));
+                    let (record, next) = match split_record(rest) {
+                        Ok(split) => split,
+                        Err(Malformed::Truncated) => return Ok(()),
+                        Err(Malformed::Invalid) => return Err(invalid()),
+                    };
+                    match record {
+                        Record::Insert(key, value) => {
+                            let key = key.parse().map_err(|_| invalid())?;
+                            let value = value.parse().map_err(|_| invalid())?;
+                            self.insert(key, value);
+                        }
+                        Record::Delete(key) => {
+                            let key: K = key.parse().map_err(|_| invalid())?;
+                            self.take(&key);
+                        }
+                    }
+                    rest = next;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_replay!(RecursiveBSTree);
+impl_replay!(IterativeBSTree);
@@ -0,0 +1,33 @@
+/// 遍历的顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// 前序遍历
+    PreOrder,
+    /// 中序遍历
+    InOrder,
+    /// 后序遍历
+    PostOrder,
+    /// 层序遍历
+    LevelOrder,
+    /// 之字形层序遍历，偶数层从左到右，奇数层从右到左，根节点在第0层
+    ZigZag,
+}
+
+/// `walk`的回调继续遍历时，决定是否访问当前节点的子树
+///
+/// 回调返回`ControlFlow::Continue(())`等同于`Visit::Children`。
+/// 前序、层序和之字形层序遍历中跳过当前节点的全部子树；中序遍历中左子树已经访问过，只跳过右子树；
+/// 后序遍历中子树都已访问过，跳过不起作用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// 继续访问子树
+    Children,
+    /// 跳过子树
+    SkipChildren,
+}
+
+impl From<()> for Visit {
+    fn from(_: ()) -> Self {
+        Visit::Children
+    }
+}
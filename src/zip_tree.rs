@@ -0,0 +1,162 @@
+use crate::augmented::AugTree;
+use crate::iterator::TraverseIter;
+
+/// 拉链树，按随机秩维护堆序的二叉查找树，是树堆的一种轻量替代
+///
+/// 每个节点带有一个几何分布的随机秩，左孩子的秩严格小于父节点，右孩子的秩不大于父节点，
+/// 插入和删除都通过`unzip`（拆分）和`zip`（拼接）完成而不需要旋转，期望高度为O(log n)。
+pub struct ZipTree<K, V> {
+    tree: AugTree<K, V, ()>,
+}
+
+impl<K: PartialOrd + Clone, V> Default for ZipTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V> ZipTree<K, V> {
+    /// 构建一棵空的拉链树
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let tree: ZipTree<i32, char> = ZipTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        ZipTree { tree: AugTree::new() }
+    }
+
+    /// 判断当前树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(1, 'a');
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 向树中插入键值对，如果键已经存在，则替换旧值为新值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(1, 'b');
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tree.insert(key, value);
+    }
+
+    /// 从树中删除键值对，并返回被删除的键和值，如果找不到键值对，则返回None
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.take(1), Some((1, 'a')));
+    /// assert_eq!(tree.take(1), None);
+    /// ```
+    pub fn take(&mut self, key: K) -> Option<(K, V)> {
+        self.tree.take(&key)
+    }
+
+    /// 根据键获取对应的值
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    /// 根据键获取对应值的可变引用
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(1, 10);
+    /// *tree.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    /// 查找是否存在键值对
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(1, 'a');
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.contains(key)
+    }
+
+    /// 按key把树拆成两棵，第一棵包含小于key的键，第二棵包含不小于key的键，只需O(h)时间
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// for i in 0..6 {
+    ///     tree.insert(i, i);
+    /// }
+    /// let (low, high) = tree.unzip(&4);
+    /// let res: Vec<&i32> = low.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&0, &1, &2, &3]);
+    /// let res: Vec<&i32> = high.inorder_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(res, vec![&4, &5]);
+    /// ```
+    pub fn unzip(self, key: &K) -> (Self, Self) {
+        let (left, right) = self.tree.unzip(key);
+        (ZipTree { tree: left }, ZipTree { tree: right })
+    }
+
+    /// 把other拼接到当前树的右侧，只需O(h)时间，要求当前树中的键都小于other中的键
+    /// # Panics
+    /// 当前树的最大键不小于other的最小键时panic
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut low = ZipTree::new();
+    /// low.insert(1, 'a');
+    /// let mut high = ZipTree::new();
+    /// high.insert(2, 'b');
+    /// let tree = low.zip(high);
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn zip(self, other: Self) -> Self {
+        if let (Some(max), Some(min)) = (self.tree.max_key(), other.tree.min_key()) {
+            assert!(max < min, "all keys of the left tree must be smaller than the right tree");
+        }
+        ZipTree {
+            tree: self.tree.zip(other.tree),
+        }
+    }
+
+    /// 中序遍历迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_bstree::ZipTree;
+    /// let mut tree = ZipTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        self.tree.inorder_iter()
+    }
+}
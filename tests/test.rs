@@ -1,6 +1,12 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
 #[cfg(test)]
 mod tests {
-    use an_ok_bstree::BSTree;
+    use an_ok_bstree::{ArenaBSTree, BSTree, BinarySearchTree, BoundedBSTree, Change, DecodeError, Eviction, GetManyMutError, IterativeBSTree, JournaledBSTree, KeepOld, KeyNotFound, LazyTree, MerkleBSTree, MultiBSTree, OccupiedError, Order, OrderStatTree, Overwrite, RecursiveBSTree, RekeyError, SumTree, Tie, VersionedBSTree, Visit, ZipTree};
+    use std::cmp::Reverse;
+    use std::collections::BTreeMap;
+    use std::ops::{Bound, ControlFlow};
+    use std::panic::{self, AssertUnwindSafe};
 
     /*
                 10
@@ -68,12 +74,13 @@ mod tests {
         tree.insert(17, 'q');
         tree.insert(10, 'j');
         tree.insert(14, 'n');
-        tree.delete_tree(2);
+        assert_eq!(tree.delete_tree(2), 1);
         assert!(!tree.contains(&2));
-        tree.delete_tree(5);
+        assert_eq!(tree.delete_tree(5), 5);
+        assert_eq!(tree.delete_tree(100), 0);
         let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
         assert_eq!(res, vec![(&8, &'h'), (&10, &'j'), (&12, &'l'), (&14, &'n'), (&15, &'o'), (&17, &'q')]);
-        tree.delete_tree(8);
+        assert_eq!(tree.delete_tree(8), 6);
         assert!(tree.is_empty());
     }
 
@@ -219,4 +226,2651 @@ mod tests {
         let res: Vec<(&i32, &char)> = tree.levelorder_iter().collect();
         assert_eq!(res, vec![(&3, &'c'), (&2, &'b'), (&4, &'d'), (&1, &'a')]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn bulk_load_extend_sorted() {
+        let tree = BSTree::bulk_load((1..=7).map(|i| (i, i * 10)));
+        let res: Vec<(&i32, &i32)> = tree.preorder_iter().collect();
+        assert_eq!(res, vec![(&4, &40), (&2, &20), (&1, &10), (&3, &30), (&6, &60), (&5, &50), (&7, &70)]);
+
+        let tree = BSTree::bulk_load(vec![(3, 'c'), (1, 'a'), (2, 'b'), (1, 'z')]);
+        let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'b'), (&1, &'z'), (&3, &'c')]);
+        assert!(BSTree::<i32, i32>::bulk_load(vec![]).is_empty());
+
+        let mut tree = BSTree::bulk_load(vec![(1, 'a'), (2, 'b'), (3, 'c')]);
+        tree.extend_sorted(vec![(4, 'd'), (5, 'e'), (6, 'f')]);
+        let res: Vec<(&i32, &char)> = tree.levelorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c'), (&5, &'e'), (&4, &'d'), (&6, &'f')]);
+        tree.extend_sorted(vec![(0, 'x'), (7, 'g')]);
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&0, &'x'), (&1, &'a'), (&2, &'b'), (&3, &'c'), (&4, &'d'), (&5, &'e'), (&6, &'f'), (&7, &'g')]);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut tree = BSTree::new();
+        tree.insert(8, 'h');
+        tree.insert(5, 'e');
+        tree.insert(15, 'o');
+        assert_eq!(tree.try_insert(3, 'c'), Ok(&mut 'c'));
+        if let Ok(value) = tree.try_insert(17, 'x') {
+            *value = 'q';
+        }
+        assert_eq!(tree.get(&17), Some(&'q'));
+        let err = tree.try_insert(5, 'y').unwrap_err();
+        assert_eq!(err, OccupiedError { key: 5, value: 'y' });
+        assert_eq!(err.to_string(), "key 5 already exists, failed to insert value 'y'");
+        assert_eq!(tree.get(&5), Some(&'e'));
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&3, &'c'), (&5, &'e'), (&8, &'h'), (&15, &'o'), (&17, &'q')]);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut tree = BSTree::new();
+        for word in "b a c a b a".split(' ') {
+            *tree.get_or_insert_with(word, || 0) += 1;
+        }
+        let res: Vec<(&&str, &i32)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&"a", &3), (&"b", &2), (&"c", &1)]);
+        let mut called = false;
+        tree.get_or_insert_with("a", || {
+            called = true;
+            0
+        });
+        assert!(!called);
+    }
+
+    #[test]
+    fn replace() {
+        // 只比较长度的键，用来模拟“相等但不相同”的键
+        #[derive(Clone, Debug)]
+        struct Name(&'static str);
+        impl PartialEq for Name {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.len() == other.0.len()
+            }
+        }
+        impl PartialOrd for Name {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.0.len().partial_cmp(&other.0.len())
+            }
+        }
+        let mut tree = BSTree::new();
+        assert!(tree.replace(Name("bob"), 1).is_none());
+        assert!(tree.replace(Name("alice"), 2).is_none());
+        let (old_key, old_value) = tree.replace(Name("tom"), 3).unwrap();
+        assert_eq!((old_key.0, old_value), ("bob", 1));
+        let res: Vec<(&'static str, i32)> = tree.inorder_iter().map(|(k, v)| (k.0, *v)).collect();
+        assert_eq!(res, vec![("tom", 3), ("alice", 2)]);
+    }
+
+    #[test]
+    fn take() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.take(&5), Some((5, 'e')));
+        assert_eq!(tree.take(&8), Some((8, 'h')));
+        assert_eq!(tree.take(&7), Some((7, 'g')));
+        assert_eq!(tree.take(&100), None);
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&3, &'c'), (&6, &'f'), (&12, &'l'), (&15, &'o'), (&17, &'q')]);
+    }
+
+    #[test]
+    fn first_last_entry() {
+        let mut tree = BSTree::new();
+        assert!(tree.first_entry().is_none());
+        assert_eq!(tree.last(), None);
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (4, 'd'), (15, 'o'), (17, 'q'), (16, 'p')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.first(), Some((&3, &'c')));
+        assert_eq!(tree.last(), Some((&17, &'q')));
+        let entry = tree.first_entry().unwrap();
+        assert_eq!((entry.key(), entry.get()), (&3, &'c'));
+        *entry.into_mut() = 'C';
+        assert_eq!(tree.first_entry().map(|e| e.remove_entry()), Some((3, 'C')));
+        assert_eq!(tree.first(), Some((&4, &'d')));
+        assert_eq!(tree.last_entry().map(|e| e.remove()), Some('q'));
+        assert_eq!(tree.last(), Some((&16, &'p')));
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&4, &'d'), (&5, &'e'), (&8, &'h'), (&15, &'o'), (&16, &'p')]);
+    }
+
+    #[test]
+    fn remove_range() {
+        /*
+                8
+               / \
+             5    15
+            / \   / \
+          3   6  12  17
+         / \   \  / \
+        2  4   7 10 14
+
+    */
+        let mut tree = BSTree::new();
+        tree.insert(8, 'h');
+        tree.insert(5, 'e');
+        tree.insert(3, 'c');
+        tree.insert(2, 'b');
+        tree.insert(4, 'd');
+        tree.insert(6, 'f');
+        tree.insert(7, 'g');
+        tree.insert(15, 'o');
+        tree.insert(12, 'l');
+        tree.insert(17, 'q');
+        tree.insert(10, 'j');
+        tree.insert(14, 'n');
+        assert_eq!(tree.remove_range(4..=10), vec![(4, 'd'), (5, 'e'), (6, 'f'), (7, 'g'), (8, 'h'), (10, 'j')]);
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'b'), (&3, &'c'), (&12, &'l'), (&14, &'n'), (&15, &'o'), (&17, &'q')]);
+        assert!(tree.remove_range(20..).is_empty());
+        assert_eq!(tree.remove_range(15..), vec![(15, 'o'), (17, 'q')]);
+        assert_eq!(tree.max_pair(), Some((&14, &'n')));
+        assert_eq!(tree.remove_range(..).len(), 4);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn nearest() {
+        let mut tree = BSTree::new();
+        assert_eq!(tree.nearest(&1), None);
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.nearest(&0), Some((&3, &'c')));
+        assert_eq!(tree.nearest(&5), Some((&5, &'e')));
+        assert_eq!(tree.nearest(&10), Some((&8, &'h')));
+        assert_eq!(tree.nearest(&11), Some((&12, &'l')));
+        assert_eq!(tree.nearest(&100), Some((&17, &'q')));
+        // 距离的平方作为自定义距离
+        assert_eq!(tree.nearest_by(&14, |a, b| (a - b) * (a - b)), Some((&15, &'o')));
+
+        let mut samples = BSTree::new();
+        samples.insert(0.5, "a");
+        samples.insert(1.5, "b");
+        assert_eq!(samples.nearest(&1.2), Some((&1.5, &"b")));
+    }
+
+    #[test]
+    fn nearest_k() {
+        /*
+                8
+               / \
+             5    15
+            / \   / \
+          3   6  12  17
+         / \   \  / \
+        2  4   7 10 14
+
+    */
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let res: Vec<&i32> = tree.nearest_k(&11, 4).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&10, &12, &8, &14]);
+        let res: Vec<&i32> = tree.nearest_k(&5, 3).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&5, &4, &6]);
+        let res: Vec<&i32> = tree.nearest_k(&100, 2).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&17, &15]);
+        assert_eq!(tree.nearest_k(&0, 100).count(), 12);
+        assert_eq!(tree.nearest_k(&0, 0).count(), 0);
+    }
+
+    #[test]
+    fn map_values() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (6, 'f'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        let mut visited = Vec::new();
+        let mapped = tree.map_values(|k, v| {
+            visited.push(*k);
+            v.to_ascii_uppercase()
+        });
+        assert_eq!(visited, vec![3, 5, 6, 8, 12, 15, 17]);
+        let res: Vec<(&i32, &char)> = mapped.levelorder_iter().collect();
+        assert_eq!(res, vec![(&8, &'H'), (&5, &'E'), (&15, &'O'), (&3, &'C'), (&6, &'F'), (&12, &'L'), (&17, &'Q')]);
+        assert!(BSTree::<i32, i32>::new().map_values(|_, v| v + 1).is_empty());
+    }
+
+    #[test]
+    fn merge_with() {
+        let mut shard1 = BSTree::new();
+        let mut shard2 = BSTree::new();
+        for (k, v) in [(8, 1), (5, 1), (3, 2), (15, 4)] {
+            shard1.insert(k, v);
+        }
+        for (k, v) in [(5, 10), (2, 20), (15, 30), (17, 40)] {
+            shard2.insert(k, v);
+        }
+        let mut collisions = Vec::new();
+        shard1.merge_with(shard2, |k, a, b| {
+            collisions.push(*k);
+            a + b
+        });
+        assert_eq!(collisions, vec![5, 15]);
+        let res: Vec<(&i32, &i32)> = shard1.inorder_iter().collect();
+        assert_eq!(res, vec![(&2, &20), (&3, &2), (&5, &11), (&8, &1), (&15, &34), (&17, &40)]);
+        let mut empty = BSTree::new();
+        empty.merge_with(shard1, |_, a, _| a);
+        assert_eq!(empty.get(&15), Some(&34));
+    }
+
+    #[test]
+    fn levels() {
+        let mut tree = BSTree::new();
+        assert!(tree.levels().is_empty());
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (6, 'f'), (7, 'g'), (15, 'o'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        let keys: Vec<Vec<i32>> = tree
+            .levels()
+            .into_iter()
+            .map(|level| level.into_iter().map(|(k, _)| *k).collect())
+            .collect();
+        assert_eq!(keys, vec![vec![8], vec![5, 15], vec![3, 6, 17], vec![2, 7]]);
+    }
+
+    #[test]
+    fn depth_iter() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (6, 'f'), (7, 'g'), (15, 'o'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        let res: Vec<(usize, &i32)> = tree.depth_iter().map(|(d, k, _)| (d, k)).collect();
+        assert_eq!(res, vec![(0, &8), (1, &5), (2, &3), (2, &6), (3, &7), (1, &15), (2, &17)]);
+        let lines: Vec<String> = tree
+            .depth_iter()
+            .map(|(d, k, v)| format!("{}{}:{}", "  ".repeat(d), k, v))
+            .collect();
+        assert_eq!(lines[4], "      7:g");
+    }
+
+    #[test]
+    fn subtree_iter() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        let res: Vec<&i32> = tree.subtree_iter(&5).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&2, &3, &4, &5, &6, &7]);
+        let res: Vec<&i32> = tree.subtree_iter(&15).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&12, &15, &17]);
+        assert_eq!(tree.subtree_iter(&8).count(), 10);
+        assert_eq!(tree.subtree_iter(&9).count(), 0);
+        assert_eq!(tree.inorder_iter().count(), 10);
+    }
+
+    #[test]
+    fn prune() {
+        /*
+                8
+               / \
+             5    15
+            / \   / \
+          3   6  12  17
+         / \   \  / \
+        2  4   7 10 14
+
+    */
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let mut visited = Vec::new();
+        let removed = tree.prune(|k, _| {
+            visited.push(*k);
+            *k == 3 || *k == 12 || *k == 14
+        });
+        assert_eq!(removed, 6);
+        assert_eq!(visited, vec![8, 5, 3, 6, 7, 15, 12, 17]);
+        let res: Vec<&i32> = tree.inorder_iter().map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&5, &6, &7, &8, &15, &17]);
+        assert_eq!(tree.prune(|_, _| false), 0);
+        assert_eq!(tree.prune(|_, _| true), 6);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn partition() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        let (low, high) = tree.partition(|k, _| *k < 6);
+        let res: Vec<(&i32, &char)> = low.inorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'b'), (&3, &'c'), (&4, &'d'), (&5, &'e')]);
+        let res: Vec<(&i32, &char)> = high.inorder_iter().collect();
+        assert_eq!(res, vec![(&6, &'f'), (&7, &'g'), (&8, &'h'), (&12, &'l'), (&15, &'o'), (&17, &'q')]);
+        let (all, none) = high.partition(|_, v| v.is_alphabetic());
+        assert_eq!(all.inorder_iter().count(), 6);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn symmetric_difference() {
+        let mut a = BSTree::new();
+        let mut b = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (15, 'o'), (12, 'l')] {
+            a.insert(k, v);
+        }
+        for (k, v) in [(6, 'F'), (5, 'E'), (17, 'Q'), (12, 'L'), (1, 'A')] {
+            b.insert(k, v);
+        }
+        let diff = a.symmetric_difference(&b);
+        let res: Vec<(&i32, &char)> = diff.inorder_iter().collect();
+        assert_eq!(res, vec![(&1, &'A'), (&3, &'c'), (&6, &'F'), (&8, &'h'), (&15, &'o'), (&17, &'Q')]);
+        assert!(a.symmetric_difference(&a).is_empty());
+        let empty = BSTree::new();
+        assert_eq!(b.symmetric_difference(&empty).inorder_iter().count(), 5);
+    }
+
+    #[test]
+    fn subset_superset() {
+        let mut replica = BSTree::new();
+        let mut primary = BSTree::new();
+        for k in [8, 5, 3, 15, 12] {
+            replica.insert(k, ());
+        }
+        for k in [8, 5, 3, 2, 4, 6, 15, 12, 17] {
+            primary.insert(k, k * 10);
+        }
+        assert!(replica.is_subset(&primary));
+        assert!(primary.is_superset(&replica));
+        assert!(!primary.is_subset(&replica));
+        replica.insert(100, ());
+        assert!(!replica.is_subset(&primary));
+        replica.delete(100);
+        replica.insert(7, ());
+        assert!(!replica.is_subset(&primary));
+        let empty: BSTree<i32, ()> = BSTree::new();
+        assert!(empty.is_subset(&replica));
+        assert!(replica.is_superset(&empty));
+        assert!(replica.is_subset(&replica));
+    }
+
+    #[test]
+    fn set_operations_walk_nodes() {
+        use std::cell::Cell;
+        use std::cmp::Ordering;
+
+        thread_local!(static COMPARISONS: Cell<usize> = const { Cell::new(0) });
+
+        // 统计比较次数的键：同时遍历两棵树时每个键只需常数次比较，逐个查找则需要O(h)次
+        #[derive(Clone, PartialEq)]
+        struct Counted(i32);
+
+        impl PartialOrd for Counted {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                COMPARISONS.with(|c| c.set(c.get() + 1));
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        let n = 1024;
+        let a = BSTree::bulk_load((0..n).map(|i| (Counted(i), i)));
+        let b = BSTree::bulk_load((0..n).map(|i| (Counted(i), i)));
+        let count = |f: &dyn Fn() -> bool| {
+            COMPARISONS.with(|c| c.set(0));
+            assert!(f());
+            COMPARISONS.with(|c| c.get())
+        };
+        assert!(count(&|| a.is_subset(&b)) <= 4 * n as usize);
+        assert!(count(&|| a.is_superset(&b)) <= 4 * n as usize);
+        assert!(count(&|| a.symmetric_difference(&b).is_empty()) <= 4 * n as usize);
+        assert!(count(&|| a.diff(&b).is_empty()) <= 4 * n as usize);
+    }
+
+    #[test]
+    fn diff() {
+        let mut old = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (15, 'o'), (12, 'l')] {
+            old.insert(k, v);
+        }
+        let mut new = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'E'), (2, 'b'), (15, 'o'), (17, 'q')] {
+            new.insert(k, v);
+        }
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                Change::Added(2, 'b'),
+                Change::Removed(3, 'c'),
+                Change::Modified(5, 'e', 'E'),
+                Change::Removed(12, 'l'),
+                Change::Added(17, 'q'),
+            ]
+        );
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(BSTree::new().diff(&old).len(), 5);
+    }
+
+    #[test]
+    fn walk() {
+        /*
+                8
+               / \
+             5    15
+            / \   / \
+          3   6  12  17
+         / \   \  / \
+        2  4   7 10 14
+
+    */
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let collect = |order: Order, skip: i32| {
+            let mut keys = Vec::new();
+            let _: ControlFlow<()> = tree.walk(order, |k, _| {
+                keys.push(*k);
+                ControlFlow::Continue(if *k == skip { Visit::SkipChildren } else { Visit::Children })
+            });
+            keys
+        };
+        assert_eq!(collect(Order::PreOrder, 0), vec![8, 5, 3, 2, 4, 6, 7, 15, 12, 10, 14, 17]);
+        assert_eq!(collect(Order::InOrder, 0), vec![2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 17]);
+        assert_eq!(collect(Order::PostOrder, 0), vec![2, 4, 3, 7, 6, 5, 10, 14, 12, 17, 15, 8]);
+        assert_eq!(collect(Order::LevelOrder, 0), vec![8, 5, 15, 3, 6, 12, 17, 2, 4, 7, 10, 14]);
+        assert_eq!(collect(Order::PreOrder, 5), vec![8, 5, 15, 12, 10, 14, 17]);
+        assert_eq!(collect(Order::InOrder, 5), vec![2, 3, 4, 5, 8, 10, 12, 14, 15, 17]);
+        assert_eq!(collect(Order::PostOrder, 5), collect(Order::PostOrder, 0));
+        assert_eq!(collect(Order::LevelOrder, 15), vec![8, 5, 15, 3, 6, 2, 4, 7]);
+
+        for order in [Order::PreOrder, Order::InOrder, Order::PostOrder, Order::LevelOrder] {
+            let mut visited = 0;
+            let res = tree.walk(order, |k, v| {
+                visited += 1;
+                if *k == 12 { ControlFlow::Break(*v) } else { ControlFlow::Continue(()) }
+            });
+            assert_eq!(res, ControlFlow::Break('l'));
+            assert!(visited < 12);
+        }
+        assert_eq!(tree.walk(Order::InOrder, |_, _| ControlFlow::<()>::Continue(())), ControlFlow::Continue(()));
+    }
+
+    #[test]
+    fn fold() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let all = tree.fold(String::new(), |mut acc, _, v| {
+            acc.push(*v);
+            acc
+        });
+        assert_eq!(all, "bcdefghjlnoq");
+        let collect = |acc: Vec<i32>, k: &i32, _: &char| {
+            let mut acc = acc;
+            acc.push(*k);
+            acc
+        };
+        assert_eq!(tree.fold_range(4..12, Vec::new(), collect), vec![4, 5, 6, 7, 8, 10]);
+        assert_eq!(tree.fold_range(..=3, Vec::new(), collect), vec![2, 3]);
+        assert_eq!(tree.fold_range((std::ops::Bound::Excluded(14), std::ops::Bound::Unbounded), Vec::new(), collect), vec![15, 17]);
+        assert_eq!(tree.fold_range(9..10, Vec::new(), collect), Vec::<i32>::new());
+        assert_eq!(BSTree::<i32, i32>::new().fold(0, |acc, _, v| acc + v), 0);
+    }
+
+    #[test]
+    fn find_by_value() {
+        let mut scores = BSTree::new();
+        for (id, score) in [(8, 70), (5, 95), (3, 60), (6, 95), (15, 80), (12, 95), (17, 50)] {
+            scores.insert(id, score);
+        }
+        assert_eq!(scores.find_by_value(|s| *s == 95), Some((&5, &95)));
+        assert_eq!(scores.find_by_value(|s| *s > 100), None);
+        assert_eq!(scores.keys_with_value(|s| *s == 95), vec![&5, &6, &12]);
+        assert_eq!(scores.keys_with_value(|s| *s < 65), vec![&3, &17]);
+        assert!(scores.keys_with_value(|_| false).is_empty());
+    }
+
+    #[test]
+    fn min_max_by_value() {
+        let mut scores = BSTree::new();
+        assert_eq!(scores.min_by_value(|a: &i32, b| a.cmp(b)), None);
+        for (id, score) in [(8, 70), (5, 95), (3, 60), (6, 95), (15, 80), (12, 50), (17, 50)] {
+            scores.insert(id, score);
+        }
+        assert_eq!(scores.min_by_value(|a, b| a.cmp(b)), Some((&12, &50)));
+        assert_eq!(scores.max_by_value(|a, b| a.cmp(b)), Some((&5, &95)));
+        assert_eq!(scores.max_by_value(|a, b| b.cmp(a)), Some((&12, &50)));
+        assert_eq!(scores.min_by_value(|a, b| (a % 7).cmp(&(b % 7))), Some((&8, &70)));
+    }
+
+    #[test]
+    fn memory_usage() {
+        let mut tree = BSTree::new();
+        tree.insert(1, vec![0u8; 10]);
+        let one = tree.memory_usage();
+        assert!(one >= std::mem::size_of::<(i32, Vec<u8>)>());
+        for i in 2..=10 {
+            tree.insert(i, vec![0u8; 10]);
+        }
+        assert_eq!(tree.memory_usage(), 10 * one);
+        assert_eq!(tree.memory_usage_with(|_, v| v.capacity()), 10 * one + 100);
+        // 删除的节点进入节点池，仍然计入占用的内存，释放节点池后才减少
+        tree.remove_range(..=5);
+        assert_eq!(tree.pooled_nodes(), 5);
+        assert_eq!(tree.memory_usage(), 10 * one);
+        tree.shrink_to_fit();
+        assert_eq!(tree.memory_usage(), 5 * one);
+        tree.reserve(8);
+        assert_eq!(tree.memory_usage(), 13 * one);
+        // 插入先复用节点池中的空闲节点，总量不变
+        tree.insert(20, Vec::new());
+        assert_eq!(tree.memory_usage(), 13 * one);
+        tree.delete(6);
+        tree.delete(7);
+        assert_eq!(tree.pooled_nodes(), 9);
+        assert_eq!(tree.memory_usage(), 13 * one);
+        tree.shrink_to_fit();
+        assert_eq!(tree.memory_usage(), 4 * one);
+    }
+
+    #[test]
+    fn rekey() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.rekey(&5, 13), Ok(()));
+        assert_eq!(tree.rekey(&8, 1), Ok(()));
+        assert_eq!(tree.rekey(&2, 2), Ok(()));
+        assert_eq!(tree.rekey(&100, 0), Err(RekeyError::NotFound(0)));
+        assert_eq!(tree.rekey(&3, 12), Err(RekeyError::Occupied(12)));
+        assert_eq!(RekeyError::Occupied(12).to_string(), "key 12 already exists");
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&1, &'h'), (&2, &'b'), (&3, &'c'), (&4, &'d'), (&6, &'f'), (&7, &'g'), (&12, &'l'), (&13, &'e'), (&15, &'o'), (&17, &'q')]);
+    }
+
+    #[test]
+    fn swap_values() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.swap_values(&17, &2), Ok(()));
+        assert_eq!(tree.swap_values(&5, &4), Ok(()));
+        assert_eq!(tree.swap_values(&8, &12), Ok(()));
+        assert_eq!(tree.swap_values(&6, &6), Ok(()));
+        assert_eq!(tree.swap_values(&9, &3), Err(KeyNotFound(9)));
+        assert_eq!(tree.swap_values(&3, &20), Err(KeyNotFound(20)));
+        assert_eq!(KeyNotFound(20).to_string(), "key 20 not found");
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'q'), (&3, &'c'), (&4, &'e'), (&5, &'d'), (&6, &'f'), (&8, &'l'), (&12, &'h'), (&15, &'o'), (&17, &'b')]);
+    }
+
+    #[test]
+    fn insert_with_policy() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, "h"), (5, "e"), (15, "o")] {
+            tree.insert(k, v.to_string());
+        }
+        tree.insert_with_policy(5, "x".to_string(), KeepOld);
+        tree.insert_with_policy(15, "y".to_string(), Overwrite);
+        tree.insert_with_policy(8, "z".to_string(), |_: &i32, old: String, new: String| old + &new);
+        tree.insert_with_policy(3, "c".to_string(), KeepOld);
+        let res: Vec<(i32, &str)> = tree.inorder_iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(res, vec![(3, "c"), (5, "e"), (8, "hz"), (15, "y")]);
+        tree.insert_with_policy(3, "!".to_string(), |_: &i32, old: String, _| old).push('!');
+        assert_eq!(tree.get(&3).map(String::as_str), Some("c!"));
+    }
+
+    #[test]
+    fn insert_with() {
+        let mut tree = BSTree::new();
+        let mut built = 0;
+        for k in [8, 5, 8, 15, 5, 3] {
+            tree.insert_with(k, || {
+                built += 1;
+                k * 100
+            });
+        }
+        assert_eq!(built, 4);
+        let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&3, &300), (&5, &500), (&8, &800), (&15, &1500)]);
+    }
+
+    #[test]
+    fn insert_many() {
+        /*
+                8
+               / \
+             5    15
+            / \   / \
+          3   6  12  17
+         / \   \  / \
+        2  4   7 10 14
+
+    */
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (6, 'f'), (15, 'o'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        tree.insert_many(vec![(14, 'n'), (2, 'b'), (7, 'g'), (12, 'l'), (4, 'd'), (10, 'j'), (5, 'E'), (14, 'N')]);
+        let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+        assert_eq!(res, vec![(&8, &'h'), (&5, &'E'), (&3, &'c'), (&2, &'b'), (&4, &'d'), (&6, &'f'), (&7, &'g'),
+                             (&15, &'o'), (&12, &'l'), (&10, &'j'), (&14, &'N'), (&17, &'q')]);
+        let mut empty = BSTree::new();
+        empty.insert_many((1..=7).rev().map(|i| (i, i)));
+        let res: Vec<&i32> = empty.preorder_iter().map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&4, &2, &1, &3, &6, &5, &7]);
+    }
+
+    #[test]
+    fn remove_many() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.remove_many(vec![8, 12, 5, 100, 6, 14, 8]), 5);
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'b'), (&3, &'c'), (&4, &'d'), (&7, &'g'), (&10, &'j'), (&15, &'o'), (&17, &'q')]);
+        assert_eq!(tree.remove_many(Vec::new()), 0);
+        assert_eq!(tree.remove_many(0..20), 7);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn btreemap_conversion() {
+        let map = BTreeMap::from([(8, 'h'), (5, 'e'), (3, 'c'), (15, 'o'), (12, 'l')]);
+        let mut tree = BSTree::from(map.clone());
+        assert_eq!(tree.get(&12), Some(&'l'));
+        tree.insert(1, 'a');
+        tree.delete(15);
+        let back = BTreeMap::from(tree);
+        let res: Vec<(i32, char)> = back.into_iter().collect();
+        assert_eq!(res, vec![(1, 'a'), (3, 'c'), (5, 'e'), (8, 'h'), (12, 'l')]);
+        assert!(BTreeMap::from(BSTree::<i32, char>::new()).is_empty());
+    }
+
+    #[test]
+    fn index() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (15, 'o'), (12, 'l')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree[&8], 'h');
+        assert_eq!(tree[&3], 'c');
+        assert_eq!(tree[&12], 'l');
+        let result = std::panic::catch_unwind(|| tree[&4]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 15, 12] {
+            tree.insert(k, 0);
+        }
+        tree[&8] += 1;
+        tree[&3] += 2;
+        tree[&3] += 2;
+        *tree.get_mut(&12).unwrap() = 7;
+        assert_eq!(tree.get_mut(&4), None);
+        let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&3, &4), (&5, &0), (&8, &1), (&12, &7), (&15, &0)]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_iter() {
+        use rayon::prelude::*;
+        let mut tree = BSTree::new();
+        for i in (0..1000).rev().step_by(3).chain((0..1000).step_by(3)) {
+            tree.insert(i, i as u64);
+        }
+        let keys: Vec<i32> = tree.par_iter().map(|(k, _)| *k).collect();
+        let expected: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, expected);
+        let sum: u64 = tree.par_iter().map(|(_, v)| *v).sum();
+        assert_eq!(sum, expected.iter().map(|k| *k as u64).sum());
+        assert_eq!(tree.par_iter().find_any(|(k, _)| **k == 501).map(|(_, v)| *v), Some(501));
+        assert_eq!(BSTree::<i32, u64>::new().par_iter().count(), 0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn from_sorted_vec() {
+        let items: Vec<(i32, i32)> = (0..20000).map(|i| (i, -i)).collect();
+        let tree = BSTree::from_sorted_vec(items.clone());
+        let expected = BSTree::bulk_load(items);
+        assert!(tree.preorder_iter().eq(expected.preorder_iter()));
+        let tree = BSTree::from_sorted_vec(vec![(3, 'c'), (1, 'a'), (2, 'b'), (1, 'z')]);
+        let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&1, &'z'), (&2, &'b'), (&3, &'c')]);
+        assert!(BSTree::<i32, i32>::from_sorted_vec(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn successor_predecessor_mut() {
+        // 键为区间起点，值为区间终点
+        let mut tree = BSTree::new();
+        tree.insert(0, 3);
+        tree.insert(6, 9);
+        tree.insert(20, 25);
+        // 新区间[3, 5)与左侧邻居[0, 3)相接，原地扩展左邻居
+        if let Some((_, end)) = tree.predecessor_mut(&3) {
+            if *end == 3 {
+                *end = 5;
+            }
+        }
+        // 新区间[15, 20)与右侧邻居[20, 25)相接，取出右邻居后以新起点重新插入
+        let merged = match tree.successor_mut(&15) {
+            Some((&start, end)) if start == 20 => Some((start, *end)),
+            _ => None,
+        };
+        if let Some((start, end)) = merged {
+            tree.delete(start);
+            tree.insert(15, end);
+        }
+        let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+        assert_eq!(res, vec![(&0, &5), (&6, &9), (&15, &25)]);
+        *tree.successor_mut(&6).unwrap().1 += 1;
+        *tree.predecessor_mut(&100).unwrap().1 += 1;
+        assert_eq!(tree.get(&15), Some(&27));
+        assert_eq!(tree.predecessor_mut(&0), None);
+        assert_eq!(tree.successor_mut(&15), None);
+    }
+
+    #[test]
+    fn lower_upper_bound() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let mut cursor = tree.lower_bound(Bound::Included(&9));
+        assert_eq!(cursor.peek_prev(), Some((&8, &'h')));
+        assert_eq!(cursor.next(), Some((&10, &'j')));
+        assert_eq!(cursor.next(), Some((&12, &'l')));
+        assert_eq!(cursor.prev(), Some((&12, &'l')));
+        assert_eq!(cursor.prev(), Some((&10, &'j')));
+        assert_eq!(cursor.prev(), Some((&8, &'h')));
+        let keys: Vec<&i32> = tree.lower_bound(Bound::Excluded(&12)).map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![&14, &15, &17]);
+        let mut cursor = tree.upper_bound(Bound::Excluded(&5));
+        assert_eq!(cursor.peek_next(), Some((&5, &'e')));
+        assert_eq!(cursor.prev(), Some((&4, &'d')));
+        assert_eq!(cursor.prev(), Some((&3, &'c')));
+        assert_eq!(cursor.prev(), Some((&2, &'b')));
+        assert_eq!(cursor.prev(), None);
+        assert_eq!(cursor.peek_next(), Some((&2, &'b')));
+        let cursor = tree.upper_bound(Bound::Included(&1));
+        assert_eq!((cursor.peek_prev(), cursor.peek_next()), (None, Some((&2, &'b'))));
+        let mut cursor = tree.lower_bound(Bound::Included(&20));
+        assert_eq!((cursor.peek_prev(), cursor.next()), (Some((&17, &'q')), None));
+        assert_eq!(tree.lower_bound(Bound::Unbounded).count(), 12);
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k * 10);
+        }
+        let [a, b, c] = tree.get_many_mut([&17, &2, &8]).unwrap();
+        std::mem::swap(a, b);
+        *c += 1;
+        assert_eq!((tree.get(&17), tree.get(&2), tree.get(&8)), (Some(&20), Some(&170), Some(&81)));
+        assert_eq!(tree.get_many_mut([&4, &12, &4]).err(), Some(GetManyMutError::Duplicate(4)));
+        assert_eq!(tree.get_many_mut([&4, &11]).err(), Some(GetManyMutError::NotFound(11)));
+        assert!(tree.get_many_mut::<0>([]).is_ok());
+    }
+
+    #[test]
+    fn insert_randomized() {
+        let mut tree = BSTree::new();
+        for i in 0..5000 {
+            tree.insert_randomized(i, i);
+        }
+        for i in (0..5000).step_by(7) {
+            tree.insert_randomized(i, -i);
+        }
+        // 顺序插入时普通的insert会退化成高度5000的链
+        assert!(tree.levels().len() < 200);
+        let keys: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, (0..5000).collect::<Vec<_>>());
+        assert_eq!(tree.get(&14), Some(&-14));
+        assert_eq!(tree.get(&15), Some(&15));
+    }
+
+    #[test]
+    fn zip_tree() {
+        let mut tree = ZipTree::new();
+        for i in 0..2000 {
+            tree.insert(i, i);
+        }
+        tree.insert(7, -7);
+        assert_eq!(tree.get(&7), Some(&-7));
+        assert_eq!(tree.take(8), Some((8, 8)));
+        assert_eq!(tree.take(8), None);
+        let (low, high) = tree.unzip(&1000);
+        assert!(low.inorder_iter().all(|(k, _)| *k < 1000));
+        assert!(high.inorder_iter().all(|(k, _)| *k >= 1000));
+        assert_eq!(low.inorder_iter().count() + high.inorder_iter().count(), 1999);
+        let tree = low.zip(high);
+        let keys: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+        let expected: Vec<i32> = (0..2000).filter(|k| *k != 8).collect();
+        assert_eq!(keys, expected);
+        let result = std::panic::catch_unwind(|| {
+            let mut a = ZipTree::new();
+            a.insert(5, ());
+            let mut b = ZipTree::new();
+            b.insert(3, ());
+            a.zip(b)
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn for_each_in_place() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let preorder: Vec<(i32, char)> = tree.preorder_iter().map(|(k, v)| (*k, *v)).collect();
+        let mut res = Vec::new();
+        tree.for_each(|k, v| res.push((*k, *v)));
+        let inorder: Vec<(i32, char)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, inorder);
+        // 遍历之后树的形状不变
+        let after: Vec<(i32, char)> = tree.preorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(after, preorder);
+        let mut empty: BSTree<i32, char> = BSTree::new();
+        empty.for_each(|_, _| panic!("empty tree has no entries"));
+        let mut chain = BSTree::new();
+        for i in (0..2000).rev() {
+            chain.insert(i, ());
+        }
+        let mut count = 0;
+        chain.for_each(|_, _| count += 1);
+        assert_eq!(count, 2000);
+    }
+
+    #[test]
+    fn zigzag() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let keys: Vec<&i32> = tree.zigzag_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![&8, &15, &5, &3, &6, &12, &17, &14, &10, &7, &4, &2]);
+        // 跳过键15的子树
+        let mut keys = Vec::new();
+        let _: ControlFlow<()> = tree.walk(Order::ZigZag, |k, _| {
+            keys.push(*k);
+            ControlFlow::Continue(if *k == 15 { Visit::SkipChildren } else { Visit::Children })
+        });
+        assert_eq!(keys, vec![8, 15, 5, 3, 6, 7, 4, 2]);
+        assert_eq!(BSTree::<i32, char>::new().zigzag_iter().count(), 0);
+    }
+
+    #[test]
+    fn vertical_order() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let columns: Vec<Vec<i32>> = tree
+            .vertical_order()
+            .into_iter()
+            .map(|col| col.into_iter().map(|(k, _)| *k).collect())
+            .collect();
+        assert_eq!(columns, vec![vec![2], vec![3], vec![5, 4, 10], vec![8, 6, 12], vec![15, 7, 14], vec![17]]);
+        assert!(BSTree::<i32, char>::new().vertical_order().is_empty());
+    }
+
+    #[test]
+    fn diameter() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        // 最长路径为2-3-5-8-15-12-10
+        assert_eq!(tree.diameter(), 6);
+        tree.insert(1, 'a');
+        assert_eq!(tree.diameter(), 7);
+        // 最长路径15-20-30-50-70-80-90不经过根节点
+        let mut tree = BSTree::new();
+        for k in [10, 100, 50, 30, 20, 15, 70, 80, 90] {
+            tree.insert(k, ());
+        }
+        assert_eq!(tree.diameter(), 6);
+        assert_eq!(BSTree::<i32, ()>::new().diameter(), 0);
+    }
+
+    #[test]
+    fn same_structure() {
+        let entries = [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')];
+        let mut tree = BSTree::new();
+        let mut copy = BSTree::new();
+        for (k, v) in entries {
+            tree.insert(k, v);
+            copy.insert(k, v);
+        }
+        assert!(tree.same_structure(&copy));
+        // 内容相同但形状不同
+        let balanced = BSTree::bulk_load(entries);
+        assert!(!tree.same_structure(&balanced));
+        // 形状相同但值不同
+        copy.insert(7, 'z');
+        assert!(!tree.same_structure(&copy));
+        assert!(BSTree::<i32, char>::new().same_structure(&BSTree::new()));
+        assert!(!tree.same_structure(&BSTree::new()));
+    }
+
+    #[test]
+    fn symmetric_and_mirror() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        assert!(!tree.is_symmetric());
+        let inorder: Vec<(i32, char)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        let levels: Vec<Vec<i32>> = tree.levels().into_iter().map(|l| l.into_iter().map(|(k, _)| *k).collect()).collect();
+        let mirrored = tree.mirror();
+        let res: Vec<(i32, char)> = mirrored.inorder_iter().map(|(k, v)| (k.0, *v)).collect();
+        assert_eq!(res, inorder.into_iter().rev().collect::<Vec<_>>());
+        // 每一层的顺序左右颠倒
+        let mirrored_levels: Vec<Vec<i32>> = mirrored.levels().into_iter().map(|l| l.into_iter().map(|(k, _)| k.0).collect()).collect();
+        let expected: Vec<Vec<i32>> = levels.into_iter().map(|l| l.into_iter().rev().collect()).collect();
+        assert_eq!(mirrored_levels, expected);
+        assert_eq!(mirrored.successor(&Reverse(8)), Some((&Reverse(7), &'g')));
+        assert!(BSTree::bulk_load((0..15).map(|i| (i, ()))).is_symmetric());
+        assert!(BSTree::<i32, ()>::new().is_symmetric());
+        assert!(BSTree::<i32, ()>::new().mirror().is_empty());
+    }
+
+    #[test]
+    fn path_to() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let res: Vec<(&i32, &char)> = tree.path_to(&14).collect();
+        assert_eq!(res, vec![(&8, &'h'), (&15, &'o'), (&12, &'l'), (&14, &'n')]);
+        let res: Vec<&i32> = tree.path_to(&8).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&8]);
+        // 不存在的键到插入位置的父节点为止
+        let res: Vec<&i32> = tree.path_to(&11).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&8, &15, &12, &10]);
+        assert_eq!(BSTree::<i32, char>::new().path_to(&1).count(), 0);
+    }
+
+    #[test]
+    fn lca_and_distance() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        assert_eq!(tree.lca(&2, &7), Some((&5, &'e')));
+        assert_eq!(tree.lca(&10, &17), Some((&15, &'o')));
+        assert_eq!(tree.lca(&12, &14), Some((&12, &'l')));
+        assert_eq!(tree.lca(&2, &14), Some((&8, &'h')));
+        assert_eq!(tree.distance(&2, &14), Some(6));
+        assert_eq!(tree.distance(&7, &4), Some(4));
+        assert_eq!(tree.distance(&8, &10), Some(3));
+        assert_eq!(tree.distance(&8, &11), None);
+        assert_eq!(BSTree::<i32, char>::new().distance(&1, &1), None);
+    }
+
+    #[test]
+    fn structure_codec() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let encoded = tree.encode_structure();
+        assert_eq!(encoded, "8 5 3 2 # # 4 # # 6 # 7 # # 15 12 10 # # 14 # # 17 # #");
+        let decoded: BSTree<i32, char> = BSTree::decode_structure(&encoded).unwrap();
+        assert_eq!(decoded.encode_structure(), encoded);
+        let res: Vec<(&i32, &char)> = decoded.preorder_iter().take(2).collect();
+        assert_eq!(res, vec![(&8, &'\0'), (&5, &'\0')]);
+        let decode = |s: &str| BSTree::<i32, ()>::decode_structure(s).err();
+        assert_eq!(decode("8 5 # 9 # # #"), Some(DecodeError::OutOfOrder("9".to_string())));
+        assert_eq!(decode("8 8 # # #"), Some(DecodeError::OutOfOrder("8".to_string())));
+        assert_eq!(decode("8 x # #"), Some(DecodeError::InvalidKey("x".to_string())));
+        assert_eq!(decode(""), Some(DecodeError::UnexpectedEnd));
+        assert_eq!(decode("# 1"), Some(DecodeError::TrailingInput("1".to_string())));
+        assert!(BSTree::<i32, ()>::decode_structure("#").unwrap().is_empty());
+        assert_eq!(decode("8 # 9 7 # # #"), Some(DecodeError::OutOfOrder("7".to_string())));
+        // 嵌套深度由输入决定，很深的不完整输入也只返回错误，不会耗尽调用栈
+        let deep: String = (0..200_000).rev().map(|k| format!("{} ", k)).collect();
+        assert_eq!(RecursiveBSTree::<i32, ()>::decode_structure(&deep).err(), Some(DecodeError::UnexpectedEnd));
+        let deep: String = (0..200_000).map(|k| format!("{} # ", k)).collect();
+        assert_eq!(RecursiveBSTree::<i32, ()>::decode_structure(&deep).err(), Some(DecodeError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn flatten() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let inorder: Vec<(i32, char)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        tree.flatten();
+        // 每层只有一个节点，且都是右孩子
+        let levels = tree.levels();
+        assert_eq!(levels.len(), 12);
+        assert!(levels.iter().all(|level| level.len() == 1));
+        let vine: Vec<(i32, char)> = tree.preorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(vine, inorder);
+        assert_eq!(tree.get(&14), Some(&'n'));
+        tree.flatten();
+        assert_eq!(tree.levels().len(), 12);
+        let mut empty: BSTree<i32, char> = BSTree::new();
+        empty.flatten();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn from_preorder() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let (keys, values): (Vec<i32>, Vec<char>) = tree.preorder_iter().map(|(k, v)| (*k, *v)).unzip();
+        let rebuilt = BSTree::from_preorder(keys.clone(), values.clone()).unwrap();
+        assert!(rebuilt.same_structure(&tree));
+        // 键与值个数不同
+        assert!(BSTree::from_preorder(keys, values.into_iter().skip(1)).is_none());
+        // 3出现在8的右子树之后，不是合法的前序序列
+        assert!(BSTree::from_preorder(vec![8, 15, 3], vec![(); 3]).is_none());
+        assert!(BSTree::from_preorder(vec![5, 5], vec![(); 2]).is_none());
+        assert!(BSTree::<i32, ()>::from_preorder(vec![], vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn from_level_order() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let pairs: Vec<(i32, char)> = tree.levelorder_iter().map(|(k, v)| (*k, *v)).collect();
+        let rebuilt = BSTree::from_level_order(pairs.clone()).unwrap();
+        assert!(rebuilt.same_structure(&tree));
+        let res: Vec<(i32, char)> = rebuilt.levelorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, pairs);
+        // 9在第二层之后出现，但它只能是8的右孩子
+        assert!(BSTree::from_level_order(vec![(8, ()), (5, ()), (3, ()), (9, ())]).is_none());
+        assert!(BSTree::<i32, ()>::from_level_order(vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parent_iter() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let rows: Vec<(Option<i32>, i32, char)> = tree.parent_iter().map(|(p, k, v)| (p.copied(), *k, *v)).collect();
+        assert_eq!(rows[0], (None, 8, 'h'));
+        assert!(rows.contains(&(Some(15), 12, 'l')));
+        assert!(rows.contains(&(Some(6), 7, 'g')));
+        // 按行依次插入即可重建出同样的树
+        let mut rebuilt = BSTree::new();
+        for (parent, k, v) in &rows {
+            assert!(parent.is_none_or(|p| rebuilt.contains(&p)));
+            rebuilt.insert(*k, *v);
+        }
+        assert!(rebuilt.same_structure(&tree));
+        assert_eq!(BSTree::<i32, char>::new().parent_iter().count(), 0);
+    }
+
+    #[test]
+    fn walker() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let all: Vec<&i32> = tree.walker().map(|(k, _)| k).collect();
+        let preorder: Vec<&i32> = tree.preorder_iter().map(|(k, _)| k).collect();
+        assert_eq!(all, preorder);
+        // 跳过键5和键12的子树
+        let mut walker = tree.walker();
+        let mut visited = Vec::new();
+        while let Some((k, _)) = walker.next() {
+            visited.push(*k);
+            if *k == 5 || *k == 12 {
+                walker.skip_subtree();
+            }
+        }
+        assert_eq!(visited, vec![8, 5, 15, 12, 17]);
+        // 连续调用skip_subtree与调用一次相同，在next之前调用不起作用
+        let mut walker = tree.walker();
+        walker.skip_subtree();
+        assert_eq!(walker.next(), Some((&8, &'h')));
+        walker.skip_subtree();
+        walker.skip_subtree();
+        assert_eq!(walker.next(), None);
+    }
+
+    #[test]
+    fn for_each_mut() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (2, 'b'), (4, 'd'), (6, 'f'), (7, 'g'), (15, 'o'), (12, 'l'), (17, 'q'), (10, 'j'), (14, 'n')] {
+            tree.insert(k, v);
+        }
+        let mut keys = Vec::new();
+        tree.for_each_mut(|k, v| {
+            keys.push(*k);
+            if *k % 2 == 0 {
+                *v = v.to_ascii_uppercase();
+            }
+        });
+        assert_eq!(keys, vec![2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 17]);
+        let values: String = tree.inorder_iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, "BcDeFgHJLNoq");
+        let mut empty: BSTree<i32, char> = BSTree::new();
+        empty.for_each_mut(|_, _| panic!("empty tree has no entries"));
+    }
+
+    #[test]
+    fn map_keys() {
+        let mut tree = BSTree::new();
+        for (k, v) in [(8, 'h'), (5, 'e'), (3, 'c'), (6, 'f'), (15, 'o'), (12, 'l'), (17, 'q')] {
+            tree.insert(k, v);
+        }
+        let before: Vec<char> = tree.preorder_iter().map(|(_, v)| *v).collect();
+        let shifted = tree.map_keys(|k| (k as i64) * 10 - 1);
+        let res: Vec<(i64, char)> = shifted.preorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(
+            res,
+            vec![(79, 'h'), (49, 'e'), (29, 'c'), (59, 'f'), (149, 'o'), (119, 'l'), (169, 'q')]
+        );
+        let after: Vec<char> = res.iter().map(|(_, v)| *v).collect();
+        assert_eq!(before, after);
+        assert_eq!(shifted.get(&119), Some(&'l'));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "strictly increasing")]
+    fn map_keys_rejects_non_monotonic() {
+        let mut tree = BSTree::new();
+        for k in [2, 1, 3] {
+            tree.insert(k, ());
+        }
+        let _ = tree.map_keys(|k| -k);
+    }
+
+    #[test]
+    fn retain_range() {
+        let keys = [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 85];
+        let build = || {
+            let mut tree = BSTree::new();
+            for k in keys {
+                tree.insert(k, k / 5);
+            }
+            tree
+        };
+        let collect = |tree: &BSTree<i32, i32>| -> Vec<i32> { tree.inorder_iter().map(|(k, _)| *k).collect() };
+        let mut tree = build();
+        tree.retain_range(25..=65);
+        assert_eq!(collect(&tree), vec![25, 30, 35, 40, 45, 50, 55, 60, 65]);
+        assert_eq!(tree.get(&45), Some(&9));
+        let mut tree = build();
+        tree.retain_range((Bound::Excluded(70), Bound::Unbounded));
+        assert_eq!(collect(&tree), vec![75, 80, 85]);
+        let mut tree = build();
+        tree.retain_range(..22);
+        assert_eq!(collect(&tree), vec![10, 20]);
+        let mut tree = build();
+        tree.retain_range(..);
+        assert_eq!(collect(&tree).len(), keys.len());
+        tree.retain_range(26..29);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn iter_slice() {
+        let mut tree = BSTree::new();
+        for k in [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 85] {
+            tree.insert(k, k * 2);
+        }
+        let page: Vec<(&i32, &i32)> = tree.iter_slice(4..7).collect();
+        assert_eq!(page, vec![(&35, &70), (&40, &80), (&45, &90)]);
+        let all: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+        for start in 0..=all.len() {
+            for end in start..=all.len() + 2 {
+                let res: Vec<i32> = tree.iter_slice(start..end).map(|(k, _)| *k).collect();
+                assert_eq!(res, all[start..end.min(all.len())].to_vec());
+            }
+        }
+        let res: Vec<&i32> = tree.iter_slice(..=1).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&10, &20]);
+        let res: Vec<&i32> = tree.iter_slice((Bound::Excluded(12), Bound::Unbounded)).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&80, &85]);
+        assert_eq!(BSTree::<i32, i32>::new().iter_slice(..).count(), 0);
+    }
+
+    #[test]
+    fn order_stat_sorted_inserts() {
+        // 按升序插入也不会退化成链表，递归深度保持在O(log n)
+        let mut tree = OrderStatTree::new();
+        let n = 200_000;
+        for i in 0..n {
+            tree.insert(i, i);
+        }
+        assert_eq!(tree.len(), n as usize);
+        assert_eq!(tree.select(123_456), Some((&123_456, &123_456)));
+        assert_eq!(tree.rank(&(n - 1)), Some(n as usize - 1));
+        for i in (0..n).step_by(2) {
+            tree.take(&i);
+        }
+        assert_eq!(tree.len(), n as usize / 2);
+        let res: Vec<&i32> = tree.iter_slice(10..13).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&21, &23, &25]);
+    }
+
+    #[test]
+    fn order_stat_iter_slice() {
+        let mut tree = OrderStatTree::new();
+        for k in [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 85] {
+            tree.insert(k, k * 2);
+        }
+        let page: Vec<(&i32, &i32)> = tree.iter_slice(4..7).collect();
+        assert_eq!(page, vec![(&35, &70), (&40, &80), (&45, &90)]);
+        for round in 0..3 {
+            let all: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+            assert_eq!(tree.len(), all.len());
+            for start in 0..=all.len() {
+                for end in start..=all.len() + 2 {
+                    let res: Vec<i32> = tree.iter_slice(start..end).map(|(k, _)| *k).collect();
+                    assert_eq!(res, all[start..end.min(all.len())].to_vec());
+                }
+            }
+            for (rank, key) in all.iter().enumerate() {
+                assert_eq!(tree.select(rank).map(|(k, _)| k), Some(key));
+                assert_eq!(tree.rank(key), Some(rank));
+            }
+            assert_eq!(tree.select(all.len()), None);
+            // 删除各种位置的节点后子树大小仍然正确
+            for key in [[50, 10, 85], [30, 65, 12], [70, 20, 45]][round] {
+                tree.take(&key);
+            }
+            tree.insert(round as i32 * 7 + 1, 0);
+        }
+        let res: Vec<&i32> = tree.iter_slice(..=1).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&1, &8]);
+        let res: Vec<&i32> = tree.iter_slice((Bound::Excluded(7), Bound::Unbounded)).map(|(k, _)| k).collect();
+        assert_eq!(res, vec![&75, &80]);
+        assert_eq!(OrderStatTree::<i32, i32>::new().iter_slice(..).count(), 0);
+    }
+
+    #[test]
+    fn sample() {
+        let tree = BSTree::bulk_load((0..10).map(|i| (i, i * i)));
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut rng = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut counts = [0; 10];
+        for _ in 0..10_000 {
+            let (k, v) = tree.sample(&mut rng).unwrap();
+            assert_eq!(*v, k * k);
+            counts[*k as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (800..1200).contains(&c)), "{:?}", counts);
+        let mut counts = [0; 10];
+        for _ in 0..2_000 {
+            let picked = tree.sample_n(3, &mut rng);
+            assert_eq!(picked.len(), 3);
+            assert!(picked.windows(2).all(|w| w[0].0 < w[1].0));
+            for (k, _) in picked {
+                counts[*k as usize] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&c| (480..720).contains(&c)), "{:?}", counts);
+        assert_eq!(tree.sample_n(0, &mut rng), vec![]);
+        assert_eq!(tree.sample_n(20, &mut rng).len(), 10);
+        assert_eq!(BSTree::<i32, i32>::new().sample(&mut rng), None);
+    }
+
+    #[test]
+    fn order_stat_sample() {
+        let mut tree = OrderStatTree::new();
+        for i in [5, 2, 8, 0, 3, 7, 9, 1, 4, 6] {
+            tree.insert(i, i * i);
+        }
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut rng = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut counts = [0; 10];
+        for _ in 0..10_000 {
+            let (k, v) = tree.sample(&mut rng).unwrap();
+            assert_eq!(*v, k * k);
+            counts[*k as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (800..1200).contains(&c)), "{:?}", counts);
+        let mut counts = [0; 10];
+        for _ in 0..2_000 {
+            let picked = tree.sample_n(3, &mut rng);
+            assert_eq!(picked.len(), 3);
+            assert!(picked.windows(2).all(|w| w[0].0 < w[1].0));
+            for (k, _) in picked {
+                counts[*k as usize] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&c| (480..720).contains(&c)), "{:?}", counts);
+        assert_eq!(tree.sample_n(0, &mut rng), vec![]);
+        assert_eq!(tree.sample_n(20, &mut rng).len(), 10);
+        assert_eq!(OrderStatTree::<i32, i32>::new().sample(&mut rng), None);
+    }
+
+    #[test]
+    fn sum_tree_range_sum() {
+        let mut tree = SumTree::new();
+        for k in [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 85] {
+            tree.insert(k, k as u64);
+        }
+        assert_eq!(tree.range_sum(25..=45), 25 + 30 + 35 + 40 + 45);
+        assert_eq!(tree.range_sum((Bound::Excluded(75), Bound::Unbounded)), 80 + 85);
+        assert_eq!(tree.range_sum(..), 745);
+        assert_eq!(tree.range_sum(26..29), 0);
+        // 删除有两个孩子的节点、插入新键、替换旧值后子树和仍然正确
+        assert_eq!(tree.take(&30), Some((30, 30)));
+        tree.insert(33, 33);
+        tree.insert(40, 4);
+        assert_eq!(tree.take(&50), Some((50, 50)));
+        assert_eq!(tree.range_sum(25..=45), 25 + 33 + 35 + 4 + 45);
+        let expected: Vec<(i32, u64)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        for low in 0..90 {
+            for high in low..90 {
+                let sum: u64 = expected.iter().filter(|(k, _)| (low..high).contains(k)).map(|(_, v)| v).sum();
+                assert_eq!(tree.range_sum(low..high), sum);
+            }
+        }
+        // 按升序插入也保持平衡，不会因为递归过深而栈溢出
+        let mut tree = SumTree::new();
+        for k in 0..200_000u64 {
+            tree.insert(k, k);
+        }
+        assert_eq!(tree.range_sum(..), 199_999 * 200_000 / 2);
+        assert_eq!(tree.range_sum(100..=102), 303);
+    }
+
+    #[test]
+    fn lazy_range_apply() {
+        let keys = [50, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 85];
+        let mut tree = LazyTree::new();
+        let mut model = BTreeMap::new();
+        for k in keys {
+            tree.insert(k, k as i64);
+            model.insert(k, k as i64);
+        }
+        let updates: [(Bound<i32>, Bound<i32>, i64); 5] = [
+            (Bound::Included(25), Bound::Included(65), 100),
+            (Bound::Unbounded, Bound::Excluded(40), -7),
+            (Bound::Excluded(70), Bound::Unbounded, 3),
+            (Bound::Unbounded, Bound::Unbounded, 1),
+            (Bound::Included(26), Bound::Excluded(29), 1000),
+        ];
+        for (i, (low, high, delta)) in updates.iter().enumerate() {
+            tree.range_apply((*low, *high), *delta);
+            for (_, v) in model.range_mut((*low, *high)) {
+                *v += delta;
+            }
+            // 交替穿插删除和插入，检查标记在结构变化时被正确下推
+            if i == 1 {
+                assert_eq!(tree.take(&30), model.remove_entry(&30));
+                assert_eq!(tree.take(&50), model.remove_entry(&50));
+            }
+            if i == 2 {
+                tree.insert(33, 0);
+                model.insert(33, 0);
+            }
+            for (k, v) in &model {
+                assert_eq!(tree.get(k), Some(*v));
+            }
+        }
+        *tree.get_mut(&45).unwrap() += 1;
+        *model.get_mut(&45).unwrap() += 1;
+        let res: Vec<(i32, i64)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        let expected: Vec<(i32, i64)> = model.into_iter().collect();
+        assert_eq!(res, expected);
+        assert!(!tree.contains(&30));
+        // 按升序插入也保持平衡，下推标记和删除都不会因为递归过深而栈溢出
+        let mut tree = LazyTree::new();
+        for k in 0..200_000 {
+            tree.insert(k, 0i64);
+        }
+        tree.range_apply(1000.., 5);
+        tree.range_apply(..=1000, 2);
+        assert_eq!(tree.take(&1000), Some((1000, 7)));
+        assert_eq!(tree.get(&199_999), Some(5));
+        assert_eq!(tree.inorder_iter().map(|(_, v)| *v).sum::<i64>(), 1000 * 2 + 198_999 * 5);
+    }
+
+    #[test]
+    fn versioned_bstree() {
+        let mut tree = VersionedBSTree::new();
+        let mut history: Vec<BTreeMap<i32, i32>> = Vec::new();
+        let mut model = BTreeMap::new();
+        for (round, k) in [50, 30, 70, 20, 40, 60, 80, 35, 45, 65].iter().enumerate() {
+            tree.insert(*k, round as i32);
+            model.insert(*k, round as i32);
+            if round % 3 == 2 {
+                let victim = [30, 50, 70][round / 3 % 3];
+                assert_eq!(tree.remove(&victim), model.remove(&victim));
+                tree.insert(40, -(round as i32));
+                model.insert(40, -(round as i32));
+            }
+            assert_eq!(tree.commit(), history.len());
+            history.push(model.clone());
+        }
+        assert_eq!(tree.remove(&1000), None);
+        tree.insert(1000, 0);
+        assert_eq!(tree.version_count(), history.len());
+        for (version, snapshot) in history.iter().enumerate() {
+            let res: Vec<(i32, i32)> = tree.iter_at(version).map(|(k, v)| (*k, *v)).collect();
+            let expected: Vec<(i32, i32)> = snapshot.iter().map(|(k, v)| (*k, *v)).collect();
+            assert_eq!(res, expected);
+            for k in [20, 30, 40, 50, 1000] {
+                assert_eq!(tree.get_at(version, &k), snapshot.get(&k));
+            }
+        }
+        assert_eq!(tree.get(&1000), Some(&0));
+        assert_eq!(tree.inorder_iter().count(), model.len() + 1);
+    }
+
+    #[test]
+    fn versioned_snapshot() {
+        let mut tree = VersionedBSTree::new();
+        for k in [3, 2, 1, 4] {
+            tree.insert(k, k * 10);
+        }
+        let snap = tree.snapshot();
+        let copy = snap.clone();
+        tree.insert(2, -20);
+        tree.insert(5, 50);
+        tree.remove(&3);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(snap.len(), 4);
+        assert_eq!(snap.get(&2), Some(&20));
+        assert_eq!(snap.get(&3), Some(&30));
+        assert_eq!(snap.get(&5), None);
+        assert_eq!(tree.get(&2), Some(&-20));
+        // 快照可以交给其他线程遍历，树在当前线程继续修改
+        let export = std::thread::spawn(move || copy.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>());
+        tree.insert(6, 60);
+        assert_eq!(export.join().unwrap(), vec![(1, 10), (2, 20), (3, 30), (4, 40)]);
+        assert!(VersionedBSTree::<i32, i32>::new().snapshot().is_empty());
+    }
+
+    #[test]
+    fn journaled_undo_redo() {
+        let snapshot = |tree: &JournaledBSTree<i32, char>| -> Vec<(i32, char)> {
+            tree.tree().inorder_iter().map(|(k, v)| (*k, *v)).collect()
+        };
+        let mut tree = JournaledBSTree::new();
+        let mut states = vec![snapshot(&tree)];
+        tree.insert(2, 'b');
+        states.push(snapshot(&tree));
+        tree.insert_many(vec![(1, 'a'), (3, 'c'), (2, 'B'), (1, 'A')]);
+        states.push(snapshot(&tree));
+        assert!(tree.delete(&3));
+        states.push(snapshot(&tree));
+        assert_eq!(tree.delete_many(vec![1, 9, 2]), 2);
+        states.push(snapshot(&tree));
+        assert!(!tree.delete(&7));
+        assert_eq!(tree.delete_many(vec![7]), 0);
+        assert!(tree.tree().is_empty());
+        for i in (0..states.len() - 1).rev() {
+            assert!(tree.undo());
+            assert_eq!(snapshot(&tree), states[i]);
+        }
+        assert!(!tree.undo());
+        for state in &states[1..] {
+            assert!(tree.redo());
+            assert_eq!(snapshot(&tree), *state);
+        }
+        assert!(!tree.redo());
+        tree.undo();
+        tree.undo();
+        assert_eq!(snapshot(&tree), vec![(1, 'A'), (2, 'B'), (3, 'c')]);
+        tree.insert(4, 'd');
+        assert!(!tree.redo());
+        assert!(tree.undo());
+        assert_eq!(snapshot(&tree), states[2]);
+    }
+
+    #[test]
+    #[cfg(feature = "persist")]
+    fn save_and_load() {
+        let mut tree = BSTree::new();
+        for k in [50, 30, 70, 20, 40, 60, 80, 35, 45, 65] {
+            tree.insert(k, format!("v{}", k * 3));
+        }
+        let path = std::env::temp_dir().join(format!("an_ok_bstree_test_{}.txt", std::process::id()));
+        tree.save_to(&path).unwrap();
+        let loaded: BSTree<i32, String> = BSTree::load_from(&path).unwrap();
+        assert!(loaded.same_structure(&tree));
+        let res: Vec<(&i32, &String)> = loaded.preorder_iter().collect();
+        let expected: Vec<(&i32, &String)> = tree.preorder_iter().collect();
+        assert_eq!(res, expected);
+        BSTree::<i32, String>::new().save_to(&path).unwrap();
+        assert!(BSTree::<i32, String>::load_from(&path).unwrap().is_empty());
+        // 键和值中的空格、换行和分隔符原样保存
+        let mut tree = BSTree::new();
+        for (k, v) in [("b 1", "x\ny"), ("a:2", ""), ("c\n", "3:abc\n")] {
+            tree.insert(k.to_string(), v.to_string());
+        }
+        tree.save_to(&path).unwrap();
+        let loaded: BSTree<String, String> = BSTree::load_from(&path).unwrap();
+        assert!(loaded.same_structure(&tree));
+        assert!(loaded.preorder_iter().eq(tree.preorder_iter()));
+        let tmp = path.with_file_name(format!(".{}.tmp", path.file_name().unwrap().to_str().unwrap()));
+        assert!(!tmp.exists());
+        for bad in ["1:2 2:10\n1:1 1:x\n", "1:2 2:20\n1:3 2:30\n1:1 2:10\n", "1:2 2:20", "1:2 2:20\n1:1", "x:2 2:20\n", "1:2 5:20\n"] {
+            std::fs::write(&path, bad).unwrap();
+            let err = BSTree::<i32, i32>::load_from(&path).err().unwrap();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{:?}", bad);
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(BSTree::<i32, i32>::load_from(&path).err().unwrap().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    #[cfg(feature = "persist")]
+    fn wal_replay() {
+        use an_ok_bstree::WalWriter;
+        use std::fs::OpenOptions;
+        use std::io::{BufReader, Write};
+
+        let path = std::env::temp_dir().join(format!("an_ok_bstree_wal_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut model = BTreeMap::new();
+        {
+            let file = OpenOptions::new().create(true).append(true).open(&path).unwrap();
+            let mut wal = WalWriter::new(file);
+            for i in 0..50 {
+                let key = (i * 7) % 23;
+                if i % 4 == 3 {
+                    wal.log_delete(&key).unwrap();
+                    model.remove(&key);
+                } else {
+                    let value = format!("value {}", i);
+                    wal.log_insert(&key, &value).unwrap();
+                    model.insert(key, value);
+                }
+            }
+            wal.commit().unwrap();
+            // 模拟写到一半时崩溃
+            wal.into_inner().write_all(b"+ 2:99 4:to").unwrap();
+        }
+        let file = BufReader::new(std::fs::File::open(&path).unwrap());
+        let tree: BSTree<i32, String> = BSTree::replay(file).unwrap();
+        let res: Vec<(i32, String)> = tree.inorder_iter().map(|(k, v)| (*k, v.clone())).collect();
+        let expected: Vec<(i32, String)> = model.into_iter().collect();
+        assert_eq!(res, expected);
+        assert!(!tree.contains(&99));
+        let err = BSTree::<i32, String>::replay(&b"+ 1:1 1:a\n- 1:x\n"[..]).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(BSTree::<i32, String>::replay(&b"+ 1:1\n"[..]).is_err());
+        assert!(BSTree::<i32, String>::replay(&b"+ 1 a\n"[..]).is_err());
+        // 值中的空格、换行和分隔符原样重放，末尾被截断的多字节字符也视为写入中断
+        let mut wal = WalWriter::new(Vec::new());
+        wal.log_insert(&1, &"a b\n- 1:1\n").unwrap();
+        wal.log_insert(&2, &"值").unwrap();
+        let log = wal.into_inner();
+        let tree: BSTree<i32, String> = BSTree::replay(&log[..]).unwrap();
+        assert_eq!(tree.get(&1).map(String::as_str), Some("a b\n- 1:1\n"));
+        assert_eq!(tree.get(&2).map(String::as_str), Some("值"));
+        let tree: BSTree<i32, String> = BSTree::replay(&log[..log.len() - 3]).unwrap();
+        assert!(!tree.contains(&2));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn frozen_mmap() {
+        use an_ok_bstree::FrozenBSTree;
+
+        let mut tree = BSTree::new();
+        for k in [50u32, 30, 70, 20, 40, 60, 80, 10, 25, 35, 45, 55, 65, 75, 85] {
+            tree.insert(k, (k as i64) * -3);
+        }
+        let path = std::env::temp_dir().join(format!("an_ok_bstree_frozen_{}.bin", std::process::id()));
+        FrozenBSTree::write_to(&tree, &path).unwrap();
+        let frozen: FrozenBSTree<u32, i64> = FrozenBSTree::open_mmap(&path).unwrap();
+        assert_eq!(frozen.len(), 15);
+        for k in 0..100 {
+            assert_eq!(frozen.get(&k), tree.get(&k).copied());
+        }
+        let ranges = [(0, 100), (25, 45), (26, 29), (46, 86), (85, 86), (0, 10)];
+        for (low, high) in ranges {
+            let expected: Vec<(u32, i64)> = tree.fold_range(low..high, Vec::new(), |mut acc, k, v| {
+                acc.push((*k, *v));
+                acc
+            });
+            assert_eq!(frozen.range(low..high), expected);
+        }
+        assert_eq!(frozen.range(..=20).len(), 2);
+        // 文件中的孩子编号越界时拒绝打开
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] = 0x7f;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(FrozenBSTree::<u32, i64>::open_mmap(&path).is_err());
+        bytes.pop();
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(FrozenBSTree::<u32, i64>::open_mmap(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn merkle_root_hash() {
+        let mut a = MerkleBSTree::new();
+        let mut b = MerkleBSTree::new();
+        let mut model = BTreeMap::new();
+        for i in 0..60u32 {
+            let key = (i * 37) % 41;
+            if i % 5 == 4 {
+                assert_eq!(a.take(&key), model.remove_entry(&key));
+                b.take(&key);
+            } else {
+                a.insert(key, i);
+                b.insert(key, i);
+                model.insert(key, i);
+            }
+            assert_eq!(a.root_hash(), b.root_hash());
+            assert!(a.verify());
+        }
+        let res: Vec<(u32, u32)> = a.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        let expected: Vec<(u32, u32)> = model.into_iter().collect();
+        assert_eq!(res, expected);
+        let agreed = a.root_hash();
+        let leaf = *a.inorder_iter().last().unwrap().0;
+        b.insert(leaf, 1000);
+        assert_ne!(a.root_hash(), b.root_hash());
+        a.insert(leaf, 1000);
+        assert_eq!(a.root_hash(), b.root_hash());
+        assert_ne!(a.root_hash(), agreed);
+        b.insert(100, 0);
+        assert_ne!(a.root_hash(), b.root_hash());
+        b.take(&100);
+        assert_eq!(a.root_hash(), b.root_hash());
+        assert!(b.verify());
+    }
+
+    // 只依赖BinarySearchTree接口的通用测试，可以对任意实现运行
+    fn exercise_binary_search_tree<T: BinarySearchTree<i32, i32>>() {
+        let mut tree = T::default();
+        assert!(tree.is_empty());
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k * 10);
+        }
+        let keys = |tree: &T| -> Vec<i32> { tree.inorder_iter().map(|(k, _)| *k).collect() };
+        assert_eq!(keys(&tree), vec![2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 17]);
+        let res: Vec<i32> = tree.preorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![8, 5, 3, 2, 4, 6, 7, 15, 12, 10, 14, 17]);
+        let res: Vec<i32> = tree.postorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![2, 4, 3, 7, 6, 5, 10, 14, 12, 17, 15, 8]);
+        let res: Vec<i32> = tree.levelorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![8, 5, 15, 3, 6, 12, 17, 2, 4, 7, 10, 14]);
+        assert_eq!(tree.get(&6), Some(&60));
+        *tree.get_mut(&6).unwrap() += 1;
+        assert_eq!(tree.get(&6), Some(&61));
+        assert!(tree.contains(&14) && !tree.contains(&13));
+        assert_eq!(tree.min_pair(), Some((&2, &20)));
+        assert_eq!(tree.max_pair(), Some((&17, &170)));
+        assert_eq!(tree.successor(&8), Some((&10, &100)));
+        assert_eq!(tree.predecessor(&8), Some((&7, &70)));
+        assert_eq!(tree.take(&5), Some((5, 50)));
+        assert_eq!(tree.take(&5), None);
+        tree.delete(15);
+        tree.delete(100);
+        assert_eq!(keys(&tree), vec![2, 3, 4, 6, 7, 8, 10, 12, 14, 17]);
+    }
+
+    #[test]
+    fn binary_search_tree_trait() {
+        exercise_binary_search_tree::<BSTree<i32, i32>>();
+        exercise_binary_search_tree::<RecursiveBSTree<i32, i32>>();
+        exercise_binary_search_tree::<IterativeBSTree<i32, i32>>();
+    }
+
+    #[test]
+    fn both_implementations_side_by_side() {
+        let mut recursive = RecursiveBSTree::new();
+        let mut iterative = IterativeBSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            recursive.insert(k, k * 2);
+            iterative.insert(k, k * 2);
+        }
+        recursive.delete(5);
+        iterative.delete(5);
+        assert!(recursive.preorder_iter().eq(iterative.preorder_iter()));
+        assert_eq!(recursive.encode_structure(), iterative.encode_structure());
+        let copy: IterativeBSTree<i32, i32> = BTreeMap::from(recursive).into();
+        assert!(copy.inorder_iter().eq(iterative.inorder_iter()));
+    }
+
+    #[test]
+    fn node_pool_reuse() {
+        let mut tree = BSTree::new();
+        tree.reserve(4);
+        for round in 0..3 {
+            for i in 0..4 {
+                tree.insert(i, i + round);
+            }
+            assert_eq!(tree.pooled_nodes(), 0);
+            assert_eq!(tree.get(&3), Some(&(3 + round)));
+            for i in 0..4 {
+                assert_eq!(tree.take(&i), Some((i, i + round)));
+            }
+            assert!(tree.is_empty());
+            assert_eq!(tree.pooled_nodes(), 4);
+        }
+        tree.insert(7, 7);
+        tree.delete(7);
+        tree.delete(8);
+        assert_eq!(tree.pooled_nodes(), 4);
+        tree.shrink_to_fit();
+        assert_eq!(tree.pooled_nodes(), 0);
+        tree.insert(5, 5);
+        assert_eq!(tree.inorder_iter().collect::<Vec<_>>(), vec![(&5, &5)]);
+    }
+
+    #[test]
+    fn node_pool_every_variant() {
+        let mut tree = BSTree::new();
+        tree.reserve(12);
+        tree.insert(0, 0);
+        assert_eq!(tree.pooled_nodes(), 11);
+        assert!(tree.try_insert(1, 1).is_ok());
+        assert_eq!(tree.pooled_nodes(), 10);
+        *tree.get_or_insert_with(2, || 0) += 2;
+        assert_eq!(tree.pooled_nodes(), 9);
+        *tree.get_or_insert_default(3) += 3;
+        assert_eq!(tree.pooled_nodes(), 8);
+        tree.insert_add(4, 4);
+        assert_eq!(tree.pooled_nodes(), 7);
+        assert_eq!(tree.replace(5, 5), None);
+        assert_eq!(tree.pooled_nodes(), 6);
+        tree.insert_with_policy(6, 6, KeepOld);
+        assert_eq!(tree.pooled_nodes(), 5);
+        assert!(tree.insert_with(7, || 7));
+        assert_eq!(tree.pooled_nodes(), 4);
+        tree.insert_randomized(8, 8);
+        assert_eq!(tree.pooled_nodes(), 3);
+        tree.insert_many(vec![(9, 9), (10, 10)]);
+        assert_eq!(tree.pooled_nodes(), 1);
+        tree.extend_sorted(vec![(11, 11)]);
+        assert_eq!(tree.pooled_nodes(), 0);
+        // 键已经存在时不取节点
+        tree.insert(0, 0);
+        assert_eq!(tree.replace(1, 1), Some((1, 1)));
+        tree.insert_many(vec![(2, 2), (3, 3)]);
+        assert_eq!(tree.pooled_nodes(), 0);
+        assert!(tree.inorder_iter().map(|(k, v)| (*k, *v)).eq((0..12).map(|i| (i, i))));
+
+        assert_eq!(tree.remove_range(0..2), vec![(0, 0), (1, 1)]);
+        assert_eq!(tree.pooled_nodes(), 2);
+        tree.retain_range(..10);
+        assert_eq!(tree.pooled_nodes(), 4);
+        assert_eq!(tree.remove_many(vec![2, 3]), 2);
+        assert_eq!(tree.pooled_nodes(), 6);
+        assert_eq!(tree.pop_while(|k, _| *k < 5), vec![(4, 4)]);
+        assert_eq!(tree.pooled_nodes(), 7);
+        assert_eq!(tree.first_entry().map(|entry| entry.remove()), Some(5));
+        assert_eq!(tree.pooled_nodes(), 8);
+        let pruned = tree.prune(|k, _| *k == 9);
+        assert_eq!(tree.pooled_nodes(), 8 + pruned);
+        if let Some((&root, _)) = tree.preorder_iter().next() {
+            assert_eq!(tree.delete_tree(root), 4 - pruned);
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.pooled_nodes(), 12);
+
+        tree.insert_many((0..12).map(|i| (i, i)));
+        assert_eq!(tree.pooled_nodes(), 0);
+    }
+
+    #[test]
+    fn bounded_eviction() {
+        let scores = [(50, 'a'), (20, 'b'), (90, 'c'), (70, 'd'), (10, 'e'), (80, 'f')];
+        let mut top = BoundedBSTree::with_capacity(3, Eviction::EvictMin);
+        let mut bottom = BoundedBSTree::with_capacity(3, Eviction::EvictMax);
+        let mut first = BoundedBSTree::with_capacity(3, Eviction::Reject);
+        for &(score, name) in scores.iter() {
+            top.insert(score, name);
+            bottom.insert(score, name);
+            first.insert(score, name);
+            assert!(top.len() <= 3);
+        }
+        let keys = |tree: &BoundedBSTree<i32, char>| tree.tree().inorder_iter().map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(&top), vec![70, 80, 90]);
+        assert_eq!(keys(&bottom), vec![10, 20, 50]);
+        assert_eq!(keys(&first), vec![20, 50, 90]);
+        assert!(top.is_full());
+        assert_eq!(top.take(&80), Some((80, 'f')));
+        assert_eq!(top.insert(60, 'g'), None);
+        assert_eq!(top.insert(60, 'h'), None);
+        assert_eq!(top.len(), 3);
+        let mut empty = BoundedBSTree::with_capacity(0, Eviction::EvictMin);
+        assert_eq!(empty.insert(1, 'x'), Some((1, 'x')));
+        assert!(empty.is_empty());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn operation_metrics() {
+        use an_ok_bstree::{metrics, reset_metrics, Metrics};
+        let mut tree = BSTree::bulk_load((1..=7).map(|i| (i, i)));
+        reset_metrics();
+        assert_eq!(tree.get(&1), Some(&1));
+        let m = metrics();
+        assert_eq!(m.node_visits, 3);
+        assert_eq!(m.rotations, 0);
+        assert!(m.comparisons >= 3);
+        reset_metrics();
+        tree.insert(8, 8);
+        assert_eq!(metrics().node_visits, 3);
+        reset_metrics();
+        tree.delete(8);
+        assert_eq!(metrics().node_visits, 4);
+        // 只统计按键定位节点的查找，前驱后继不计入
+        reset_metrics();
+        assert_eq!(tree.successor(&1), Some((&2, &2)));
+        assert_eq!(tree.predecessor(&7), Some((&6, &6)));
+        assert_eq!(metrics(), Metrics::default());
+        // 计数是线程局部的
+        let handle = std::thread::spawn(move || {
+            tree.get(&7);
+            (metrics().node_visits, tree)
+        });
+        let (visits, mut tree) = handle.join().unwrap();
+        assert_eq!(visits, 3);
+        assert_eq!(metrics(), Metrics::default());
+        tree.flatten();
+        assert!(metrics().rotations > 0);
+        reset_metrics();
+        assert_eq!(metrics(), Metrics::default());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_events() {
+        use std::fmt::Debug;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        // 把每个事件记录为"消息 字段=值"形式的一行文本
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+        struct Line(String);
+
+        impl Visit for Line {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                if field.name() == "message" {
+                    self.0.insert_str(0, &format!("{:?}", value));
+                } else {
+                    self.0.push_str(&format!(" {}={:?}", field.name(), value));
+                }
+            }
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut line = Line(String::new());
+                event.record(&mut line);
+                self.0.lock().unwrap().push(line.0);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Recorder(lines.clone()), || {
+            let mut tree = BSTree::new();
+            for k in [5, 3, 8, 4] {
+                tree.insert(k, ());
+            }
+            tree.delete(4);
+            tree.insert(4, ());
+            tree.delete(3);
+            tree.delete(5);
+            tree.get_or_insert_with(6, || ());
+            tree.try_insert(8, ()).unwrap_err();
+            tree.insert_many(vec![(1, ()), (9, ())]);
+            BSTree::bulk_load((0..10).map(|i| (i, i)));
+        });
+        let lines = lines.lock().unwrap();
+        assert_eq!(
+            *lines,
+            vec![
+                "insert path_len=0",
+                "insert path_len=1",
+                "insert path_len=1",
+                "insert path_len=2",
+                "delete case=\"leaf\"",
+                "insert path_len=2",
+                "delete case=\"one child\"",
+                "delete case=\"two children\"",
+                "insert path_len=2",
+                "insert path_len=1",
+                "rebuild op=\"insert_many\" len=2",
+                "rebuild op=\"bulk_load\" len=10",
+            ]
+        );
+    }
+
+    #[cfg(all(feature = "debug-validate", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "invariant violated after `insert`: the key at in-order index 1 is less than its in-order predecessor")]
+    fn debug_validate_detects_corruption() {
+        use std::cell::Cell;
+        use std::cmp::Ordering;
+        use std::rc::Rc;
+
+        // 键的大小由共享的Cell决定，插入后在树外修改它就能破坏查找树的性质；键没有实现Debug
+        #[derive(Clone, PartialEq)]
+        struct Shared(Rc<Cell<i32>>);
+
+        impl PartialOrd for Shared {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.get().partial_cmp(&other.0.get())
+            }
+        }
+
+        let mut tree = BSTree::new();
+        let keys: Vec<Shared> = (0..3).map(|i| Shared(Rc::new(Cell::new(i * 10)))).collect();
+        for key in &keys {
+            tree.insert(key.clone(), ());
+        }
+        tree.take(&keys[1]);
+        keys[2].0.set(-5);
+        tree.insert(Shared(Rc::new(Cell::new(7))), ());
+    }
+
+    #[cfg(all(feature = "debug-validate", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "invariant violated after `get_or_insert_with`: the key at in-order index 2 is less than its in-order predecessor")]
+    fn debug_validate_before_handing_out_value() {
+        use std::cell::Cell;
+        use std::cmp::Ordering;
+        use std::rc::Rc;
+
+        #[derive(Clone, PartialEq)]
+        struct Shared(Rc<Cell<i32>>);
+
+        impl PartialOrd for Shared {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.0.get().partial_cmp(&other.0.get())
+            }
+        }
+
+        let mut tree = BSTree::new();
+        let low = Shared(Rc::new(Cell::new(1)));
+        tree.insert(Shared(Rc::new(Cell::new(5))), 0);
+        tree.insert(low.clone(), 0);
+        low.0.set(8);
+        // 值的可变借用只在检查通过后才交出，这里不会执行到加一
+        *tree.get_or_insert_with(Shared(Rc::new(Cell::new(3))), || 0) += 1;
+    }
+
+    // 随机操作序列中的一步
+    #[derive(Debug, Clone)]
+    enum ModelOp {
+        Insert(u8, u16),
+        Take(u8),
+        Get(u8),
+        Successor(u8),
+        Predecessor(u8),
+    }
+
+    fn model_op() -> impl proptest::strategy::Strategy<Value = ModelOp> {
+        use proptest::prelude::*;
+        prop_oneof![
+            (any::<u8>(), any::<u16>()).prop_map(|(k, v)| ModelOp::Insert(k, v)),
+            any::<u8>().prop_map(ModelOp::Take),
+            any::<u8>().prop_map(ModelOp::Get),
+            any::<u8>().prop_map(ModelOp::Successor),
+            any::<u8>().prop_map(ModelOp::Predecessor),
+        ]
+    }
+
+    // 把ops依次作用在树和BTreeMap上，比较每一步可观察到的结果
+    fn check_against_btreemap<T: BinarySearchTree<u8, u16>>(ops: &[ModelOp]) -> Result<(), proptest::test_runner::TestCaseError> {
+        use proptest::prelude::*;
+        let mut tree = T::default();
+        let mut model = BTreeMap::new();
+        for op in ops {
+            match *op {
+                ModelOp::Insert(k, v) => {
+                    tree.insert(k, v);
+                    model.insert(k, v);
+                }
+                ModelOp::Take(k) => prop_assert_eq!(tree.take(&k), model.remove_entry(&k)),
+                ModelOp::Get(k) => prop_assert_eq!(tree.get(&k), model.get(&k)),
+                ModelOp::Successor(k) => {
+                    prop_assert_eq!(tree.successor(&k), model.range((Bound::Excluded(k), Bound::Unbounded)).next())
+                }
+                ModelOp::Predecessor(k) => prop_assert_eq!(tree.predecessor(&k), model.range(..k).next_back()),
+            }
+            prop_assert_eq!(tree.min_pair(), model.iter().next());
+            prop_assert_eq!(tree.max_pair(), model.iter().next_back());
+        }
+        prop_assert!(tree.inorder_iter().eq(model.iter()));
+        Ok(())
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn model_matches_btreemap(ops in proptest::collection::vec(model_op(), 0..200)) {
+            check_against_btreemap::<BSTree<u8, u16>>(&ops)?;
+            check_against_btreemap::<RecursiveBSTree<u8, u16>>(&ops)?;
+            check_against_btreemap::<IterativeBSTree<u8, u16>>(&ops)?;
+        }
+    }
+
+    #[test]
+    fn into_keys_and_values() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 6, 15, 12] {
+            tree.insert(k, format!("v{}", k));
+        }
+        tree.delete(5);
+        let keys = tree.into_keys();
+        assert_eq!(keys.size_hint(), (5, Some(5)));
+        assert_eq!(keys.collect::<Vec<_>>(), vec![3, 6, 8, 12, 15]);
+        let tree = BSTree::bulk_load((1..=4).map(|i| (i, vec![i; i])));
+        let values: Vec<Vec<usize>> = tree.into_values().collect();
+        assert_eq!(values, vec![vec![1], vec![2, 2], vec![3, 3, 3], vec![4, 4, 4, 4]]);
+        assert_eq!(BSTree::<i32, ()>::new().into_values().next(), None);
+    }
+
+    #[test]
+    fn get_or_else_is_lazy() {
+        let tree = BSTree::bulk_load((1..=3).map(|i| (i, i * 100)));
+        let mut calls = 0;
+        let fallback = -1;
+        assert_eq!(tree.get_or_else(&2, || { calls += 1; &fallback }), &200);
+        assert_eq!(tree.get_or_else(&9, || { calls += 1; &fallback }), &-1);
+        assert_eq!(tree.get_cloned_or_else(&3, || { calls += 1; 0 }), 300);
+        assert_eq!(tree.get_cloned_or_else(&0, || { calls += 1; 0 }), 0);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn get_or_insert_default_accumulates() {
+        let mut groups: BSTree<char, Vec<&str>> = BSTree::new();
+        for word in ["pear", "apple", "plum", "avocado", "fig"] {
+            groups.get_or_insert_default(word.chars().next().unwrap()).push(word);
+        }
+        let res: Vec<(&char, &Vec<&str>)> = groups.inorder_iter().collect();
+        assert_eq!(res, vec![(&'a', &vec!["apple", "avocado"]), (&'f', &vec!["fig"]), (&'p', &vec!["pear", "plum"])]);
+        assert!(groups.get_or_insert_default('z').is_empty());
+        assert!(groups.contains(&'z'));
+    }
+
+    #[test]
+    fn min_max_pair_mut() {
+        let mut queue = BSTree::new();
+        for (priority, task) in [(5, "write"), (1, "plan"), (9, "ship"), (3, "test")] {
+            queue.insert(priority, (task, 0));
+        }
+        if let Some((_, (_, bumps))) = queue.min_pair_mut() {
+            *bumps += 1;
+        }
+        if let Some((_, (_, bumps))) = queue.max_pair_mut() {
+            *bumps += 2;
+        }
+        assert_eq!(queue.min_pair(), Some((&1, &("plan", 1))));
+        assert_eq!(queue.max_pair(), Some((&9, &("ship", 2))));
+        queue.delete(1);
+        assert_eq!(queue.min_pair_mut(), Some((&3, &mut ("test", 0))));
+        let mut empty: BSTree<i32, i32> = BSTree::new();
+        assert_eq!(empty.min_pair_mut(), None);
+    }
+
+    #[test]
+    fn tree_ordering() {
+        use std::collections::BTreeSet;
+        let mut a = BSTree::new();
+        for k in [3, 1, 2] {
+            a.insert(k, k * 10);
+        }
+        let b = BSTree::bulk_load((1..=3).map(|k| (k, k * 10)));
+        assert!(a == b);
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+        let c = BSTree::bulk_load(vec![(1, 10), (2, 21)]);
+        let d = BSTree::bulk_load(vec![(0, 99)]);
+        assert!(a < c && d < a);
+        let set: BTreeSet<BSTree<i32, i32>> = vec![c, a, d, b].into_iter().collect();
+        assert_eq!(set.len(), 3);
+        let firsts: Vec<i32> = set.iter().map(|t| *t.min_pair().unwrap().0).collect();
+        assert_eq!(firsts, vec![0, 1, 1]);
+        assert_eq!(BSTree::bulk_load(vec![(1.0, 1)]).partial_cmp(&BSTree::bulk_load(vec![(f64::NAN, 1)])), None);
+    }
+
+    #[test]
+    fn subtree_size() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k);
+        }
+        assert_eq!(tree.subtree_size(&8), 12);
+        assert_eq!(tree.subtree_size(&5), 6);
+        assert_eq!(tree.subtree_size(&12), 3);
+        assert_eq!(tree.subtree_size(&7), 1);
+        assert_eq!(tree.subtree_size(&9), 0);
+        let expected = tree.subtree_size(&15);
+        assert_eq!(tree.remove_tree(15).inorder_iter().count(), expected);
+        assert_eq!(tree.subtree_size(&8), 12 - expected);
+    }
+
+    #[test]
+    fn try_remove_tree() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 6, 15, 12, 17] {
+            tree.insert(k, k);
+        }
+        assert!(tree.try_remove_tree(100).is_none());
+        let rm_tree = tree.try_remove_tree(5).unwrap();
+        let res: Vec<i32> = rm_tree.inorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![3, 5, 6]);
+        assert!(tree.try_remove_tree(5).is_none());
+        assert!(tree.try_remove_tree(8).is_some());
+        assert!(tree.is_empty());
+        assert!(tree.try_remove_tree(8).is_none());
+    }
+
+    #[test]
+    fn graft() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k);
+        }
+        let moved = tree.remove_tree(12);
+        let mut other = BSTree::new();
+        other.insert(20, 20);
+        assert!(other.graft(moved).is_ok());
+        let res: Vec<i32> = other.inorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![10, 12, 14, 20]);
+
+        let mut straddle = BSTree::new();
+        straddle.insert(9, 9);
+        straddle.insert(16, 16);
+        let mut rejected = tree.graft(straddle).unwrap_err().0;
+        assert!(!tree.contains(&9));
+        rejected.delete(16);
+        assert!(tree.graft(rejected).is_ok());
+        assert!(tree.graft(BSTree::new()).is_ok());
+        let res: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![2, 3, 4, 5, 6, 7, 8, 9, 15, 17]);
+        let mut dup = BSTree::new();
+        dup.insert(6, 0);
+        assert!(tree.graft(dup).is_err());
+    }
+
+    #[test]
+    fn merge_iter() {
+        let a = BSTree::bulk_load((0..10).step_by(2).map(|k| (k, 'a')));
+        let b = BSTree::bulk_load((0..10).step_by(3).map(|k| (k, 'b')));
+        let res: Vec<(i32, char)> = a.merge_iter(&b, Tie::Both).map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, vec![(0, 'a'), (0, 'b'), (2, 'a'), (3, 'b'), (4, 'a'), (6, 'a'), (6, 'b'), (8, 'a'), (9, 'b')]);
+        let res: Vec<(i32, char)> = a.merge_iter(&b, Tie::Left).map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, vec![(0, 'a'), (2, 'a'), (3, 'b'), (4, 'a'), (6, 'a'), (8, 'a'), (9, 'b')]);
+        let res: Vec<(i32, char)> = a.merge_iter(&b, Tie::Right).map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, vec![(0, 'b'), (2, 'a'), (3, 'b'), (4, 'a'), (6, 'b'), (8, 'a'), (9, 'b')]);
+        let empty = BSTree::new();
+        assert_eq!(a.merge_iter(&empty, Tie::Both).count(), 5);
+        assert_eq!(empty.merge_iter(&b, Tie::Left).count(), 4);
+    }
+
+    #[test]
+    fn set_operation_iters() {
+        let a = BSTree::bulk_load((0..20).step_by(2).map(|k| (k, 'a')));
+        let b = BSTree::bulk_load((0..20).step_by(3).map(|k| (k, 'b')));
+        let keys = |it: &mut dyn Iterator<Item = (&i32, &char)>| it.map(|(k, _)| *k).collect::<Vec<i32>>();
+        assert_eq!(keys(&mut a.union_iter(&b)), vec![0, 2, 3, 4, 6, 8, 9, 10, 12, 14, 15, 16, 18]);
+        assert!(a.union_iter(&b).filter(|(k, _)| *k % 2 == 0).all(|(_, v)| *v == 'a'));
+        assert_eq!(keys(&mut a.intersection_iter(&b)), vec![0, 6, 12, 18]);
+        assert!(b.intersection_iter(&a).all(|(_, v)| *v == 'b'));
+        assert_eq!(keys(&mut a.difference_iter(&b)), vec![2, 4, 8, 10, 14, 16]);
+        assert_eq!(keys(&mut b.difference_iter(&a)), vec![3, 9, 15]);
+        let empty = BSTree::new();
+        assert_eq!(a.intersection_iter(&empty).count(), 0);
+        assert_eq!(a.difference_iter(&empty).count(), 10);
+        assert_eq!(empty.difference_iter(&a).count(), 0);
+    }
+
+    #[test]
+    fn multiset_counts() {
+        let mut tree = MultiBSTree::new();
+        for word in "the cat and the hat and the bat".split(' ') {
+            tree.insert(word);
+        }
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.count(&"the"), 3);
+        assert_eq!(tree.count(&"dog"), 0);
+        let res: Vec<(&&str, usize)> = tree.iter().collect();
+        assert_eq!(res, vec![(&"and", 2), (&"bat", 1), (&"cat", 1), (&"hat", 1), (&"the", 3)]);
+        assert_eq!(tree.remove_one(&"bat"), Some(0));
+        assert!(!tree.contains(&"bat"));
+        assert_eq!(tree.remove_one(&"the"), Some(2));
+        assert_eq!(tree.remove_one(&"dog"), None);
+        assert_eq!(tree.remove_all(&"and"), 2);
+        let res: Vec<&&str> = tree.iter_expanded().collect();
+        assert_eq!(res, vec![&"cat", &"hat", &"the", &"the"]);
+        assert_eq!(tree.len(), 4);
+    }
+
+    #[test]
+    fn insert_add() {
+        let mut hist = BSTree::new();
+        for x in [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5] {
+            hist.insert_add(x, 1u32);
+        }
+        let res: Vec<(i32, u32)> = hist.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, vec![(1, 2), (2, 1), (3, 2), (4, 1), (5, 3), (6, 1), (9, 1)]);
+        hist.insert_add(9, 10);
+        assert_eq!(hist.get(&9), Some(&11));
+    }
+
+    #[test]
+    fn balance_factors() {
+        let empty: BSTree<i32, i32> = BSTree::new();
+        assert!(empty.balance_factors().is_empty());
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k);
+        }
+        let res: Vec<(i32, isize)> = tree.balance_factors().into_iter().map(|(k, b)| (*k, b)).collect();
+        assert_eq!(res, vec![(2, 0), (3, 0), (4, 0), (5, 0), (6, -1), (7, 0), (8, 0), (10, 0), (12, 0), (14, 0), (15, 1), (17, 0)]);
+        let balanced = BSTree::bulk_load((0..4).map(|k| (k, k)));
+        assert!(balanced.balance_factors().iter().all(|(_, b)| b.abs() <= 1));
+    }
+
+    #[test]
+    fn splay_to_root() {
+        let keys = [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14];
+        for target in keys {
+            let mut tree = BSTree::new();
+            for k in keys {
+                tree.insert(k, k * 10);
+            }
+            assert!(tree.splay_to_root(&target));
+            assert_eq!(tree.preorder_iter().next(), Some((&target, &(target * 10))));
+            let res: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+            assert_eq!(res, vec![2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 17]);
+            let before: Vec<i32> = tree.preorder_iter().map(|(k, _)| *k).collect();
+            assert!(!tree.splay_to_root(&11));
+            let after: Vec<i32> = tree.preorder_iter().map(|(k, _)| *k).collect();
+            assert_eq!(before, after);
+        }
+        let mut chain = BSTree::new();
+        for k in 0..64 {
+            chain.insert(k, ());
+        }
+        chain.splay_to_root(&63);
+        assert!(chain.balance_factors().iter().all(|(_, b)| b.abs() <= 33));
+    }
+
+    #[test]
+    fn pop_while() {
+        let keys = [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14];
+        let mut sorted = keys.to_vec();
+        sorted.sort();
+        for stop in 0..=keys.len() {
+            let mut tree = BSTree::new();
+            for k in keys {
+                tree.insert(k, k * 10);
+            }
+            let bound = sorted.get(stop).copied().unwrap_or(i32::MAX);
+            let popped = tree.pop_while(|k, _| *k < bound);
+            let expected: Vec<(i32, i32)> = sorted[..stop].iter().map(|k| (*k, k * 10)).collect();
+            assert_eq!(popped, expected);
+            let rest: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+            assert_eq!(rest, sorted[stop..].to_vec());
+        }
+        let mut tree = BSTree::bulk_load((0..10).map(|k| (k, k % 3)));
+        let popped = tree.pop_while(|_, v| *v != 2);
+        assert_eq!(popped, vec![(0, 0), (1, 1)]);
+        assert_eq!(tree.min_pair(), Some((&2, &2)));
+        assert!(tree.pop_while(|_, _| false).is_empty());
+        assert_eq!(tree.pop_while(|_, _| true).len(), 8);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn neighbors() {
+        let empty: BSTree<i32, i32> = BSTree::new();
+        assert_eq!(empty.neighbors(&1), (None, None));
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k * 10);
+        }
+        for key in 0..20 {
+            let floor = tree.get_pair(&key).or_else(|| tree.predecessor(&key));
+            let ceiling = tree.get_pair(&key).or_else(|| tree.successor(&key));
+            assert_eq!(tree.neighbors(&key), (floor, ceiling));
+        }
+    }
+
+    #[test]
+    fn arena_node_ids() {
+        let keys = [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14];
+        let mut tree = ArenaBSTree::new();
+        let ids: Vec<_> = keys.iter().map(|&k| tree.insert(k, k * 10)).collect();
+        assert_eq!(tree.len(), keys.len());
+        for (k, id) in keys.iter().zip(&ids) {
+            assert_eq!(tree.find(k), Some(*id));
+        }
+        let mut remaining: Vec<i32> = keys.to_vec();
+        for (i, (&k, &id)) in keys.iter().zip(&ids).enumerate() {
+            if i % 2 == 0 {
+                assert_eq!(tree.remove_by_id(id), Some((k, k * 10)));
+                assert!(!tree.contains_id(id));
+                remaining.retain(|&r| r != k);
+            }
+        }
+        remaining.sort();
+        let res: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+        assert_eq!(res, remaining);
+        for (i, (&k, &id)) in keys.iter().zip(&ids).enumerate() {
+            if i % 2 == 1 {
+                assert_eq!(tree.get_by_id(id), Some((&k, &(k * 10))));
+            }
+        }
+        let reused = tree.insert(100, 0);
+        assert!(ids.iter().all(|id| *id != reused));
+        assert_eq!(tree.get_by_id(ids[0]), None);
+        *tree.get_by_id_mut(reused).unwrap() += 5;
+        assert_eq!(tree.take(&100), Some((100, 5)));
+        assert_eq!(tree.len(), remaining.len());
+    }
+
+    #[test]
+    fn parent_of() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k * 10);
+        }
+        let parents: Vec<(Option<&i32>, &i32)> = tree.parent_iter().map(|(p, k, _)| (p, k)).collect();
+        for (parent, key) in parents {
+            assert_eq!(tree.parent_of(key).map(|(k, _)| k), parent);
+        }
+        assert_eq!(tree.parent_of(&7), Some((&6, &60)));
+        assert_eq!(tree.parent_of(&8), None);
+        assert_eq!(tree.parent_of(&11), None);
+    }
+
+    #[test]
+    fn sibling_uncle_of() {
+        /*
+                8
+               / \
+             5    15
+            / \   / \
+          3   6  12  17
+         / \   \  / \
+        2  4   7 10 14
+        */
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, ());
+        }
+        let sibling = |k: i32| tree.sibling_of(&k).map(|(k, _)| *k);
+        let uncle = |k: i32| tree.uncle_of(&k).map(|(k, _)| *k);
+        assert_eq!(sibling(5), Some(15));
+        assert_eq!(sibling(2), Some(4));
+        assert_eq!(sibling(12), Some(17));
+        assert_eq!(sibling(7), None);
+        assert_eq!(sibling(8), None);
+        assert_eq!(sibling(9), None);
+        assert_eq!(uncle(3), Some(15));
+        assert_eq!(uncle(10), Some(17));
+        assert_eq!(uncle(2), Some(6));
+        assert_eq!(uncle(7), Some(3));
+        assert_eq!(uncle(17), Some(5));
+        assert_eq!(uncle(5), None);
+    }
+
+    #[test]
+    fn spines() {
+        let empty: BSTree<i32, i32> = BSTree::new();
+        assert_eq!(empty.left_spine().count(), 0);
+        assert_eq!(empty.right_spine().count(), 0);
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k * 10);
+        }
+        let res: Vec<(&i32, &i32)> = tree.left_spine().collect();
+        assert_eq!(res, vec![(&8, &80), (&5, &50), (&3, &30), (&2, &20)]);
+        let res: Vec<i32> = tree.right_spine().map(|(k, _)| *k).collect();
+        assert_eq!(res, vec![8, 15, 17]);
+        assert_eq!(tree.left_spine().last(), tree.min_pair());
+        assert_eq!(tree.right_spine().last(), tree.max_pair());
+    }
+
+    #[test]
+    fn for_each_panic_keeps_tree() {
+        let mut tree = BSTree::new();
+        for k in [8, 5, 3, 2, 4, 6, 7, 15, 12, 17, 10, 14] {
+            tree.insert(k, k * 10);
+        }
+        let structure = tree.encode_structure();
+        for stop in [2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 15, 17] {
+            let mut seen = Vec::new();
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                tree.for_each(|k, _| {
+                    if *k == stop {
+                        panic!("stop at {}", stop);
+                    }
+                    seen.push(*k);
+                })
+            }));
+            assert!(res.is_err());
+            assert_eq!(seen.len(), tree.inorder_iter().position(|(k, _)| *k == stop).unwrap());
+            assert_eq!(tree.encode_structure(), structure);
+        }
+        let mut sum = 0;
+        tree.for_each(|_, v| sum += v);
+        assert_eq!(sum, 1030);
+    }
+
+    #[test]
+    fn merkle_root_hash_is_pinned() {
+        // 哈希算法、编码和由键决定的秩都是固定的，根哈希不随Rust版本和平台变化
+        let mut tree = MerkleBSTree::new();
+        for i in [2usize, 1, 3] {
+            tree.insert(i, format!("v{}", i));
+        }
+        assert_eq!(tree.root_hash(), 0x7f76_f833_d698_3051);
+    }
+
+    #[test]
+    fn merkle_root_hash_ignores_operation_order() {
+        // 树的形状只取决于键的集合，不同顺序得到相同内容时根哈希相同
+        let mut a = MerkleBSTree::new();
+        let mut b = MerkleBSTree::new();
+        for i in 0..500u32 {
+            a.insert(i, i * 2);
+        }
+        for i in (0..600u32).rev() {
+            b.insert((i * 7) % 600, 0);
+        }
+        for i in 500..600u32 {
+            b.take(&i);
+        }
+        for i in 0..500u32 {
+            b.insert(i, i * 2);
+        }
+        assert_eq!(a.root_hash(), b.root_hash());
+        assert!(a.verify() && b.verify());
+        b.insert(250, 0);
+        assert_ne!(a.root_hash(), b.root_hash());
+
+        // 有序插入大量的键也不会退化成链
+        let mut tree = MerkleBSTree::new();
+        for i in 0..200_000u32 {
+            tree.insert(i, i);
+        }
+        for i in 0..100_000u32 {
+            assert_eq!(tree.take(&(i * 2)), Some((i * 2, i * 2)));
+        }
+        assert!(tree.verify());
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn bstree_nodes_come_from_the_given_allocator() {
+        use an_ok_bstree::{IterativeBSTree, RecursiveBSTree};
+        use std::alloc::{AllocError, Allocator, Global, Layout};
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        // 记录尚未释放的分配次数，其余交给全局分配器
+        #[derive(Clone, Copy)]
+        struct Counting<'a>(&'a Cell<usize>);
+
+        unsafe impl Allocator for Counting<'_> {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.0.set(self.0.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.0.set(self.0.get() - 1);
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        macro_rules! check {
+            ($tree:ident) => {{
+                let live = Cell::new(0);
+                let mut tree = $tree::new_in(Counting(&live));
+                let mut model = BTreeMap::new();
+                for i in 0..50 {
+                    let key = (i * 17) % 23;
+                    if i % 4 == 3 {
+                        assert_eq!(tree.take(&key), model.remove_entry(&key));
+                    } else {
+                        tree.insert(key, i);
+                        model.insert(key, i);
+                    }
+                    // 删除的节点留在节点池中，内存仍由分配器持有
+                    assert_eq!(live.get(), model.len() + tree.pooled_nodes());
+                }
+                tree.shrink_to_fit();
+                assert_eq!(live.get(), model.len());
+                tree.reserve(5);
+                assert_eq!(live.get(), model.len() + 5);
+
+                // 由现有的树派生出的新树使用同一个分配器
+                let doubled = tree.map_values(|_, v| v * 2);
+                assert_eq!(live.get(), model.len());
+                let (even, odd) = doubled.partition(|k, _| k % 2 == 0);
+                assert_eq!(live.get(), model.len());
+                let res: Vec<(i32, i32)> = even.inorder_iter().chain(odd.inorder_iter()).map(|(k, v)| (*k, *v / 2)).collect();
+                let mut expected: Vec<(i32, i32)> = model.iter().filter(|(k, _)| *k % 2 == 0).map(|(k, v)| (*k, *v)).collect();
+                expected.extend(model.iter().filter(|(k, _)| *k % 2 != 0).map(|(k, v)| (*k, *v)));
+                assert_eq!(res, expected);
+                drop(even);
+                drop(odd);
+                assert_eq!(live.get(), 0);
+            }};
+        }
+
+        check!(RecursiveBSTree);
+        check!(IterativeBSTree);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn splay_rotation_counts_agree() {
+        use an_ok_bstree::{metrics, reset_metrics};
+        let keys: Vec<i32> = (0..40).map(|i| (i * 29) % 41).collect();
+        for &target in &keys {
+            let mut recursive = RecursiveBSTree::new();
+            let mut iterative = IterativeBSTree::new();
+            for &k in &keys {
+                recursive.insert(k, ());
+                iterative.insert(k, ());
+            }
+            // 两种实现的旋转次数都等于被伸展的节点的深度
+            let depth = recursive.path_to(&target).count() as u64 - 1;
+            reset_metrics();
+            assert!(recursive.splay_to_root(&target));
+            assert_eq!(metrics().rotations, depth);
+            reset_metrics();
+            assert!(iterative.splay_to_root(&target));
+            assert_eq!(metrics().rotations, depth);
+        }
+    }
+
+    #[test]
+    fn insert_with_policy_panic_keeps_tree() {
+        let keys = [5, 3, 8, 1, 4];
+        for stop in keys {
+            let mut tree = BSTree::new();
+            for k in keys {
+                tree.insert(k, k * 10);
+            }
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                tree.insert_with_policy(stop, 0, |_: &i32, _, _| -> i32 { panic!("policy failed") });
+            }));
+            assert!(res.is_err());
+            // 策略已经拿走了旧值，只丢失这一个键，其余键值对和查找树的性质都保持不变
+            let res: Vec<(i32, i32)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+            let expected: Vec<(i32, i32)> = [1, 3, 4, 5, 8].iter().filter(|&&k| k != stop).map(|&k| (k, k * 10)).collect();
+            assert_eq!(res, expected);
+        }
+
+        let mut tree = BSTree::bulk_load(keys.iter().map(|&k| (k, k * 10)));
+        let other = BSTree::bulk_load(vec![(2, 0), (4, 1), (8, 2), (9, 3)]);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            tree.merge_with(other, |k, old, new| if *k == 8 { panic!("merge failed") } else { old + new });
+        }));
+        assert!(res.is_err());
+        let res: Vec<(i32, i32)> = tree.inorder_iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(res, vec![(1, 10), (2, 0), (3, 30), (4, 41), (5, 50)]);
+    }
+
+    #[test]
+    fn pop_while_panic_keeps_tree() {
+        for stop in [1, 3, 4, 5, 8] {
+            let mut tree = BSTree::new();
+            for k in [5, 3, 8, 1, 4] {
+                tree.insert(k, k * 10);
+            }
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                tree.pop_while(|k, _| if *k == stop { panic!("stop at {}", stop) } else { true })
+            }));
+            assert!(res.is_err());
+            // 已经摘下的键随着panic丢失，stop和比它大的键都留在树中
+            let res: Vec<i32> = tree.inorder_iter().map(|(k, _)| *k).collect();
+            let expected: Vec<i32> = [1, 3, 4, 5, 8].iter().copied().filter(|&k| k >= stop).collect();
+            assert_eq!(res, expected);
+            assert_eq!(tree.get(&stop), Some(&(stop * 10)));
+        }
+    }
+}